@@ -0,0 +1,115 @@
+use crate::filter::Filter;
+use crate::DebugEntry;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// How long the status bar flashes after an alert.
+const FLASH_DURATION: Duration = Duration::from_secs(3);
+
+// Without focus reports from the terminal, udl counts as in the background after this long without
+// a key press.
+const IDLE_AFTER: Duration = Duration::from_secs(30);
+
+// Rings the terminal bell and flashes the status bar for entries matching one of the `--alert`
+// filters, while udl is in the background.
+pub struct Alerts {
+    pub rules: Vec<Filter>,
+    // Whether the terminal has focus, once it reported it.
+    focused: Option<bool>,
+    last_input: Instant,
+    // Label of the entry that raised the last alert, and when.
+    flash: Option<(String, Instant)>,
+}
+
+impl Alerts {
+    pub fn new(rules: Vec<Filter>) -> Alerts {
+        Alerts {
+            rules,
+            focused: None,
+            last_input: Instant::now(),
+            flash: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.rules.is_empty()
+    }
+
+    pub fn set_focus(&mut self, focused: bool) {
+        self.focused = Some(focused);
+        if focused {
+            self.flash = None;
+        }
+    }
+
+    // A key was pressed, so udl has the attention again.
+    pub fn input(&mut self) {
+        self.last_input = Instant::now();
+        self.flash = None;
+    }
+
+    fn in_background(&self) -> bool {
+        match self.focused {
+            Some(focused) => !focused,
+            None => self.last_input.elapsed() >= IDLE_AFTER,
+        }
+    }
+
+    // Whether the entry raises an alert, which then flashes the status bar.
+    pub fn check(&mut self, entry: &DebugEntry) -> bool {
+        if !self.in_background() || !self.rules.iter().any(|rule| rule.matches(entry)) {
+            return false;
+        }
+        self.flash = Some((entry.label.to_string(), Instant::now()));
+
+        true
+    }
+
+    // The label of the entry that raised the alert while flashing, and whether the status bar is lit
+    // right now. It switches every half a second.
+    pub fn flash(&self) -> Option<(&str, bool)> {
+        let (label, since) = self.flash.as_ref()?;
+        let elapsed = since.elapsed();
+        if elapsed >= FLASH_DURATION {
+            return None;
+        }
+
+        Some((label, (elapsed.as_millis() / 500).is_multiple_of(2)))
+    }
+}
+
+// Rings the bell of the terminal.
+pub fn ring_bell<W: Write>(out: &mut W) -> io::Result<()> {
+    out.write_all(b"\x07")?;
+    out.flush()
+}
+
+// Has the terminal report gaining and losing focus, for as long as it lives.
+pub struct FocusTerminal<W: Write> {
+    term: W,
+}
+
+impl<W: Write> From<W> for FocusTerminal<W> {
+    fn from(mut term: W) -> FocusTerminal<W> {
+        // Failing to enable the reports leaves udl without them, alerts then rely on key presses.
+        let _ = term.write_all(b"\x1b[?1004h");
+        FocusTerminal { term }
+    }
+}
+
+impl<W: Write> Drop for FocusTerminal<W> {
+    fn drop(&mut self) {
+        let _ = self.term.write_all(b"\x1b[?1004l");
+        let _ = self.term.flush();
+    }
+}
+
+impl<W: Write> Write for FocusTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.term.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.term.flush()
+    }
+}
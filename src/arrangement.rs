@@ -0,0 +1,114 @@
+use crate::config::{Column, Config};
+use crate::filter;
+use crate::DebugEntry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::PathBuf;
+
+// Order of the entries list by the values at a path instead of newest first, toggled on a column with
+// `O`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Sort {
+    // Path of the values, like `label` or `data.duration`.
+    pub path: String,
+    pub descending: bool,
+}
+
+impl Sort {
+    // Orders two buffered items by their values. Entries without one go last, entries with the same
+    // value stay newest first.
+    pub fn compare(&self, items: &[DebugEntry], a: usize, b: usize) -> Ordering {
+        let value = |index: usize| filter::resolve(&items[index], &self.path).filter(|value| !value.is_null());
+        let order = match (value(a), value(b)) {
+            (Some(first), Some(second)) if self.descending => compare_values(&second, &first),
+            (Some(first), Some(second)) => compare_values(&first, &second),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+
+        order.then(a.cmp(&b))
+    }
+
+    pub fn apply(&self, items: &[DebugEntry], indexes: &mut [usize]) {
+        indexes.sort_by(|a, b| self.compare(items, *a, *b));
+    }
+
+    // Where the buffered item goes within the sorted indexes.
+    pub fn position(&self, items: &[DebugEntry], indexes: &[usize], index: usize) -> usize {
+        indexes.partition_point(|other| self.compare(items, *other, index) == Ordering::Less)
+    }
+}
+
+// Numbers by their value before other values, texts regardless of case.
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(Ordering::Equal),
+        (Value::Number(_), _) => Ordering::Less,
+        (_, Value::Number(_)) => Ordering::Greater,
+        (Value::String(a), Value::String(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (a, b) => a.to_string().cmp(&b.to_string()),
+    }
+}
+
+// Paths of the columns of the entries list, in the order shown: the label of the entry, the
+// configured columns and the tags.
+pub fn column_paths(columns: &[Column]) -> Vec<&str> {
+    let mut paths = vec!["label"];
+    paths.extend(columns.iter().map(|column| column.path.as_str()));
+    paths.push("tags");
+
+    paths
+}
+
+// The order of the configured columns and of the entries, changed with keys and kept in
+// `columns.json` next to the config file so it survives restarting udl.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Arrangement {
+    // Paths of the configured columns, in the order shown.
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub sort: Option<Sort>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Arrangement {
+    // Loads the arrangement, starting with the configured one when there is none or it cannot be read.
+    pub fn load() -> Arrangement {
+        let path = Config::path().and_then(|config| Some(config.parent()?.join("columns.json")));
+        let mut arrangement: Arrangement = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        arrangement.path = path;
+
+        arrangement
+    }
+
+    // Puts the configured columns in the arranged order, columns added to the config since after the
+    // others.
+    pub fn arrange(&self, columns: &mut [Column]) {
+        columns.sort_by_key(|column| self.columns.iter().position(|path| *path == column.path).unwrap_or(usize::MAX));
+    }
+
+    // Remembers the order of the columns and the sort, and saves them.
+    pub fn update(&mut self, columns: &[Column], sort: Option<Sort>) {
+        self.columns = columns.iter().map(|column| column.path.clone()).collect();
+        self.sort = sort;
+
+        // Losing the arrangement is not worth interrupting for.
+        if let Some(path) = &self.path {
+            if let Some(directory) = path.parent() {
+                let _ = fs::create_dir_all(directory);
+            }
+            if let Ok(content) = serde_json::to_string_pretty(self) {
+                let _ = fs::write(path, content);
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::DebugEntry;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use udl_core::timestamp;
+
+// Points beyond this many per series only show the most recent ones.
+const MAX_POINTS: usize = 1000;
+
+// The values of a numeric key over time, for the entries with the same label.
+pub struct Series {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+// The series of each numeric key at the top of the data of the entries with the label, given newest
+// first. Points are at the seconds since the first of these entries, or at their position when an
+// entry has no time that can be read.
+pub fn collect(items: &[DebugEntry], label: &str) -> Vec<Series> {
+    let entries: Vec<&DebugEntry> = items.iter().rev().filter(|item| item.label.as_ref() == label).collect();
+    let times: Option<Vec<f64>> = entries.iter().map(|entry| timestamp::parse(&entry.time)).collect();
+    let start = times.as_ref().and_then(|times| times.first().copied()).unwrap_or(0.0);
+
+    let mut series: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+    for (position, entry) in entries.iter().enumerate() {
+        let x = match &times {
+            Some(times) => times[position] - start,
+            None => position as f64,
+        };
+        for (key, value) in entry.data.iter() {
+            if let Some(y) = value.as_f64() {
+                series.entry(key).or_default().push((x, y));
+            }
+        }
+    }
+
+    series
+        .into_iter()
+        .map(|(name, points)| Series {
+            name: name.to_string(),
+            points: points[points.len().saturating_sub(MAX_POINTS)..].to_vec(),
+        })
+        .collect()
+}
+
+// Whether the entry has a numeric value to plot.
+pub fn has_numbers(entry: &DebugEntry) -> bool {
+    entry.data.values().any(Value::is_number)
+}
+
+// Lowest and highest value of the points, on the axis, padded when they are the same.
+pub fn bounds(series: &[Series], axis: fn(&(f64, f64)) -> f64) -> [f64; 2] {
+    let values = series.iter().flat_map(|series| series.points.iter().map(axis));
+    let (low, high) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)));
+    if low > high {
+        return [0.0, 1.0];
+    }
+    if low == high {
+        return [low - 1.0, high + 1.0];
+    }
+
+    [low, high]
+}
@@ -0,0 +1,397 @@
+use crate::completions;
+use crate::filter::{self, Filter};
+use crate::headless::Format;
+use crate::run::OnNewRun;
+use crate::snippet;
+use crate::theme::Theme;
+use getopts::Options;
+use std::path::PathBuf;
+use std::time::Duration;
+use udl_core::access::{Access, Network};
+use udl_core::decoder;
+use udl_core::listener::DEFAULT_MAX_PAYLOAD_SIZE;
+use udl_core::tail::LineFormat;
+
+// What to do on start, besides listening for new entries.
+pub enum Mode {
+    Listen,
+    // Show a saved session.
+    Open(PathBuf),
+    // Push the entries of a file again, with their original timing.
+    Replay(PathBuf),
+    // Collect entries in the background, without an interface.
+    Daemon,
+    // Show the entries of a running daemon.
+    Attach,
+    // Listen while sending generated entries.
+    Demo,
+    // Show the calls of an Xdebug function trace.
+    ImportXdebug(PathBuf),
+    // Print client code for the language.
+    Snippet(String),
+    // Show a saved session next to the given one, or the entries received, marking what differs.
+    Compare(PathBuf, Option<PathBuf>),
+    // Listen while following a log file, parsing the lines appended to it.
+    Tail(PathBuf, LineFormat),
+    // Send the text, or stdin without any, as an entry to a running udl.
+    Send(Vec<String>),
+    // Print the completion script for the shell.
+    Completions(String),
+    // Print the man page.
+    Manpage,
+}
+
+pub struct Cli {
+    pub mode: Mode,
+    pub host: String,
+    pub port: u16,
+    // Overrides the theme of the config file.
+    pub theme: Option<Theme>,
+    pub filter: Option<Filter>,
+    pub headless: bool,
+    pub format: Format,
+    pub web: Option<String>,
+    pub script: Option<String>,
+    pub notify: Vec<Filter>,
+    pub bell: bool,
+    pub alerts: Vec<Filter>,
+    pub pause_on: Vec<Filter>,
+    pub db: Option<PathBuf>,
+    pub max_entries: Option<usize>,
+    // Entries are removed once kept for this long.
+    pub ttl: Option<Duration>,
+    pub spill: Option<PathBuf>,
+    // Entries received but not added yet, before clients have to wait or entries are dropped.
+    pub queue_size: usize,
+    pub drop_when_full: bool,
+    pub max_payload_size: usize,
+    // Clients allowed to connect and how many at the same time.
+    pub access: Access,
+    // Overrides the decoder of the config file.
+    pub decoder: Option<&'static dyn udl_core::Decoder>,
+    pub speed: f64,
+    pub rate: f64,
+    pub imports: Vec<PathBuf>,
+    pub on_new_run: Option<OnNewRun>,
+    // Read entries from stdin as well, a json payload per line.
+    pub stdin: bool,
+    // Label of the entries sent with `udl send`, and whether every line of stdin is sent separately.
+    pub label: Option<String>,
+    pub lines: bool,
+}
+
+// How an option takes its value.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Takes {
+    Nothing,
+    // A value, named in the help like `PORT`.
+    Value(&'static str),
+    // A value, and the option can be repeated.
+    Values(&'static str),
+}
+
+// An option of udl. The parser, help, shell completions and man page are all built from `OPTIONS`.
+pub struct Opt {
+    pub short: &'static str,
+    pub long: &'static str,
+    pub description: &'static str,
+    pub takes: Takes,
+}
+
+impl Opt {
+    const fn flag(short: &'static str, long: &'static str, description: &'static str) -> Opt {
+        Opt { short, long, description, takes: Takes::Nothing }
+    }
+
+    const fn value(short: &'static str, long: &'static str, description: &'static str, hint: &'static str) -> Opt {
+        Opt { short, long, description, takes: Takes::Value(hint) }
+    }
+
+    const fn values(short: &'static str, long: &'static str, description: &'static str, hint: &'static str) -> Opt {
+        Opt { short, long, description, takes: Takes::Values(hint) }
+    }
+
+    // Whether the value is a path, to complete file names.
+    pub fn takes_file(&self) -> bool {
+        matches!(self.takes, Takes::Value("FILE") | Takes::Values("FILE"))
+    }
+
+    // The values the option accepts, when there are only a few.
+    pub fn choices(&self) -> Vec<&'static str> {
+        match self.long {
+            "theme" => vec!["dark", "light", "terminal"],
+            "decoder" => decoder::DECODERS.iter().map(|decoder| decoder.name()).collect(),
+            "on-new-run" => vec!["clear", "separate"],
+            _ => vec![],
+        }
+    }
+}
+
+pub const OPTIONS: &[Opt] = &[
+    Opt::value("", "host", "Address to listen on (default 127.0.0.1)", "HOST"),
+    Opt::value("p", "port", "Port to listen on (default 9337)", "PORT"),
+    Opt::value("", "theme", "Color theme: dark, light or terminal (default dark)", "THEME"),
+    Opt::value("f", "filter", "Only show entries matching the filter expression", "EXPRESSION"),
+    Opt::flag("", "headless", "Write entries to stdout instead of showing them"),
+    Opt::value("", "format", "Output format with --headless: compact, json, pretty, logfmt or a template like '{time} {label}', or the format of the lines with tail: json, laravel or plain", "FORMAT"),
+    Opt::values("", "notify", "Send a desktop notification for entries matching the filter, can be repeated", "EXPRESSION"),
+    Opt::flag("", "bell", "Ring the terminal bell along with --notify notifications"),
+    Opt::values("", "pause-on", "Pause on entries matching the filter, holding back the ones after it, can be repeated", "EXPRESSION"),
+    Opt::values("", "alert", "Ring the bell and flash the status bar for entries matching the filter while udl is in the background, can be repeated", "EXPRESSION"),
+    Opt::flag("", "stdin", "Read entries from stdin as well, a json payload per line"),
+    Opt::value("", "web", "Serve a page mirroring the entries on this address, like 127.0.0.1:9340", "ADDRESS"),
+    Opt::value("", "script", "Command transforming each entry, see the readme", "COMMAND"),
+    Opt::value("", "db", "Keep every entry in this file and load it again on start", "FILE"),
+    Opt::value("", "max-entries", "Keep at most this many entries in memory", "COUNT"),
+    Opt::value("", "ttl", "Remove entries after this long, like 15m or 1h", "DURATION"),
+    Opt::value("", "spill", "Write entries evicted by --max-entries to this file", "FILE"),
+    Opt::value("", "queue-size", "Entries waiting to be shown before clients have to wait (default 10000)", "COUNT"),
+    Opt::flag("", "drop-when-full", "Drop entries when the queue is full instead of making clients wait"),
+    Opt::values("", "allow", "Only accept connections from this address or network, like 192.168.1.0/24, can be repeated", "NETWORK"),
+    Opt::value("", "max-connections", "Reject connections while this many are open", "COUNT"),
+    Opt::value("", "decoder", "How clients send payloads: json (default), ndjson, length-prefixed, msgpack or cbor", "DECODER"),
+    Opt::value("", "max-payload-size", "Skip payloads larger than this many bytes (default 64 MiB)", "BYTES"),
+    Opt::value("", "speed", "Speed multiplier for replay (default 1)", "MULTIPLIER"),
+    Opt::value("", "rate", "Entries per second for demo (default 2)", "RATE"),
+    Opt::value("", "on-new-run", "Clear or separate the entries when a client starts a new session or request", "clear|separate"),
+    Opt::values("", "import", "Load entries from a session or json lines file, can be repeated", "FILE"),
+    Opt::value("", "label", "Label of the entry sent with send, the first line of the text by default", "LABEL"),
+    Opt::flag("", "lines", "Send every line of stdin as an entry as soon as it is read, with send"),
+    Opt::flag("h", "help", "Print this help"),
+];
+
+// The subcommands, with their arguments and what they do.
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    ("listen", "[OPTIONS]", "Listen for entries and show them, the default"),
+    ("open", "<SESSION> [OPTIONS]", "Show a saved session"),
+    ("replay", "<FILE> [OPTIONS]", "Push the entries of a file again, with their original timing"),
+    ("daemon", "[OPTIONS]", "Collect entries in the background, without an interface"),
+    ("attach", "[OPTIONS]", "Show the entries of a running daemon"),
+    ("demo", "[OPTIONS]", "Listen while sending generated entries"),
+    ("import-xdebug", "<TRACE> [OPTIONS]", "Show the calls of an Xdebug function trace"),
+    ("compare", "<BASELINE> [SESSION] [OPTIONS]", "Show a saved session next to another one, or the entries received, marking what differs"),
+    ("tail", "<FILE> [--format json|laravel|plain] [OPTIONS]", "Listen while following a log file, parsing the lines appended to it"),
+    ("snippet", "<LANGUAGE> [--host HOST] [--port PORT]", "Print client code for the language"),
+    ("send", "[TEXT] [--label LABEL] [--lines] [--host HOST] [--port PORT]", "Send the text, or stdin without any, as an entry to a running udl"),
+    ("completions", "<SHELL>", "Print the completion script for bash, zsh or fish"),
+    ("manpage", "", "Print the man page"),
+];
+
+fn options() -> Options {
+    let mut options = Options::new();
+    for option in OPTIONS {
+        match option.takes {
+            Takes::Nothing => options.optflag(option.short, option.long, option.description),
+            Takes::Value(hint) => options.optopt(option.short, option.long, option.description, hint),
+            Takes::Values(hint) => options.optmulti(option.short, option.long, option.description, hint),
+        };
+    }
+    options
+}
+
+pub fn usage() -> String {
+    let mut brief = String::from("Usage:");
+    for (name, arguments, _) in COMMANDS {
+        // Listening needs no subcommand.
+        let name = if *name == "listen" { "[listen]" } else { name };
+        brief.push_str(format!("\n    udl {} {}", name, arguments).trim_end());
+    }
+    options().usage(&brief)
+}
+
+impl Cli {
+    // Parses the arguments without the program name. Returns `None` when help was requested.
+    pub fn parse(args: &[String]) -> Result<Option<Cli>, String> {
+        let matches = options().parse(args).map_err(|error| error.to_string())?;
+        if matches.opt_present("help") {
+            return Ok(None);
+        }
+
+        let mut port: u16 = 9337;
+        let mut free = matches.free.iter();
+        let mut mode = match free.next().map(String::as_str) {
+            None | Some("listen") => Mode::Listen,
+            Some("open") => Mode::Open(PathBuf::from(free.next().ok_or("open requires a session file")?)),
+            Some("replay") => Mode::Replay(PathBuf::from(free.next().ok_or("replay requires a file")?)),
+            Some("daemon") => Mode::Daemon,
+            Some("attach") => Mode::Attach,
+            Some("demo") => Mode::Demo,
+            Some("import-xdebug") => {
+                Mode::ImportXdebug(PathBuf::from(free.next().ok_or("import-xdebug requires a trace file")?))
+            }
+            Some("compare") => Mode::Compare(
+                PathBuf::from(free.next().ok_or("compare requires a baseline session file")?),
+                free.next().map(PathBuf::from),
+            ),
+            // The format of the lines is set below, from --format.
+            Some("tail") => Mode::Tail(PathBuf::from(free.next().ok_or("tail requires a log file")?), LineFormat::Json),
+            Some("snippet") => Mode::Snippet(
+                free.next()
+                    .ok_or_else(|| format!("snippet requires a language: {}", snippet::LANGUAGES.join(", ")))?
+                    .to_string(),
+            ),
+            // The rest of the arguments are the text to send.
+            Some("send") => Mode::Send(free.by_ref().cloned().collect()),
+            Some("completions") => Mode::Completions(
+                free.next()
+                    .ok_or_else(|| format!("completions requires a shell: {}", completions::SHELLS.join(", ")))?
+                    .to_string(),
+            ),
+            Some("manpage") => Mode::Manpage,
+            // A port on its own, as accepted before there were subcommands.
+            Some(argument) if argument.chars().all(|c| c.is_ascii_digit()) => {
+                port = parse_port(argument)?;
+                Mode::Listen
+            }
+            Some(argument) => return Err(format!("Unknown command: {}", argument)),
+        };
+        if let Some(argument) = free.next() {
+            return Err(format!("Unexpected argument: {}", argument));
+        }
+
+        if let Some(value) = matches.opt_str("port") {
+            port = parse_port(&value)?;
+        }
+
+        let theme = match matches.opt_str("theme") {
+            Some(name) => Some(Theme::named(&name).ok_or_else(|| format!("Unknown theme: {}", name))?),
+            None => None,
+        };
+
+        let format = match (&mut mode, matches.opt_str("format")) {
+            (Mode::Tail(_, line_format), Some(name)) => {
+                *line_format = LineFormat::named(&name).ok_or_else(|| format!("Unknown line format: {}, use json, laravel or plain", name))?;
+                Format::Compact
+            }
+            (_, Some(name)) => Format::named(&name).ok_or_else(|| format!("Unknown format: {}", name))?,
+            (_, None) => Format::Compact,
+        };
+
+        let max_entries = match matches.opt_str("max-entries") {
+            Some(value) => match value.parse() {
+                Ok(count) if count > 0 => Some(count),
+                _ => return Err(format!("Invalid --max-entries: {}", value)),
+            },
+            None => None,
+        };
+
+        let ttl = match matches.opt_str("ttl") {
+            Some(value) => match filter::parse_duration(&value) {
+                // Shorter ones would remove entries before they can be seen.
+                Some(seconds) if seconds >= 1.0 => match Duration::try_from_secs_f64(seconds) {
+                    Ok(ttl) => Some(ttl),
+                    Err(_) => return Err(format!("Invalid --ttl: {}", value)),
+                },
+                _ => return Err(format!("Invalid --ttl: {}, use at least 1s", value)),
+            },
+            None => None,
+        };
+
+        let queue_size = match matches.opt_str("queue-size") {
+            Some(value) => match value.parse() {
+                Ok(size) if size > 0 => size,
+                _ => return Err(format!("Invalid --queue-size: {}", value)),
+            },
+            None => 10000,
+        };
+
+        let decoder = match matches.opt_str("decoder") {
+            Some(name) => Some(decoder::named(&name).ok_or_else(|| format!("Unknown decoder: {}", name))?),
+            None => None,
+        };
+
+        let max_payload_size = match matches.opt_str("max-payload-size") {
+            Some(value) => match value.parse() {
+                Ok(size) if size > 0 => size,
+                _ => return Err(format!("Invalid --max-payload-size: {}", value)),
+            },
+            None => DEFAULT_MAX_PAYLOAD_SIZE,
+        };
+
+        let allowed = matches.opt_strs("allow").iter().map(|network| Network::parse(network)).collect::<Result<_, _>>()?;
+        let max_connections = match matches.opt_str("max-connections") {
+            Some(value) => match value.parse() {
+                Ok(count) if count > 0 => Some(count),
+                _ => return Err(format!("Invalid --max-connections: {}", value)),
+            },
+            None => None,
+        };
+
+        let on_new_run = match matches.opt_str("on-new-run") {
+            Some(name) => Some(OnNewRun::named(&name).ok_or_else(|| format!("Invalid --on-new-run: {}, use clear or separate", name))?),
+            None => None,
+        };
+
+        let speed: f64 = match matches.opt_str("speed") {
+            Some(value) => value.parse().map_err(|_| format!("Invalid --speed: {}", value))?,
+            None => 1.0,
+        };
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(String::from("--speed must be a number greater than zero"));
+        }
+
+        let rate: f64 = match matches.opt_str("rate") {
+            Some(value) => value.parse().map_err(|_| format!("Invalid --rate: {}", value))?,
+            None => 2.0,
+        };
+        // The pause between entries has to fit a Duration as well.
+        if !(rate.is_finite() && rate > 0.0) || Duration::try_from_secs_f64(1.0 / rate).is_err() {
+            return Err(String::from("--rate must be a number greater than zero"));
+        }
+
+        Ok(Some(Cli {
+            mode,
+            host: matches.opt_str("host").unwrap_or_else(|| String::from("127.0.0.1")),
+            port,
+            theme,
+            filter: matches.opt_str("filter").map(|source| Filter::parse(&source)).transpose()?,
+            headless: matches.opt_present("headless"),
+            format,
+            web: matches.opt_str("web"),
+            script: matches.opt_str("script"),
+            notify: matches
+                .opt_strs("notify")
+                .iter()
+                .map(|source| Filter::parse(source))
+                .collect::<Result<_, _>>()?,
+            bell: matches.opt_present("bell"),
+            alerts: matches
+                .opt_strs("alert")
+                .iter()
+                .map(|source| Filter::parse(source))
+                .collect::<Result<_, _>>()?,
+            pause_on: matches
+                .opt_strs("pause-on")
+                .iter()
+                .map(|source| Filter::parse(source))
+                .collect::<Result<_, _>>()?,
+            db: matches.opt_str("db").map(PathBuf::from),
+            max_entries,
+            ttl,
+            spill: matches.opt_str("spill").map(PathBuf::from),
+            queue_size,
+            drop_when_full: matches.opt_present("drop-when-full"),
+            max_payload_size,
+            access: Access::new(allowed, max_connections),
+            decoder,
+            speed,
+            rate,
+            imports: matches.opt_strs("import").into_iter().map(PathBuf::from).collect(),
+            on_new_run,
+            stdin: matches.opt_present("stdin"),
+            label: matches.opt_str("label"),
+            lines: matches.opt_present("lines"),
+        }))
+    }
+
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+}
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.parse() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("Invalid port: {}, expected a number between 1 and 65535", value)),
+    }
+}
@@ -0,0 +1,9 @@
+use std::io::{self, Write};
+use udl_core::base64;
+
+// Copies the text to the clipboard with an OSC 52 escape sequence, which the terminal handles, so it
+// also works over ssh. Not every terminal supports it.
+pub fn copy<W: Write>(out: &mut W, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", base64::encode(text.as_bytes()))?;
+    out.flush()
+}
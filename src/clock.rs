@@ -0,0 +1,78 @@
+use serde::Deserialize;
+use udl_core::timestamp;
+
+// How entry times are shown, cycled with `Z` or set with `time_zone` in the config.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeZone {
+    // As the client sent it.
+    #[default]
+    Original,
+    Utc,
+    Local,
+}
+
+impl TimeZone {
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeZone::Original => "as sent",
+            TimeZone::Utc => "UTC",
+            TimeZone::Local => "local time",
+        }
+    }
+
+    pub fn next(self) -> TimeZone {
+        match self {
+            TimeZone::Original => TimeZone::Utc,
+            TimeZone::Utc => TimeZone::Local,
+            TimeZone::Local => TimeZone::Original,
+        }
+    }
+
+    // The time as shown in the time zone, as seconds since the unix epoch, to compare with a time
+    // typed as shown.
+    pub fn wall_clock(self, time: &str) -> Option<f64> {
+        let seconds = timestamp::parse(time)?;
+        Some(match self {
+            TimeZone::Original => seconds + timestamp::offset(time) as f64,
+            TimeZone::Utc => seconds,
+            TimeZone::Local => seconds + local_offset(seconds as i64) as f64,
+        })
+    }
+
+    // The time in the time zone with milliseconds, or as sent when it cannot be read.
+    pub fn display(self, time: &str) -> String {
+        let seconds = match timestamp::parse(time) {
+            Some(seconds) if self != TimeZone::Original => seconds,
+            _ => return time.to_string(),
+        };
+
+        match self {
+            TimeZone::Local => {
+                let offset = local_offset(seconds as i64);
+                let sign = if offset < 0 { '-' } else { '+' };
+                let minutes = offset.abs() / 60;
+                format!("{} {}{:02}:{:02}", timestamp::format_millis(seconds, offset), sign, minutes / 60, minutes % 60)
+            }
+            _ => format!("{} UTC", timestamp::format_millis(seconds, 0)),
+        }
+    }
+}
+
+// Seconds the local time zone is ahead of UTC at the time, following daylight saving time.
+#[cfg(unix)]
+fn local_offset(seconds: i64) -> i64 {
+    let time = seconds as libc::time_t;
+    let mut local: libc::tm = unsafe { std::mem::zeroed() };
+    // Fills `local` for the time, without touching shared state like `localtime` does.
+    if unsafe { libc::localtime_r(&time, &mut local) }.is_null() {
+        return 0;
+    }
+
+    local.tm_gmtoff as i64
+}
+
+#[cfg(not(unix))]
+fn local_offset(_seconds: i64) -> i64 {
+    0
+}
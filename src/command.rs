@@ -0,0 +1,81 @@
+use crate::filter::Filter;
+use termion::event::Key;
+
+// Commands that can be typed in the command palette, opened with `:`.
+pub enum Command {
+    // `filter <expression>` shows only the matching entries, `filter` without one clears it.
+    // `tag <name>` is a shorthand for filtering on a tag, `search <text>` for entries containing the text.
+    Filter(Option<Filter>),
+    // `mark [name]` adds a marker entry, to show only what came after it with `since marker`.
+    Mark(Option<String>),
+    // `watch <path>` follows the value at a path like `data.cart.total` across the entries.
+    Watch(String),
+    // `unwatch [path]` stops following the path, or all of them.
+    Unwatch(Option<String>),
+    // `goto <id>` selects the entry with the id, like `12` or `#12`, or the newest with the `ref`.
+    // `goto 14:32:05` selects the first entry at or after the time.
+    Goto(String),
+    // `unmute` unmutes all sources and resumes those held back.
+    Unmute,
+    // `follow [on]` resumes the list and selects the newest entry, `follow off` pauses it like `P`.
+    Follow(bool),
+    // `summary [file]` writes a summary of the session to the file, `summary.md` by default.
+    Summary(String),
+}
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Command, String> {
+        let input = input.trim();
+        let (name, argument) = match input.find(char::is_whitespace) {
+            Some(index) => (&input[..index], input[index..].trim()),
+            None => (input, ""),
+        };
+
+        match name {
+            "filter" if argument.is_empty() => Ok(Command::Filter(None)),
+            "filter" => Ok(Command::Filter(Some(Filter::parse(argument)?))),
+            "tag" if argument.is_empty() => Err(String::from("tag requires a tag name")),
+            "tag" => Ok(Command::Filter(Some(Filter::parse(&format!("tags = \"{}\"", argument.replace('"', "")))?))),
+            "search" if argument.is_empty() => Ok(Command::Filter(None)),
+            "search" => Ok(Command::Filter(Some(Filter::text(argument)))),
+            "mark" => Ok(Command::Mark(Some(argument.to_string()).filter(|name| !name.is_empty()))),
+            "watch" if argument.is_empty() => Err(String::from("watch requires a path, like data.cart.total")),
+            "watch" => Ok(Command::Watch(argument.to_string())),
+            "goto" if argument.is_empty() => Err(String::from("goto requires an entry id, ref or time")),
+            "goto" => Ok(Command::Goto(argument.to_string())),
+            "unmute" => Ok(Command::Unmute),
+            "summary" if argument.is_empty() => Ok(Command::Summary(String::from("summary.md"))),
+            "summary" => Ok(Command::Summary(argument.to_string())),
+            "follow" if matches!(argument, "" | "on") => Ok(Command::Follow(true)),
+            "follow" if argument == "off" => Ok(Command::Follow(false)),
+            "follow" => Err(String::from("follow takes on or off")),
+            "unwatch" => Ok(Command::Unwatch(Some(argument.to_string()).filter(|path| !path.is_empty()))),
+            "" => Err(String::from("No command given")),
+            _ => Err(format!("Unknown command: {}", name)),
+        }
+    }
+}
+
+// A key as named in the `keys` of the config: a single char like `x`, `F1` to `F12`, `ctrl-x` or
+// `alt-x`.
+pub fn parse_key(name: &str) -> Result<Key, String> {
+    let single = |text: &str| {
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) => Some(char),
+            _ => None,
+        }
+    };
+    let lowercase = name.to_lowercase();
+    let key = match (single(name), lowercase.strip_prefix("ctrl-"), lowercase.strip_prefix("alt-")) {
+        (Some(char), _, _) => Some(Key::Char(char)),
+        (_, Some(rest), _) => single(rest).map(Key::Ctrl),
+        (_, _, Some(_)) => single(&name[4..]).map(Key::Alt),
+        _ => match lowercase.strip_prefix('f').and_then(|number| number.parse().ok()) {
+            Some(number @ 1..=12) => Some(Key::F(number)),
+            _ => None,
+        },
+    };
+
+    key.ok_or_else(|| format!("Unknown key {:?}, use a char like x, F1 to F12, ctrl-x or alt-x", name))
+}
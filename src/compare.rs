@@ -0,0 +1,215 @@
+use crate::DebugEntry;
+use std::collections::HashMap;
+
+// How an entry compares to its counterpart in the baseline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Same,
+    Changed,
+    // Not in the baseline.
+    Added,
+}
+
+impl Status {
+    pub fn marker(self) -> &'static str {
+        match self {
+            Status::Same => "  ",
+            Status::Changed => "~ ",
+            Status::Added => "+ ",
+        }
+    }
+}
+
+// A saved session to compare the entries with, shown next to them.
+pub struct Comparison {
+    pub name: String,
+    // Newest first, like the entries list.
+    pub baseline: Vec<DebugEntry>,
+    // Selection and first row shown of the baseline list, following the selected entry.
+    pub selected: Option<usize>,
+    pub offset: usize,
+    // Index in the baseline of the counterpart of each entry by id, and whether it differs.
+    pairs: HashMap<u64, Option<(usize, bool)>>,
+    // Whether each baseline entry has a counterpart.
+    paired: Vec<bool>,
+    // Whether an entry differs from a baseline entry, by id and baseline index, as comparing large
+    // entries again for every new one is slow.
+    differs: HashMap<(u64, usize), bool>,
+    // Number of entries and table revision the pairs were made for.
+    paired_for: Option<(usize, u64)>,
+}
+
+impl Comparison {
+    // Takes the baseline entries in order of arrival.
+    pub fn new(name: String, mut baseline: Vec<DebugEntry>) -> Comparison {
+        baseline.reverse();
+        let paired = vec![false; baseline.len()];
+
+        Comparison {
+            name,
+            baseline,
+            selected: None,
+            offset: 0,
+            pairs: HashMap::new(),
+            paired,
+            differs: HashMap::new(),
+            paired_for: None,
+        }
+    }
+
+    // Pairs the entries, given newest first, with the baseline. The nth entry with a label is the
+    // counterpart of the nth baseline entry with that label, so entries added or left out in between
+    // do not shift the rest.
+    pub fn pair(&mut self, items: &[DebugEntry], revision: u64) {
+        if self.paired_for == Some((items.len(), revision)) {
+            return;
+        }
+        // Entries already in the list changed.
+        if self.paired_for.is_some_and(|(_, paired_revision)| paired_revision != revision) {
+            self.differs.clear();
+        }
+        self.paired_for = Some((items.len(), revision));
+
+        let mut by_label: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, entry) in self.baseline.iter().enumerate().rev() {
+            by_label.entry(&entry.label).or_default().push(index);
+        }
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        self.pairs.clear();
+        self.paired = vec![false; self.baseline.len()];
+        for entry in items.iter().rev() {
+            let occurrence = seen.entry(&entry.label).or_insert(0);
+            let counterpart = by_label.get(entry.label.as_ref()).and_then(|indexes| indexes.get(*occurrence)).copied();
+            *occurrence += 1;
+
+            if let Some(index) = counterpart {
+                self.paired[index] = true;
+            }
+            let (baseline, differs) = (&self.baseline, &mut self.differs);
+            let pair = counterpart.map(|index| {
+                let differs = *differs
+                    .entry((entry.id, index))
+                    .or_insert_with(|| comparable_lines(entry) != comparable_lines(&baseline[index]));
+                (index, differs)
+            });
+            self.pairs.insert(entry.id, pair);
+        }
+    }
+
+    pub fn counterpart(&self, entry: &DebugEntry) -> Option<&DebugEntry> {
+        let (index, _) = (*self.pairs.get(&entry.id)?)?;
+        self.baseline.get(index)
+    }
+
+    pub fn status(&self, entry: &DebugEntry) -> Status {
+        match self.pairs.get(&entry.id) {
+            Some(Some((_, true))) => Status::Changed,
+            Some(Some((_, false))) => Status::Same,
+            _ => Status::Added,
+        }
+    }
+
+    // Selects the counterpart of the selected entry in the baseline list.
+    pub fn follow(&mut self, entry: Option<&DebugEntry>) {
+        if let Some(entry) = entry {
+            self.selected = self.pairs.get(&entry.id).copied().flatten().map(|(index, _)| index);
+        }
+    }
+
+    // Whether the baseline entry has no counterpart among the entries.
+    pub fn is_missing(&self, index: usize) -> bool {
+        !self.paired.get(index).copied().unwrap_or(true)
+    }
+
+    // Numbers of changed, added and missing entries.
+    pub fn summary(&self) -> (usize, usize, usize) {
+        let changed = self.pairs.values().filter(|pair| matches!(pair, Some((_, true)))).count();
+        let added = self.pairs.values().filter(|pair| pair.is_none()).count();
+        let missing = self.paired.iter().filter(|paired| !**paired).count();
+
+        (changed, added, missing)
+    }
+}
+
+// A line of the difference between an entry and its counterpart.
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+// The label, data and backtrace of the entry as lines, leaving out what differs on every run like
+// the time and id.
+pub fn comparable_lines(entry: &DebugEntry) -> Vec<String> {
+    let mut lines = vec![format!("label: {}", entry.label)];
+    // Json objects are sorted by key, so the same data gives the same lines.
+    let data = serde_json::to_value(&entry.data).unwrap_or_default();
+    lines.extend(serde_json::to_string_pretty(&data).unwrap_or_default().lines().map(str::to_string));
+    lines.extend(
+        entry
+            .backtrace
+            .iter()
+            .map(|frame| format!("at {}:{} {}", frame.file, frame.line, frame.function)),
+    );
+
+    lines
+}
+
+// Lines beyond which the differences are not searched, the table for it grows with the square.
+const MAX_DIFF_LINES: usize = 1000;
+
+// Differences between the lines, keeping the longest run of lines both have in common.
+pub fn diff(old: &[String], new: &[String]) -> Vec<Line> {
+    let prefix = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let mut lines: Vec<Line> = old[..prefix].iter().cloned().map(Line::Same).collect();
+    let (old_rest, new_rest) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+    if old_rest.len() > MAX_DIFF_LINES || new_rest.len() > MAX_DIFF_LINES {
+        lines.extend(old_rest.iter().cloned().map(Line::Removed));
+        lines.extend(new_rest.iter().cloned().map(Line::Added));
+    } else {
+        lines.extend(diff_lines(old_rest, new_rest));
+    }
+    lines.extend(old[old.len() - suffix..].iter().cloned().map(Line::Same));
+
+    lines
+}
+
+fn diff_lines(old: &[String], new: &[String]) -> Vec<Line> {
+    // Lengths of the longest common subsequences of the remaining lines.
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Line::Same(old[i].clone()));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            lines.push(Line::Removed(old[i].clone()));
+            i += 1;
+        } else {
+            lines.push(Line::Added(new[j].clone()));
+            j += 1;
+        }
+    }
+
+    lines
+}
@@ -0,0 +1,185 @@
+use crate::cli::{Opt, Takes, COMMANDS, OPTIONS};
+use crate::snippet;
+
+// Completion scripts printed by `udl completions <shell>`, built from the options and subcommands
+// of `cli.rs` so they stay in sync with the parser.
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+// Subcommands taking a file.
+const FILE_COMMANDS: [&str; 5] = ["open", "replay", "import-xdebug", "compare", "tail"];
+
+pub fn script(shell: &str) -> Option<String> {
+    match shell {
+        "bash" => Some(bash()),
+        "zsh" => Some(zsh()),
+        "fish" => Some(fish()),
+        _ => None,
+    }
+}
+
+// The words completing the argument of a subcommand, like the languages of `snippet`.
+fn arguments(command: &str) -> Vec<&'static str> {
+    match command {
+        "snippet" => snippet::LANGUAGES.to_vec(),
+        "completions" => SHELLS.to_vec(),
+        _ => vec![],
+    }
+}
+
+fn names(option: &Opt) -> Vec<String> {
+    let mut names = vec![format!("--{}", option.long)];
+    if !option.short.is_empty() {
+        names.insert(0, format!("-{}", option.short));
+    }
+    names
+}
+
+fn bash() -> String {
+    let mut values = String::new();
+    for option in OPTIONS.iter().filter(|option| option.takes != Takes::Nothing) {
+        let completion = if option.takes_file() {
+            String::from("compgen -f -- \"$cur\"")
+        } else {
+            format!("compgen -W \"{}\" -- \"$cur\"", option.choices().join(" "))
+        };
+        values.push_str(&format!("        {})\n            COMPREPLY=($({}))\n            return ;;\n", names(option).join("|"), completion));
+    }
+    let options: Vec<String> = OPTIONS.iter().flat_map(names).collect();
+    let commands: Vec<&str> = COMMANDS.iter().map(|(name, _, _)| *name).collect();
+    let mut arguments_cases = format!("        {})\n            COMPREPLY=($(compgen -f -- \"$cur\")) ;;\n", FILE_COMMANDS.join("|"));
+    for (command, _, _) in COMMANDS {
+        let words = arguments(command);
+        if !words.is_empty() {
+            arguments_cases.push_str(&format!("        {})\n            COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n", command, words.join(" ")));
+        }
+    }
+
+    format!(
+        r#"# bash completion for udl, generated by `udl completions bash`.
+_udl() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    local prev="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$prev" in
+{}    esac
+    if [[ "$cur" == -* ]]; then
+        COMPREPLY=($(compgen -W "{}" -- "$cur"))
+        return
+    fi
+    if [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{}" -- "$cur"))
+        return
+    fi
+    case "${{COMP_WORDS[1]}}" in
+{}    esac
+}}
+complete -F _udl udl
+"#,
+        values,
+        options.join(" "),
+        commands.join(" "),
+        arguments_cases
+    )
+}
+
+// Escapes the text for a zsh `_arguments` spec in single quotes.
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''").replace('[', "\\[").replace(']', "\\]").replace(':', "\\:")
+}
+
+fn zsh() -> String {
+    let mut specs = String::new();
+    for option in OPTIONS {
+        let names = names(option);
+        let repeat = if matches!(option.takes, Takes::Values(_)) { "*" } else { "" };
+        let exclusive = if names.len() > 1 { format!("({})", names.join(" ")) } else { String::new() };
+        let value = match option.takes {
+            Takes::Nothing => String::new(),
+            Takes::Value(hint) | Takes::Values(hint) if option.takes_file() => format!(":{}:_files", hint),
+            Takes::Value(hint) | Takes::Values(hint) if option.choices().is_empty() => format!(":{}: ", hint),
+            Takes::Value(hint) | Takes::Values(hint) => format!(":{}:({})", hint, option.choices().join(" ")),
+        };
+        for name in &names {
+            specs.push_str(&format!(
+                "    '{}{}{}[{}]{}' \\\n",
+                exclusive,
+                repeat,
+                name,
+                zsh_escape(option.description),
+                value
+            ));
+        }
+    }
+    let commands: Vec<String> =
+        COMMANDS.iter().map(|(name, _, description)| format!("        '{}:{}'", name, zsh_escape(description))).collect();
+    let mut arguments_cases = format!("                {}) _files ;;\n", FILE_COMMANDS.join("|"));
+    for (command, _, _) in COMMANDS {
+        let words = arguments(command);
+        if !words.is_empty() {
+            arguments_cases.push_str(&format!("                {}) _values {} {} ;;\n", command, command, words.join(" ")));
+        }
+    }
+
+    format!(
+        r#"#compdef udl
+# zsh completion for udl, generated by `udl completions zsh`.
+_udl() {{
+    local -a commands
+    commands=(
+{}
+    )
+    _arguments -s \
+{}    '1: :->command' \
+    '*:: :->arguments'
+    case $state in
+        command) _describe command commands ;;
+        arguments)
+            case $words[1] in
+{}            esac
+            ;;
+    esac
+}}
+_udl "$@"
+"#,
+        commands.join("\n"),
+        specs,
+        arguments_cases
+    )
+}
+
+// Quotes the text for fish.
+fn fish_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn fish() -> String {
+    let mut script = String::from("# fish completion for udl, generated by `udl completions fish`.\ncomplete -c udl -f\n");
+    for (name, _, description) in COMMANDS {
+        script.push_str(&format!("complete -c udl -n __fish_use_subcommand -a {} -d {}\n", name, fish_quote(description)));
+    }
+    script.push_str(&format!("complete -c udl -n '__fish_seen_subcommand_from {}' -F\n", FILE_COMMANDS.join(" ")));
+    for (command, _, _) in COMMANDS {
+        let words = arguments(command);
+        if !words.is_empty() {
+            script.push_str(&format!("complete -c udl -n '__fish_seen_subcommand_from {}' -a '{}'\n", command, words.join(" ")));
+        }
+    }
+    for option in OPTIONS {
+        let mut line = String::from("complete -c udl");
+        if !option.short.is_empty() {
+            line.push_str(&format!(" -s {}", option.short));
+        }
+        line.push_str(&format!(" -l {} -d {}", option.long, fish_quote(option.description)));
+        if option.takes != Takes::Nothing {
+            line.push_str(" -r");
+            if option.takes_file() {
+                line.push_str(" -F");
+            } else if !option.choices().is_empty() {
+                line.push_str(&format!(" -a '{}'", option.choices().join(" ")));
+            }
+        }
+        script.push_str(&line);
+        script.push('\n');
+    }
+
+    script
+}
@@ -0,0 +1,351 @@
+use async_std::channel::Sender;
+use async_std::task;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{env, fs};
+use termion::event::Key;
+use udl_core::decoder::{self, Decoder};
+
+use crate::command::{self, Command};
+use crate::density::{Density, Truncation};
+use crate::editor;
+use crate::sourcemap::SourceMaps;
+use crate::BacktraceItem;
+use crate::filter::Filter;
+use crate::clock::TimeZone;
+use crate::highlight::HighlightRule;
+use crate::hook::Hook;
+use crate::kind::{Kind, TypeMarker};
+use crate::label::LabelRule;
+use crate::number::NumberFormat;
+use crate::preset::Preset;
+use crate::redact::Redaction;
+use crate::theme::Theme;
+use crate::util::event::AppEvent;
+use crate::webhook::Webhook;
+
+// Levels of nested objects shown in the details unless configured otherwise.
+const DEFAULT_EXPAND_DEPTH: usize = 4;
+
+// Entries with more bytes of data than this are flagged in the list unless configured otherwise.
+const DEFAULT_LARGE_ENTRY_SIZE: usize = 100 * 1024;
+
+// Latest entries whose payload is kept as received unless configured otherwise.
+const DEFAULT_RAW_PAYLOADS: usize = 100;
+
+// User configuration, read from `~/.config/udl/config.json` or the file in `UDL_CONFIG`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // Backtrace frames with a file containing one of these are collapsed by default.
+    pub vendor_patterns: Vec<String>,
+    // Rewrites of file path prefixes, for clients running in containers or on other machines.
+    pub path_mappings: Vec<PathMapping>,
+    // Directory of the project, backtrace files within it are shown relative to it and its first
+    // frame is selected.
+    pub project_root: Option<String>,
+    // Url for OSC 8 hyperlinks on backtrace files, with `{file}` and `{line}` placeholders.
+    pub hyperlink_template: Option<String>,
+    // Name of a built-in editor preset, see `editor.rs`.
+    pub editor: Option<String>,
+    // Command opening a backtrace file, with `{file}` and `{line}` placeholders, instead of the preset.
+    pub editor_command: Option<String>,
+    // Theme name, used when `--theme` is not given.
+    pub theme: Option<String>,
+    // How clients send payloads, used when `--decoder` is not given.
+    pub decoder: Option<String>,
+    // Filter expression applied at start, used when `--filter` is not given.
+    pub filter: Option<String>,
+    // Slack or Discord webhooks receiving the matching entries.
+    pub webhooks: Vec<Webhook>,
+    // Commands run on the matching entries.
+    pub hooks: Vec<Hook>,
+    // Values to color in the details pane, and optionally the rows of their entries.
+    pub highlights: Vec<HighlightRule>,
+    // Directory with source maps, `<file>.map`, for the frames of bundled javascript.
+    pub source_maps: Option<String>,
+    // Reports mouse clicks, to open the actions of an entry with a right click. Selecting text then
+    // needs shift in most terminals.
+    pub mouse: bool,
+    // Extra columns of the entries list, with values from the entries.
+    pub columns: Vec<Column>,
+    // Spacing of the rows of the entries list, and where long labels are cut off.
+    pub density: Density,
+    pub truncate_labels: Truncation,
+    // How the numbers at paths of the data are shown, see `number.rs`.
+    pub number_formats: Vec<NumberFormat>,
+    // Levels of nested objects shown in the details, deeper ones are collapsed. `None` shows all.
+    pub expand_depth: Option<usize>,
+    // Bytes of data above which an entry is flagged in the list.
+    pub large_entry_size: usize,
+    // Latest entries to keep the payload of as received, for the raw view.
+    pub raw_payloads: usize,
+    // Paths followed across the entries from the start, like `watch` in the command palette.
+    pub watches: Vec<String>,
+    // Arrangement of the panes at start, see `preset.rs`.
+    pub layout: Preset,
+    // Time zone the times of the entries are shown in.
+    pub time_zone: TimeZone,
+    // Sensitive values masked as entries are received.
+    pub redact: Redaction,
+    // Rewrites of the labels of entries as they are received, see `label.rs`.
+    pub labels: Vec<LabelRule>,
+    // Paths shown as a table above the data of the entries with a label, like
+    // `"query": ["data.duration", "connection.name"]`.
+    pub summary_fields: HashMap<String, Vec<String>>,
+    // Print a summary of the session on quitting, see `summary.rs`.
+    pub summary_on_exit: bool,
+    // Glyphs and colors of the markers of the entry types in the list, instead of the default ones.
+    pub type_markers: HashMap<Kind, TypeMarker>,
+    // Keys running a sequence of palette commands, like `"F2": ["filter level = error", "follow on"]`.
+    pub keys: HashMap<String, Vec<String>>,
+    #[serde(skip)]
+    source_map_cache: SourceMaps,
+}
+
+// A column of the entries list showing the value at a filter path, like `data.duration`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Column {
+    pub path: String,
+    // Heading of the column, the path without one.
+    pub title: Option<String>,
+    // Width in characters.
+    #[serde(default = "default_column_width")]
+    pub width: u16,
+}
+
+fn default_column_width() -> u16 {
+    10
+}
+
+impl Column {
+    pub fn title(&self) -> &str {
+        self.title.as_deref().unwrap_or(&self.path)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PathMapping {
+    pub from: String,
+    pub to: String,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            vendor_patterns: vec![String::from("vendor/"), String::from("node_modules/")],
+            path_mappings: vec![],
+            project_root: None,
+            hyperlink_template: None,
+            editor: None,
+            editor_command: None,
+            theme: None,
+            decoder: None,
+            filter: None,
+            webhooks: vec![],
+            hooks: vec![],
+            highlights: vec![],
+            source_maps: None,
+            mouse: false,
+            columns: vec![],
+            density: Density::Compact,
+            truncate_labels: Truncation::End,
+            number_formats: vec![],
+            expand_depth: Some(DEFAULT_EXPAND_DEPTH),
+            large_entry_size: DEFAULT_LARGE_ENTRY_SIZE,
+            raw_payloads: DEFAULT_RAW_PAYLOADS,
+            watches: vec![],
+            layout: Preset::Default,
+            time_zone: TimeZone::Original,
+            type_markers: HashMap::new(),
+            keys: HashMap::new(),
+            redact: Redaction::default(),
+            labels: vec![],
+            summary_fields: HashMap::new(),
+            summary_on_exit: false,
+            source_map_cache: SourceMaps::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        if let Ok(path) = env::var("UDL_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+
+        env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".config/udl/config.json"))
+    }
+
+    // Loads the config file, falling back to the defaults when there is none.
+    pub fn load() -> Result<Config, Box<dyn Error>> {
+        let path = match Config::path() {
+            Some(path) if path.exists() => path,
+            _ => return Ok(Config::default()),
+        };
+
+        let content = fs::read_to_string(&path)?;
+        let mut config: Config = serde_json::from_str(&content)
+            .map_err(|error| format!("Invalid config file {}: {}", path.display(), error))?;
+        if let Some(name) = &config.theme {
+            Theme::named(name).ok_or_else(|| format!("Unknown theme in {}: {}", path.display(), name))?;
+        }
+        if let Some(name) = &config.editor {
+            editor::named(name).ok_or_else(|| {
+                format!(
+                    "Unknown editor in {}: {}, use one of {}",
+                    path.display(),
+                    name,
+                    editor::names().join(", ")
+                )
+            })?;
+        }
+        if let Some(name) = &config.decoder {
+            decoder::named(name).ok_or_else(|| format!("Unknown decoder in {}: {}", path.display(), name))?;
+        }
+        config.filter()?;
+        for webhook in config.webhooks.iter_mut() {
+            webhook.compile().map_err(|error| format!("Invalid webhook filter in {}: {}", path.display(), error))?;
+        }
+        for hook in config.hooks.iter_mut() {
+            hook.compile().map_err(|error| format!("Invalid hook filter in {}: {}", path.display(), error))?;
+        }
+        for rule in config.highlights.iter_mut() {
+            rule.compile().map_err(|error| format!("Invalid highlight in {}: {}", path.display(), error))?;
+        }
+        for (name, commands) in &config.keys {
+            command::parse_key(name).map_err(|error| format!("Invalid key in {}: {}", path.display(), error))?;
+            for input in commands {
+                Command::parse(input).map_err(|error| format!("Invalid command for {} in {}: {}", name, path.display(), error))?;
+            }
+        }
+        config.redact.compile().map_err(|error| format!("Invalid redact pattern in {}: {}", path.display(), error))?;
+
+        Ok(config)
+    }
+
+    // The configured template, or the url of the editor preset.
+    pub fn hyperlink_template(&self) -> Option<&str> {
+        self.hyperlink_template
+            .as_deref()
+            .or_else(|| self.editor.as_deref().and_then(editor::named).and_then(|editor| editor.url))
+    }
+
+    pub fn editor_command(&self) -> Option<&str> {
+        self.editor_command
+            .as_deref()
+            .or_else(|| self.editor.as_deref().and_then(editor::named).map(|editor| editor.command))
+    }
+
+    pub fn decoder(&self) -> &'static dyn Decoder {
+        self.decoder.as_deref().and_then(decoder::named).unwrap_or_else(decoder::default)
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme.as_deref().and_then(Theme::named).unwrap_or_default()
+    }
+
+    // The commands bound to the key, if any.
+    pub fn commands(&self, key: Key) -> Option<&[String]> {
+        self.keys
+            .iter()
+            .find(|(name, _)| command::parse_key(name) == Ok(key))
+            .map(|(_, commands)| &commands[..])
+    }
+
+    // The paths to summarize the entries with the label by, if any.
+    pub fn summary_fields(&self, label: &str) -> &[String] {
+        self.summary_fields.get(label).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn filter(&self) -> Result<Option<Filter>, String> {
+        self.filter.as_deref().map(Filter::parse).transpose()
+    }
+
+    // Applies the first path mapping matching the start of the file, expanding `~` to the home directory.
+    pub fn map_path(&self, file: &str) -> String {
+        for mapping in &self.path_mappings {
+            if let Some(rest) = file.strip_prefix(mapping.from.as_str()) {
+                return format!("{}{}", expand_home(&mapping.to), rest);
+            }
+        }
+
+        file.to_string()
+    }
+
+    // The file and line to show for a frame: the original source when there is a source map for
+    // the file, with the path mappings applied.
+    pub fn locate(&self, frame: &BacktraceItem) -> (String, i64) {
+        let file = self.map_path(&frame.file);
+        let directory = match &self.source_maps {
+            Some(directory) => PathBuf::from(expand_home(directory)),
+            None => Path::new(&file).parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+
+        match self.source_map_cache.resolve(&directory, &file, frame.line, frame.column.unwrap_or(1)) {
+            Some((source, line)) => (self.map_path(&source), line),
+            None => (file, frame.line),
+        }
+    }
+
+    pub fn is_vendor_file(&self, file: &str) -> bool {
+        self.vendor_patterns
+            .iter()
+            .any(|pattern| file.contains(pattern.as_str()))
+    }
+
+    // The file relative to the project root, or as is when outside of it or there is none.
+    pub fn display_path(&self, file: &str) -> String {
+        self.project_relative(file).unwrap_or(file).to_string()
+    }
+
+    fn project_relative<'a>(&self, file: &'a str) -> Option<&'a str> {
+        let root = expand_home(self.project_root.as_deref()?);
+        let rest = file.strip_prefix(root.trim_end_matches('/'))?;
+
+        rest.strip_prefix('/')
+    }
+
+    // The first frame of the project: within the project root when there is one, and not in a vendor
+    // file. Usually the frame to look at first.
+    pub fn first_project_frame(&self, backtrace: &[BacktraceItem]) -> Option<usize> {
+        backtrace.iter().position(|frame| {
+            let (file, _) = self.locate(frame);
+            !self.is_vendor_file(&file) && (self.project_root.is_none() || self.project_relative(&file).is_some())
+        })
+    }
+}
+
+// Sends `ConfigChanged` whenever the modification time of the config file changes, including it
+// being created or removed.
+pub async fn watch(events: Sender<AppEvent>) {
+    let path = match Config::path() {
+        Some(path) => path,
+        None => return,
+    };
+    let modified = |path: &PathBuf| -> Option<SystemTime> { fs::metadata(path).and_then(|meta| meta.modified()).ok() };
+
+    let mut last = modified(&path);
+    loop {
+        task::sleep(Duration::from_secs(1)).await;
+        let current = modified(&path);
+        if current != last {
+            last = current;
+            if events.send(AppEvent::ConfigChanged).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix('~'), env::var("HOME")) {
+        (Some(rest), Ok(home)) => format!("{}{}", home, rest),
+        _ => path.to_string(),
+    }
+}
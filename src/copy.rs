@@ -0,0 +1,77 @@
+use crate::DebugEntry;
+use serde_json::Value;
+
+// What the selected entry is copied as.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    // The whole entry as pretty printed json.
+    Json,
+    // The details as shown.
+    Text,
+    // A curl command sending the request of an http entry again.
+    Curl,
+}
+
+pub const FORMATS: [&str; 3] = ["json", "text", "curl"];
+
+impl Format {
+    pub fn named(name: &str) -> Option<Format> {
+        match name.trim() {
+            "json" => Some(Format::Json),
+            "text" => Some(Format::Text),
+            "curl" => Some(Format::Curl),
+            _ => None,
+        }
+    }
+}
+
+// The format after the given one, to cycle through them with tab.
+pub fn next_format(name: &str) -> &'static str {
+    let index = FORMATS.iter().position(|format| *format == name.trim()).map_or(0, |index| index + 1);
+    FORMATS[index % FORMATS.len()]
+}
+
+// The entry in the format, with its details as shown for `Format::Text`.
+pub fn format(entry: &DebugEntry, format: Format, details: &str) -> Result<String, String> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(entry).map_err(|error| error.to_string()),
+        Format::Text => Ok(format!("{}\n{}", entry.label, details)),
+        Format::Curl => curl(entry).ok_or_else(|| String::from("Only entries with a request holding a url can be copied as curl")),
+    }
+}
+
+// A curl command for an entry with a `request` like `{"method": "POST", "url": "...", "headers":
+// {"Accept": "application/json"}, "body": ...}`. A body that is not a string is sent as json.
+pub fn curl(entry: &DebugEntry) -> Option<String> {
+    let request = entry.data.get("request")?;
+    let url = request.get("url")?.as_str()?;
+
+    let mut command = String::from("curl");
+    if let Some(method) = request.get("method").and_then(Value::as_str) {
+        if !method.eq_ignore_ascii_case("GET") {
+            command.push_str(&format!(" -X {}", quote(&method.to_uppercase())));
+        }
+    }
+    command.push_str(&format!(" {}", quote(url)));
+    if let Some(headers) = request.get("headers").and_then(Value::as_object) {
+        for (name, value) in headers {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string(),
+            };
+            command.push_str(&format!(" \\\n  -H {}", quote(&format!("{}: {}", name, value))));
+        }
+    }
+    match request.get("body") {
+        None | Some(Value::Null) => {}
+        Some(Value::String(body)) => command.push_str(&format!(" \\\n  --data-raw {}", quote(body))),
+        Some(body) => command.push_str(&format!(" \\\n  --data-raw {}", quote(&body.to_string()))),
+    }
+
+    Some(command)
+}
+
+// Quotes the text for a posix shell.
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
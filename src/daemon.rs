@@ -0,0 +1,152 @@
+use crate::cli::Cli;
+use crate::config::Config;
+use crate::label;
+use crate::util::event::AppEvent;
+use crate::DebugEntry;
+use async_std::{
+    channel::{self, Sender},
+    io::{self, BufReader},
+    os::unix::net::{UnixListener, UnixStream},
+    prelude::*,
+    task,
+};
+use std::sync::{Arc, Mutex};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use udl_core::store::Store;
+use udl_core::{listen_bounded, Backlog, Buffer, Received};
+
+// State shared between the listener and the attached interfaces.
+struct Daemon {
+    buffer: Buffer,
+    // One channel per attached interface, receiving new entries as lines of json.
+    attached: Vec<Sender<String>>,
+}
+
+// The local socket interfaces attach to, `UDL_SOCKET` or one per port in the temp directory.
+pub fn socket_path(port: u16) -> PathBuf {
+    match env::var_os("UDL_SOCKET") {
+        Some(path) => PathBuf::from(path),
+        None => env::temp_dir().join(format!("udl-{}.sock", port)),
+    }
+}
+
+// Collects entries without an interface for `udl daemon`, keeping them in the `--db` store and
+// sending them to every `udl attach`.
+pub fn run(cli: Cli, config: Config) -> Result<(), Box<dyn Error>> {
+    let mut buffer = Buffer::new();
+    buffer.max_entries = cli.max_entries;
+    buffer.ttl = cli.ttl;
+    if let Some(path) = &cli.db {
+        let (store, entries) = Store::open(path)?;
+        for entry in entries {
+            buffer.push(entry);
+        }
+        buffer.store = Some(store);
+    }
+    let daemon = Arc::new(Mutex::new(Daemon {
+        buffer,
+        attached: vec![],
+    }));
+
+    let path = socket_path(cli.port);
+    task::block_on(async {
+        let socket = bind(&path).await?;
+        eprintln!("Listening on {}, attach with `udl attach` on {}", cli.address(), path.display());
+
+        task::spawn(accept_attached(socket, Arc::clone(&daemon)));
+        let decoder = cli.decoder.unwrap_or_else(|| config.decoder());
+        listen_bounded(&cli.address(), &Backlog::unbounded(), cli.max_payload_size, &cli.access, decoder, |received| {
+            let daemon: &mut Daemon = &mut daemon.lock().unwrap();
+            let entry = match received {
+                Received::Entry(entry) => {
+                    let mut entry = *entry;
+                    config.redact.apply(&mut entry);
+                    label::apply(&config.labels, &mut entry);
+                    entry
+                }
+                Received::ParseError(mut error) => {
+                    error.payload = config.redact.text(&error.payload);
+                    if error.merge_into(&mut daemon.buffer.items).is_some() {
+                        return;
+                    }
+                    error.to_entry()
+                }
+                Received::Diagnostic(_) | Received::Raw(_) => return,
+            };
+            daemon.buffer.push(entry);
+            if let Some(line) = daemon.buffer.items.first().and_then(|entry| serde_json::to_string(entry).ok()) {
+                // Interfaces that went away have closed their channel.
+                daemon.attached.retain(|attached| attached.try_send(line.clone()).is_ok());
+            }
+        })
+        .await
+    })?;
+
+    Ok(())
+}
+
+// Binds the socket, replacing a socket file left behind by a daemon that is no longer running.
+async fn bind(path: &Path) -> io::Result<UnixListener> {
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("A daemon is already running on {}", path.display()),
+            ));
+        }
+        fs::remove_file(path)?;
+    }
+
+    UnixListener::bind(path).await
+}
+
+async fn accept_attached(socket: UnixListener, daemon: Arc<Mutex<Daemon>>) {
+    let mut incoming = socket.incoming();
+    while let Some(Ok(stream)) = incoming.next().await {
+        let (sender, receiver) = channel::unbounded();
+        {
+            // Send the entries collected so far, and then every new one.
+            let mut daemon = daemon.lock().unwrap();
+            for entry in daemon.buffer.items.iter().rev() {
+                if let Ok(line) = serde_json::to_string(entry) {
+                    let _ = sender.try_send(line);
+                }
+            }
+            daemon.attached.push(sender);
+        }
+
+        task::spawn(async move {
+            let mut stream = stream;
+            while let Ok(line) = receiver.recv().await {
+                if stream.write_all(format!("{}\n", line).as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+// Receives the entries of a running daemon for `udl attach`.
+pub async fn attach(path: PathBuf, events: Sender<AppEvent>) {
+    let stream = match UnixStream::connect(&path).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            let message = format!("Cannot attach to {}: {}, is `udl daemon` running?", path.display(), error);
+            let _ = events.send(AppEvent::Error(message)).await;
+            return;
+        }
+    };
+
+    let mut lines = BufReader::new(stream).lines();
+    while let Some(Ok(line)) = lines.next().await {
+        if let Ok(entry) = serde_json::from_str::<DebugEntry>(&line) {
+            if events.send(AppEvent::Received(Received::Entry(Box::new(entry)))).await.is_err() {
+                return;
+            }
+        }
+    }
+    let _ = events.send(AppEvent::Error(String::from("The daemon stopped"))).await;
+}
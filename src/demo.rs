@@ -0,0 +1,186 @@
+use async_std::{io, net::TcpStream, prelude::*, task};
+use serde_json::{json, Value};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use udl_core::timestamp;
+
+const ROUTES: [&str; 4] = ["GET /products", "POST /checkout", "GET /account", "POST /api/orders"];
+const TABLES: [&str; 4] = ["users", "orders", "products", "sessions"];
+const NAMES: [&str; 5] = ["Alice", "Bob", "Carol", "Dave", "Erin"];
+
+// Generates fake but realistic entries for `udl demo`: queries, models, exceptions, timers and
+// metrics, grouped into requests.
+pub struct Generator {
+    seed: u64,
+    count: u64,
+    route: &'static str,
+    timer_running: bool,
+}
+
+impl Default for Generator {
+    fn default() -> Generator {
+        Generator::new()
+    }
+}
+
+impl Generator {
+    pub fn new() -> Generator {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |duration| duration.as_nanos() as u64)
+            | 1;
+
+        Generator {
+            seed,
+            count: 0,
+            route: ROUTES[0],
+            timer_running: false,
+        }
+    }
+
+    // A xorshift generator, good enough for fake data.
+    fn random(&mut self, below: u64) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed % below.max(1)
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.random(items.len() as u64) as usize]
+    }
+
+    // The next payload, as a client would send it.
+    pub fn next_payload(&mut self) -> Value {
+        self.count += 1;
+        // Every few entries a new request starts.
+        if self.count % 8 == 1 {
+            self.route = self.pick(&ROUTES);
+        }
+
+        let (label, data, color, tags) = match self.random(10) {
+            0..=3 => self.query(),
+            4 | 5 => self.model(),
+            6 => self.exception(),
+            7 => self.metrics(),
+            _ => self.timer(),
+        };
+
+        json!({
+            "label": label,
+            "time": timestamp::now(),
+            "data": data,
+            "backtrace": self.backtrace(),
+            "section": self.route,
+            "color": color,
+            "tags": tags,
+        })
+    }
+
+    fn query(&mut self) -> (String, Value, Option<&'static str>, Vec<&'static str>) {
+        let table = self.pick(&TABLES);
+        let id = self.random(1000);
+        let duration = self.random(250) as f64 / 10.0;
+        let data = json!({
+            "query": format!("select * from `{}` where `id` = ? limit 1", table),
+            "bindings": [id],
+            "duration_ms": duration,
+        });
+        let color = if duration > 20.0 { Some("yellow") } else { None };
+
+        (format!("Query {}", table), data, color, vec!["sql"])
+    }
+
+    fn model(&mut self) -> (String, Value, Option<&'static str>, Vec<&'static str>) {
+        let name = self.pick(&NAMES);
+        let data = json!({
+            "user": {
+                "id": self.random(1000),
+                "name": name,
+                "email": format!("{}@example.com", name.to_lowercase()),
+                "address": {
+                    "street": format!("{} Main Street", self.random(200)),
+                    "city": self.pick(&["Amsterdam", "Berlin", "Lisbon"]),
+                },
+                "roles": ["customer"],
+            },
+        });
+
+        (String::from("User model"), data, None, vec!["model"])
+    }
+
+    fn exception(&mut self) -> (String, Value, Option<&'static str>, Vec<&'static str>) {
+        let (class, message) = self.pick(&[
+            ("ModelNotFoundException", "No query results for model [Order]"),
+            ("InvalidArgumentException", "The quantity must be a positive number"),
+            ("QueryException", "SQLSTATE[23000]: Integrity constraint violation"),
+        ]);
+        let data = json!({
+            "exception": {"class": class, "message": message, "code": self.random(500)},
+        });
+
+        (format!("Exception {}", class), data, Some("red"), vec!["error"])
+    }
+
+    fn metrics(&mut self) -> (String, Value, Option<&'static str>, Vec<&'static str>) {
+        let memory = (8 + self.random(56)) * 1024 * 1024;
+        let data = json!({
+            "metrics": {
+                "memory": memory,
+                "peak_memory": memory + self.random(16) * 1024 * 1024,
+                "execution_time": self.random(2000) as f64 / 1000.0,
+            },
+        });
+
+        (String::from("Request metrics"), data, None, vec![])
+    }
+
+    fn timer(&mut self) -> (String, Value, Option<&'static str>, Vec<&'static str>) {
+        let key = if self.timer_running { "measure_stop" } else { "measure_start" };
+        self.timer_running = !self.timer_running;
+
+        let label = if self.timer_running { "Render started" } else { "Render finished" };
+
+        (String::from(label), json!({ key: "render" }), None, vec![])
+    }
+
+    fn backtrace(&mut self) -> Value {
+        let controller = self.route.split('/').nth(1).unwrap_or("home");
+        json!([
+            {
+                "file": format!("/app/src/Http/Controllers/{}Controller.php", capitalize(controller)),
+                "line": 20 + self.random(80),
+                "function": "handle",
+            },
+            {
+                "file": "/app/vendor/framework/src/Routing/Pipeline.php",
+                "line": 128,
+                "function": "then",
+            },
+            {
+                "file": "/app/public/index.php",
+                "line": 52,
+                "function": "main",
+            },
+        ])
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Sends generated entries to the address like a client would, `rate` entries per second.
+pub async fn run(address: String, rate: f64) -> io::Result<()> {
+    let mut generator = Generator::new();
+    loop {
+        task::sleep(Duration::from_secs_f64(1.0 / rate)).await;
+        let payload = generator.next_payload().to_string();
+        let mut stream = TcpStream::connect(&address).await?;
+        stream.write_all(payload.as_bytes()).await?;
+        stream.write_all(b"\0").await?;
+    }
+}
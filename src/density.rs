@@ -0,0 +1,49 @@
+use serde::Deserialize;
+
+// Spacing of the rows of the entries list, set with `density` in the config.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Density {
+    // A row on every line.
+    #[default]
+    Compact,
+    // A blank line between the rows.
+    Comfortable,
+}
+
+impl Density {
+    // Lines taken by each row.
+    pub fn row_height(self) -> u16 {
+        match self {
+            Density::Compact => 1,
+            Density::Comfortable => 2,
+        }
+    }
+}
+
+// Where labels too long for the list are cut off, set with `truncate_labels` in the config.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Truncation {
+    #[default]
+    End,
+    // Keeps the start and the end, which tells long class names with the same namespace apart.
+    Middle,
+}
+
+// The text cut down to the width in chars with an ellipsis in the middle, like `App\Ht…Controller`.
+pub fn truncate_middle(text: &str, width: usize) -> String {
+    let length = text.chars().count();
+    if length <= width || width == 0 {
+        return text.to_string();
+    }
+
+    // The end tells labels apart more often, so it gets the odd char.
+    let start = (width - 1) / 2;
+    let end = width - 1 - start;
+    let mut truncated: String = text.chars().take(start).collect();
+    truncated.push('…');
+    truncated.extend(text.chars().skip(length - end));
+
+    truncated
+}
@@ -0,0 +1,27 @@
+use std::collections::VecDeque;
+use udl_core::timestamp;
+
+const MAX_LINES: usize = 1000;
+
+// udl's own events, like connections being accepted and entries being dropped, shown with `~` to
+// find out why a client does not show up. Only the last lines are kept.
+#[derive(Default)]
+pub struct Diagnostics {
+    lines: VecDeque<String>,
+}
+
+impl Diagnostics {
+    pub fn log(&mut self, message: &str) {
+        let now = timestamp::now();
+        let time = now.get(11..).unwrap_or(&now);
+        self.lines.push_back(format!("{} {}", time, message));
+        if self.lines.len() > MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+
+    // The newest lines that fit the height, oldest first.
+    pub fn last(&self, height: usize) -> impl Iterator<Item = &String> {
+        self.lines.iter().skip(self.lines.len().saturating_sub(height))
+    }
+}
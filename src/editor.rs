@@ -0,0 +1,59 @@
+use std::io;
+use std::process::{Command, Stdio};
+
+// A built-in way to open a file at a line, chosen with `"editor"` in the config.
+pub struct Editor {
+    pub name: &'static str,
+    // Command run with `sh` to open the file, with `{file}` and `{line}` placeholders.
+    pub command: &'static str,
+    // Url for the hyperlinks on backtrace files, when the editor registers a url scheme.
+    pub url: Option<&'static str>,
+}
+
+const EDITORS: [Editor; 4] = [
+    Editor {
+        name: "vscode",
+        command: "code --goto {file}:{line}",
+        url: Some("vscode://file/{file}:{line}"),
+    },
+    Editor {
+        name: "jetbrains",
+        command: "idea --line {line} {file}",
+        url: Some("idea://open?file={file}&line={line}"),
+    },
+    Editor {
+        name: "sublime",
+        command: "subl {file}:{line}",
+        url: Some("subl://open?url=file://{file}&line={line}"),
+    },
+    // Opens the file in the running Neovim, found through `$NVIM` or `--listen`.
+    Editor {
+        name: "neovim",
+        command: "nvim --remote +{line} {file}",
+        url: None,
+    },
+];
+
+pub fn named(name: &str) -> Option<&'static Editor> {
+    EDITORS.iter().find(|editor| editor.name == name)
+}
+
+pub fn names() -> Vec<&'static str> {
+    EDITORS.iter().map(|editor| editor.name).collect()
+}
+
+// Runs the command in the background, with the file quoted for the shell.
+pub fn open(command: &str, file: &str, line: i64) -> io::Result<()> {
+    let quoted = format!("'{}'", file.replace('\'', "'\\''"));
+    let command = command.replace("{file}", &quoted).replace("{line}", &line.to_string());
+
+    Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::fixture::{self, Language};
+use crate::DebugEntry;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub enum Format {
+    Json,
+    Ndjson,
+    Csv,
+    // A report to paste into an issue or pull request.
+    Markdown,
+    // A test fixture file per entry, for a path like `tests/fixtures/*.php`.
+    Fixtures(Language),
+}
+
+impl Format {
+    // Picks the format from the file extension, defaulting to pretty json.
+    pub fn from_path(path: &Path) -> Format {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if path.file_stem().is_some_and(|stem| stem == "*") {
+            if let Some(language) = extension.and_then(Language::from_extension) {
+                return Format::Fixtures(language);
+            }
+        }
+
+        match extension {
+            Some("ndjson") | Some("jsonl") => Format::Ndjson,
+            Some("csv") => Format::Csv,
+            Some("md") | Some("markdown") => Format::Markdown,
+            _ => Format::Json,
+        }
+    }
+}
+
+// Writes the entries to the path in the format matching its extension. A single entry is written as
+// a json object, multiple entries as an array.
+pub fn export(path: &Path, entries: &[&DebugEntry]) -> Result<(), Box<dyn Error>> {
+    let format = Format::from_path(path);
+    if let Format::Fixtures(language) = format {
+        let directory = path.parent().unwrap_or_else(|| Path::new(""));
+        let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+        return fixture::write(directory, extension, language, entries);
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+
+    match format {
+        Format::Json => {
+            if let [entry] = entries {
+                serde_json::to_writer_pretty(&mut out, entry)?;
+            } else {
+                serde_json::to_writer_pretty(&mut out, entries)?;
+            }
+            writeln!(out)?;
+        }
+        Format::Ndjson => {
+            for entry in entries {
+                writeln!(out, "{}", serde_json::to_string(entry)?)?;
+            }
+        }
+        Format::Csv => {
+            writeln!(out, "time,label,note,data,backtrace")?;
+            for entry in entries {
+                let backtrace: Vec<String> = entry
+                    .backtrace
+                    .iter()
+                    .map(|frame| format!("{}:{}", frame.file, frame.line))
+                    .collect();
+                let fields = [
+                    entry.time.clone(),
+                    entry.label.to_string(),
+                    entry.note.clone().unwrap_or_default(),
+                    serde_json::to_string(&entry.data)?,
+                    backtrace.join(" "),
+                ];
+                let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                writeln!(out, "{}", fields.join(","))?;
+            }
+        }
+        Format::Markdown => write!(out, "{}", markdown(entries)?)?,
+        Format::Fixtures(_) => unreachable!(),
+    }
+    out.flush()?;
+
+    Ok(())
+}
+
+// A heading per entry with its time, tags and note, its data in a json block and its backtrace as a
+// table.
+fn markdown(entries: &[&DebugEntry]) -> Result<String, Box<dyn Error>> {
+    let plural = if entries.len() == 1 { "entry" } else { "entries" };
+    let mut report = format!("# Debug report\n\n{} {} logged with udl.\n", entries.len(), plural);
+    for entry in entries {
+        report.push_str(&format!("\n## {}\n\n", entry.label.replace('\n', " ")));
+        report.push_str(&format!("- Time: {}\n", entry.time));
+        if !entry.tags.is_empty() {
+            report.push_str(&format!("- Tags: {}\n", entry.tags.join(", ")));
+        }
+        if let Some(note) = &entry.note {
+            report.push_str(&format!("- Note: {}\n", note.replace('\n', " ")));
+        }
+        if !entry.data.is_empty() {
+            // Json objects are sorted by key, so reports of the same data are the same.
+            let data = serde_json::to_string_pretty(&serde_json::to_value(&entry.data)?)?;
+            let fence = "`".repeat(3.max(longest_run(&data, '`') + 1));
+            report.push_str(&format!("\n{}json\n{}\n{}\n", fence, data, fence));
+        }
+        if !entry.backtrace.is_empty() {
+            report.push_str("\n| # | File | Line | Function |\n| --- | --- | --- | --- |\n");
+            for (index, frame) in entry.backtrace.iter().enumerate() {
+                report.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    index,
+                    table_cell(&frame.file),
+                    frame.line,
+                    table_cell(&frame.function)
+                ));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+// The most times the char occurs in a row in the text, to pick a fence or quote longer than that.
+pub fn longest_run(text: &str, char: char) -> usize {
+    text.split(|other| other != char).map(str::len).max().unwrap_or(0)
+}
+
+// The text as code in a table cell.
+fn table_cell(text: &str) -> String {
+    match text {
+        "" => String::new(),
+        text => format!("`{}`", text.replace('|', "\\|").replace('`', "'")),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,92 @@
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+// A step in an extraction path like `.user.addresses[0].city` or `.items[].name`.
+enum Step {
+    Key(String),
+    Index(usize),
+    Each,
+}
+
+fn parse(path: &str) -> Result<Vec<Step>, String> {
+    let mut steps: Vec<Step> = vec![];
+    for part in path.trim().split('.').filter(|part| !part.is_empty()) {
+        let (key, indexes) = match part.find('[') {
+            Some(index) => (&part[..index], &part[index..]),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            steps.push(Step::Key(key.to_string()));
+        }
+
+        for index in indexes.split_terminator(']') {
+            let index = index
+                .strip_prefix('[')
+                .ok_or_else(|| format!("Invalid index in {}", part))?;
+            if index.is_empty() {
+                steps.push(Step::Each);
+            } else {
+                let index = index.parse().map_err(|_| format!("Invalid index [{}]", index))?;
+                steps.push(Step::Index(index));
+            }
+        }
+    }
+
+    Ok(steps)
+}
+
+fn root(data: &HashMap<String, Value>) -> Value {
+    Value::Object(
+        data.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Map<String, Value>>(),
+    )
+}
+
+// Returns the values at the path within the data, more than one when iterating with `[]`.
+pub fn extract(data: &HashMap<String, Value>, path: &str) -> Result<Vec<Value>, String> {
+    let mut values = vec![root(data)];
+    for step in parse(path)? {
+        values = values
+            .iter()
+            .flat_map(|value| match (&step, value) {
+                (Step::Key(key), Value::Object(map)) => map.get(key).cloned().into_iter().collect(),
+                (Step::Index(index), Value::Array(items)) => items.get(*index).cloned().into_iter().collect(),
+                (Step::Each, Value::Array(items)) => items.clone(),
+                (Step::Each, Value::Object(map)) => map.values().cloned().collect(),
+                _ => vec![],
+            })
+            .collect();
+    }
+
+    Ok(values)
+}
+
+// Completes the last key of the path to the longest prefix shared by the matching keys.
+pub fn complete(data: &HashMap<String, Value>, path: &str) -> Option<String> {
+    let split = path.rfind('.')?;
+    let (parent, partial) = (&path[..split], &path[split + 1..]);
+
+    let mut keys: Vec<String> = extract(data, parent)
+        .ok()?
+        .iter()
+        .filter_map(|value| value.as_object())
+        .flat_map(|map| map.keys().cloned())
+        .filter(|key| key.starts_with(partial))
+        .collect();
+    keys.sort();
+    keys.dedup();
+
+    let first = keys.first()?;
+    let common = keys.iter().fold(first.as_str(), |common, key| {
+        let length = common
+            .char_indices()
+            .zip(key.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((index, a), _)| index + a.len_utf8());
+        &common[..length]
+    });
+
+    Some(format!("{}.{}", parent, common))
+}
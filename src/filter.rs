@@ -0,0 +1,611 @@
+use crate::index::Document;
+use crate::DebugEntry;
+use serde_json::{Map, Value};
+use std::cell::OnceCell;
+use std::cmp::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+use udl_core::timestamp;
+
+// A parsed filter expression like `label = "query" AND data.duration > 100`.
+//
+// Paths are `id`, `ref`, `label`, `time`, `note`, `section`, `color`, `pinned`, `tags`, `backtrace`,
+// `connection` (the fields of the hello of the connection), `arrival` (the `time`, `client`, `bytes`
+// and `sequence` udl recorded on receiving the entry) and `data`, followed by dotted keys into the
+// value. Comparison operators are `=`, `!=`, `>`, `>=`, `<`, `<=` and `~` (contains, case
+// insensitive), and expressions can be combined with `AND`, `OR`, `NOT` and parentheses. A path on
+// its own matches when the value exists and is not false, null or empty. When a path goes through
+// an array, the comparison matches if any of its items match. A quoted text on its own matches
+// entries containing it anywhere: in the label, note, section, tags, or a key or value of the data
+// or backtrace.
+//
+// Time ranges are `last 2m` (or `30s`, `1h`, `1d`), `since 14:02`, `until 14:05`, `between 14:02 and
+// 14:05` and `since marker`, for the entries from the last marker on. Times are a time of day, or a
+// date with an optional time of day, in the UTC times clients send.
+//
+// `origin "src/Cart.php:42"` matches the entries with a backtrace frame at that file and line.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub source: String,
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(String, Op, Value),
+    Exists(String),
+    // Lowercased text to search the whole entry for.
+    Text(String),
+    // Entries logged within the bounds, inclusive.
+    Time(Option<Moment>, Option<Moment>),
+    // Entries logged within this many seconds before the filter is applied.
+    Last(f64),
+    // Entries from the last marker on, with the id of the marker once known.
+    SinceMarker(Option<u64>),
+    // Entries with a frame at the file and line.
+    Origin(String, i64),
+}
+
+impl Expr {
+    // Whether the expression or one within it passes the test.
+    fn any(&self, test: &dyn Fn(&Expr) -> bool) -> bool {
+        test(self)
+            || match self {
+                Expr::And(left, right) | Expr::Or(left, right) => left.any(test) || right.any(test),
+                Expr::Not(inner) => inner.any(test),
+                _ => false,
+            }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Moment {
+    // Seconds since the unix epoch.
+    At(f64),
+    // Seconds since midnight, on any day.
+    Clock(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Number(f64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Filter, String> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser { tokens, position: 0 };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected {:?} in filter", token));
+        }
+
+        Ok(Filter {
+            source: source.trim().to_string(),
+            expr,
+        })
+    }
+
+    // A filter for the entries containing the text anywhere.
+    pub fn text(text: &str) -> Filter {
+        Filter {
+            source: format!("\"{}\"", text.replace('"', "")),
+            expr: Expr::Text(text.replace('"', "").to_lowercase()),
+        }
+    }
+
+    // A filter for the entries with the label, or for all the others with `exclude`.
+    pub fn label(label: &str, exclude: bool) -> Filter {
+        let op = if exclude { Op::Ne } else { Op::Eq };
+        let quote = if label.contains('"') { '\'' } else { '"' };
+        Filter {
+            source: format!("label {} {}{}{}", if exclude { "!=" } else { "=" }, quote, label, quote),
+            expr: Expr::Compare(String::from("label"), op, Value::String(label.to_string())),
+        }
+    }
+
+    // The entries matching both this filter and the other one.
+    pub fn and(self, other: Filter) -> Filter {
+        Filter {
+            source: format!("({}) AND {}", self.source, other.source),
+            expr: Expr::And(Box::new(self.expr), Box::new(other.expr)),
+        }
+    }
+
+    // Whether the filter shows the entries since the last marker, which depends on the other entries.
+    pub fn uses_marker(&self) -> bool {
+        self.expr.any(&|expr| matches!(expr, Expr::SinceMarker(_)))
+    }
+
+    // Whether the filter shows the entries of the last minutes, which drop out as time passes.
+    pub fn uses_clock(&self) -> bool {
+        self.expr.any(&|expr| matches!(expr, Expr::Last(_)))
+    }
+
+    // Sets the id of the last marker for `since marker`, without one every entry matches.
+    pub fn set_marker(&mut self, id: Option<u64>) {
+        fn set(expr: &mut Expr, id: Option<u64>) {
+            match expr {
+                Expr::And(left, right) | Expr::Or(left, right) => {
+                    set(left, id);
+                    set(right, id);
+                }
+                Expr::Not(inner) => set(inner, id),
+                Expr::SinceMarker(marker) => *marker = id,
+                _ => {}
+            }
+        }
+        set(&mut self.expr, id)
+    }
+
+    pub fn matches(&self, entry: &DebugEntry) -> bool {
+        self.matches_indexed(entry, None)
+    }
+
+    // Matches using the searchable content of the entry from the index, instead of building it
+    // when the filter searches the content.
+    pub fn matches_indexed(&self, entry: &DebugEntry, document: Option<&Document>) -> bool {
+        let content = Content {
+            entry,
+            indexed: document,
+            built: OnceCell::new(),
+        };
+        evaluate(&self.expr, &content)
+    }
+}
+
+struct Content<'a> {
+    entry: &'a DebugEntry,
+    indexed: Option<&'a Document>,
+    built: OnceCell<Document>,
+}
+
+impl Content<'_> {
+    fn document(&self) -> &Document {
+        match self.indexed {
+            Some(document) => document,
+            None => self.built.get_or_init(|| Document::new(self.entry)),
+        }
+    }
+}
+
+fn evaluate(expr: &Expr, content: &Content) -> bool {
+    let entry = content.entry;
+    match expr {
+        Expr::And(left, right) => evaluate(left, content) && evaluate(right, content),
+        Expr::Or(left, right) => evaluate(left, content) || evaluate(right, content),
+        Expr::Not(inner) => !evaluate(inner, content),
+        Expr::Exists(path) => resolve(entry, path).is_some_and(|value| any(&value, &is_truthy)),
+        // Labels are compared without copying them, unless the literal is compared as a number.
+        Expr::Compare(path, op @ (Op::Eq | Op::Ne), Value::String(text))
+            if path == "label" && text.trim().parse::<f64>().is_err() =>
+        {
+            (entry.label.as_ref() == text.as_str()) == (*op == Op::Eq)
+        }
+        Expr::Compare(path, Op::Contains, literal) if is_searchable(path) => {
+            content.document().contains(path, &as_text(literal).to_lowercase())
+        }
+        Expr::Compare(path, op, literal) => match resolve(entry, path) {
+            Some(value) => any(&value, &|value| compare(value, *op, literal)),
+            None => *op == Op::Ne,
+        },
+        Expr::Text(text) => {
+            let contains = |value: &str| value.to_lowercase().contains(text.as_str());
+            contains(&entry.label)
+                || entry.note.as_deref().is_some_and(contains)
+                || entry.section.as_deref().is_some_and(contains)
+                || entry.tags.iter().any(|tag| contains(tag))
+                || content.document().contains("", text)
+        }
+        Expr::Time(from, to) => timestamp::parse(&entry.time).is_some_and(|time| within(time, *from, *to)),
+        Expr::Last(seconds) => timestamp::parse(&entry.time).is_some_and(|time| time >= now() - seconds),
+        Expr::SinceMarker(marker) => marker.is_none_or(|marker| entry.id >= marker),
+        Expr::Origin(file, line) => entry.backtrace.iter().any(|frame| frame.line == *line && frame.file.as_ref() == file),
+    }
+}
+
+// Entries with a truthy `marker` or `session_start` in their data separate the runs of a session, for
+// `since marker`.
+pub fn is_marker(entry: &DebugEntry) -> bool {
+    ["marker", "session_start"].iter().any(|key| entry.data.get(*key).is_some_and(is_truthy))
+}
+
+// Seconds since the unix epoch.
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |now| now.as_secs_f64())
+}
+
+fn within(time: f64, from: Option<Moment>, to: Option<Moment>) -> bool {
+    let clock = time.rem_euclid(86400.0);
+    match (from, to) {
+        // A range of times of day can go past midnight, like `between 23:00 and 01:00`.
+        (Some(Moment::Clock(from)), Some(Moment::Clock(to))) if from > to => clock >= from || clock <= to,
+        _ => {
+            let after = match from {
+                Some(Moment::At(from)) => time >= from,
+                Some(Moment::Clock(from)) => clock >= from,
+                None => true,
+            };
+            let before = match to {
+                Some(Moment::At(to)) => time <= to,
+                Some(Moment::Clock(to)) => clock <= to,
+                None => true,
+            };
+            after && before
+        }
+    }
+}
+
+// A time of day like `14:02` or `14:02:30`, or a date with an optional time of day. Without seconds an
+// upper bound includes the whole minute, and without a time the whole day.
+pub fn parse_moment(text: &str, upper: bool) -> Option<Moment> {
+    let text = text.trim();
+    if !text.contains('-') {
+        let parts: Vec<&str> = text.split(':').collect();
+        if parts.len() < 2 || parts.len() > 3 {
+            return None;
+        }
+        let hours: u32 = parts[0].parse().ok().filter(|hours| *hours < 24)?;
+        let minutes: u32 = parts[1].parse().ok().filter(|minutes| *minutes < 60)?;
+        let seconds: f64 = match parts.get(2) {
+            Some(seconds) => seconds.parse().ok().filter(|seconds| *seconds < 60.0)?,
+            None if upper => 59.999,
+            None => 0.0,
+        };
+        return Some(Moment::Clock((hours * 3600 + minutes * 60) as f64 + seconds));
+    }
+
+    match timestamp::parse(text) {
+        Some(time) if upper && text.matches(':').count() == 1 => Some(Moment::At(time + 59.999)),
+        Some(time) => Some(Moment::At(time)),
+        None => {
+            let day = timestamp::parse(&format!("{} 00:00:00", text))?;
+            Some(Moment::At(if upper { day + 86399.999 } else { day }))
+        }
+    }
+}
+
+// A duration like `90s`, `2m`, `1h` or `1d`, in seconds.
+pub fn parse_duration(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let amount: f64 = text[..split].parse().ok()?;
+    let unit = match &text[split..] {
+        "" | "s" | "sec" | "seconds" => 1.0,
+        "m" | "min" | "minutes" => 60.0,
+        "h" | "hour" | "hours" => 3600.0,
+        "d" | "day" | "days" => 86400.0,
+        _ => return None,
+    };
+
+    Some(amount * unit)
+}
+
+// Whether searching the path for a text can use the searchable content, which has the data and
+// backtrace without the array indexes.
+fn is_searchable(path: &str) -> bool {
+    let mut keys = path.split('.');
+    matches!(keys.next(), Some("data") | Some("backtrace")) && keys.all(|key| key.parse::<usize>().is_err())
+}
+
+// Applies the check to the value, or to any of its items when it is an array.
+fn any(value: &Value, check: &dyn Fn(&Value) -> bool) -> bool {
+    match value {
+        Value::Array(items) => items.iter().any(|item| any(item, check)),
+        value => check(value),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => false,
+        Value::String(text) => !text.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => true,
+    }
+}
+
+fn compare(value: &Value, op: Op, literal: &Value) -> bool {
+    if op == Op::Contains {
+        return as_text(value).to_lowercase().contains(&as_text(literal).to_lowercase());
+    }
+
+    let ordering = match (as_number(value), as_number(literal)) {
+        (Some(left), Some(right)) => left.partial_cmp(&right),
+        _ => match (value, literal) {
+            (Value::Null, Value::Null) => Some(Ordering::Equal),
+            (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+            _ => Some(as_text(value).cmp(&as_text(literal))),
+        },
+    };
+
+    match (op, ordering) {
+        (Op::Eq, Some(ordering)) => ordering == Ordering::Equal,
+        (Op::Ne, Some(ordering)) => ordering != Ordering::Equal,
+        (Op::Gt, Some(ordering)) => ordering == Ordering::Greater,
+        (Op::Ge, Some(ordering)) => ordering != Ordering::Less,
+        (Op::Lt, Some(ordering)) => ordering == Ordering::Less,
+        (Op::Le, Some(ordering)) => ordering != Ordering::Greater,
+        _ => false,
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.to_string(),
+        Value::Null => String::from("null"),
+        value => value.to_string(),
+    }
+}
+
+// Looks up a dotted path on the entry. Going through an array with a key that is not an index
+// collects the key from each of its items.
+pub fn resolve(entry: &DebugEntry, path: &str) -> Option<Value> {
+    let mut keys = path.split('.');
+    let root = match keys.next()? {
+        "id" => Value::from(entry.id),
+        "label" => Value::String(entry.label.to_string()),
+        "time" => Value::String(entry.time.clone()),
+        "ref" => entry.reference.clone().map_or(Value::Null, Value::String),
+        "note" => entry.note.clone().map_or(Value::Null, Value::String),
+        "section" => entry.section.clone().map_or(Value::Null, Value::String),
+        "color" => entry.color.clone().map_or(Value::Null, Value::String),
+        "pinned" => Value::Bool(entry.pinned),
+        "tags" => Value::Array(entry.tags.iter().cloned().map(Value::String).collect()),
+        "backtrace" => serde_json::to_value(&entry.backtrace).ok()?,
+        "connection" => Value::Object(entry.connection.clone()),
+        "arrival" => serde_json::to_value(&entry.arrival).ok()?,
+        // Only the value of the first key is copied when there is one.
+        "data" => match keys.next() {
+            Some(key) => entry.data.get(key)?.clone(),
+            None => Value::Object(
+                entry
+                    .data
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect::<Map<String, Value>>(),
+            ),
+        },
+        _ => return None,
+    };
+
+    keys.try_fold(root, |value, key| lookup(&value, key))
+}
+
+fn lookup(value: &Value, key: &str) -> Option<Value> {
+    match value {
+        Value::Object(map) => map.get(key).cloned(),
+        Value::Array(items) => match key.parse::<usize>() {
+            Ok(index) => items.get(index).cloned(),
+            Err(_) => Some(Value::Array(
+                items.iter().filter_map(|item| lookup(item, key)).collect(),
+            )),
+        },
+        _ => None,
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<Token> = vec![];
+    let mut position = 0;
+
+    while position < chars.len() {
+        let c = chars[position];
+        let next = chars.get(position + 1).copied();
+
+        if c.is_whitespace() {
+            position += 1;
+            continue;
+        }
+
+        let (token, length) = match (c, next) {
+            ('(', _) => (Token::Open, 1),
+            (')', _) => (Token::Close, 1),
+            ('=', Some('=')) => (Token::Op(Op::Eq), 2),
+            ('=', _) => (Token::Op(Op::Eq), 1),
+            ('!', Some('=')) => (Token::Op(Op::Ne), 2),
+            ('!', _) => (Token::Not, 1),
+            ('>', Some('=')) => (Token::Op(Op::Ge), 2),
+            ('>', _) => (Token::Op(Op::Gt), 1),
+            ('<', Some('=')) => (Token::Op(Op::Le), 2),
+            ('<', _) => (Token::Op(Op::Lt), 1),
+            ('~', _) => (Token::Op(Op::Contains), 1),
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('"', _) | ('\'', _) => {
+                let end = chars[position + 1..]
+                    .iter()
+                    .position(|other| *other == c)
+                    .ok_or_else(|| format!("Unterminated string at position {}", position + 1))?;
+                let text: String = chars[position + 1..position + 1 + end].iter().collect();
+                (Token::Text(text), end + 2)
+            }
+            _ => {
+                let length = chars[position..]
+                    .iter()
+                    .position(|other| other.is_whitespace() || "()=!<>~&|\"'".contains(*other))
+                    .unwrap_or(chars.len() - position);
+                if length == 0 {
+                    return Err(format!("Unexpected '{}' at position {}", c, position + 1));
+                }
+                let word: String = chars[position..position + length].iter().collect();
+                let token = match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => match word.parse::<f64>() {
+                        Ok(number) => Token::Number(number),
+                        Err(_) => Token::Word(word),
+                    },
+                };
+                (token, length)
+            }
+        };
+
+        tokens.push(token);
+        position += length;
+    }
+
+    Ok(tokens)
+}
+
+fn is_time_keyword(word: &str) -> bool {
+    ["last", "since", "until", "between"].contains(&word.to_lowercase().as_str())
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.advance();
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.advance() {
+            Some(Token::Not) => Ok(Expr::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let expr = self.or()?;
+                match self.advance() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err(String::from("Missing closing parenthesis in filter")),
+                }
+            }
+            Some(Token::Text(text)) => Ok(Expr::Text(text.to_lowercase())),
+            Some(Token::Word(word)) if is_time_keyword(&word) && !matches!(self.peek(), Some(Token::Op(_)) | None) => {
+                self.time_range(&word.to_lowercase())
+            }
+            Some(Token::Word(word)) if word.eq_ignore_ascii_case("origin") && !matches!(self.peek(), Some(Token::Op(_)) | None) => {
+                self.origin()
+            }
+            Some(Token::Word(path)) => match self.peek() {
+                Some(Token::Op(op)) => {
+                    let op = *op;
+                    self.advance();
+                    Ok(Expr::Compare(path, op, self.literal()?))
+                }
+                _ => Ok(Expr::Exists(path)),
+            },
+            Some(token) => Err(format!("Expected a path but found {:?} in filter", token)),
+            None => Err(String::from("Unexpected end of filter")),
+        }
+    }
+
+    // The rest of `last 2m`, `since 14:02`, `since marker`, `until 14:05` or `between 14:02 and 14:05`.
+    fn time_range(&mut self, keyword: &str) -> Result<Expr, String> {
+        let argument = match self.advance() {
+            Some(Token::Word(word)) | Some(Token::Text(word)) => word,
+            Some(Token::Number(number)) => number.to_string(),
+            _ => return Err(format!("Expected a time after {} in filter", keyword)),
+        };
+        let moment = |text: &str, upper: bool| {
+            parse_moment(text, upper).ok_or_else(|| format!("Invalid time {} in filter, use 14:02 or \"2021-04-29 14:02\"", text))
+        };
+
+        match keyword {
+            "last" => {
+                let duration = parse_duration(&argument)
+                    .ok_or_else(|| format!("Invalid duration {} in filter, use 30s, 2m, 1h or 1d", argument))?;
+                Ok(Expr::Last(duration))
+            }
+            "since" if argument.eq_ignore_ascii_case("marker") => Ok(Expr::SinceMarker(None)),
+            "since" => Ok(Expr::Time(Some(moment(&argument, false)?), None)),
+            "until" => Ok(Expr::Time(None, Some(moment(&argument, true)?))),
+            _ => {
+                if self.advance() != Some(Token::And) {
+                    return Err(String::from("Expected AND in between ... and ... in filter"));
+                }
+                let end = match self.advance() {
+                    Some(Token::Word(word)) | Some(Token::Text(word)) => word,
+                    _ => return Err(String::from("Expected a time after AND in filter")),
+                };
+                Ok(Expr::Time(Some(moment(&argument, false)?), Some(moment(&end, true)?)))
+            }
+        }
+    }
+
+    // The rest of `origin "src/Cart.php:42"`.
+    fn origin(&mut self) -> Result<Expr, String> {
+        let origin = match self.advance() {
+            Some(Token::Text(origin)) => origin,
+            _ => return Err(String::from("Expected a quoted \"file:line\" after origin in filter")),
+        };
+        match origin.rsplit_once(':').and_then(|(file, line)| Some((file, line.trim().parse().ok()?))) {
+            Some((file, line)) => Ok(Expr::Origin(file.to_string(), line)),
+            None => Err(format!("Invalid origin {} in filter, use \"file:line\"", origin)),
+        }
+    }
+
+    fn literal(&mut self) -> Result<Value, String> {
+        match self.advance() {
+            Some(Token::Text(text)) => Ok(Value::String(text)),
+            Some(Token::Number(number)) => Ok(serde_json::Number::from_f64(number).map_or(Value::Null, Value::Number)),
+            Some(Token::Word(word)) => Ok(match word.to_lowercase().as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                "null" => Value::Null,
+                _ => Value::String(word),
+            }),
+            Some(token) => Err(format!("Expected a value but found {:?} in filter", token)),
+            None => Err(String::from("Expected a value at the end of the filter")),
+        }
+    }
+}
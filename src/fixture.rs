@@ -0,0 +1,107 @@
+use crate::export::longest_run;
+use crate::DebugEntry;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+// Labels are cut off after this many characters in the file names.
+const MAX_NAME_LENGTH: usize = 50;
+
+// Languages of the test fixtures written when exporting to a path like `tests/fixtures/*.php`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    // A `PAYLOAD` constant with the json in a raw string.
+    Rust,
+    // A file returning the payload as an array, like `$payload = require 'query.php';`.
+    Php,
+    // A CommonJS module exporting the payload.
+    JavaScript,
+    // A module with the payload as its default export.
+    TypeScript,
+}
+
+impl Language {
+    pub fn from_extension(extension: &str) -> Option<Language> {
+        match extension {
+            "rs" => Some(Language::Rust),
+            "php" => Some(Language::Php),
+            "js" | "cjs" => Some(Language::JavaScript),
+            "ts" | "mjs" => Some(Language::TypeScript),
+            _ => None,
+        }
+    }
+
+    fn source(self, entry: &DebugEntry, json: &str) -> String {
+        let comment = format!("Captured by udl from {:?}, logged on {}.", entry.label.replace('\n', " "), entry.time);
+        match self {
+            Language::Rust => {
+                let hashes = "#".repeat(longest_run(json, '#') + 1);
+                format!("// {}\npub const PAYLOAD: &str = r{}\"{}\"{};\n", comment, hashes, json, hashes)
+            }
+            Language::Php => format!("<?php\n\n// {}\nreturn json_decode(<<<'JSON'\n{}\nJSON, true);\n", comment, json),
+            Language::JavaScript => format!("// {}\nmodule.exports = {};\n", comment, json),
+            Language::TypeScript => format!("// {}\nexport default {};\n", comment, json),
+        }
+    }
+}
+
+// Writes a fixture file per entry to the directory, oldest first, named after their labels like
+// `select_users.php`, with `_2` and so on for the same label. The payloads are the entries as a
+// client sends them, without what udl adds like the id, note and arrival.
+pub fn write(directory: &Path, extension: &str, language: Language, entries: &[&DebugEntry]) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(directory)?;
+
+    let mut names: HashMap<String, usize> = HashMap::new();
+    for entry in entries {
+        let name = file_name(&entry.label);
+        let count = names.entry(name.clone()).or_insert(0);
+        *count += 1;
+        let name = match count {
+            1 => name,
+            count => format!("{}_{}", name, count),
+        };
+
+        let path = directory.join(format!("{}.{}", name, extension));
+        fs::write(&path, language.source(entry, &payload(entry)?))?;
+    }
+
+    Ok(())
+}
+
+// The entry as pretty json, leaving out the fields it does not have.
+fn payload(entry: &DebugEntry) -> Result<String, Box<dyn Error>> {
+    let mut payload = serde_json::to_value(entry)?;
+    if let Value::Object(object) = &mut payload {
+        let empty: Vec<String> = object.iter().filter(|(_, value)| value.is_null()).map(|(key, _)| key.clone()).collect();
+        for key in ["id", "note", "pinned", "arrival"].iter().map(|key| key.to_string()).chain(empty) {
+            object.remove(&key);
+        }
+    }
+
+    Ok(serde_json::to_string_pretty(&payload)?)
+}
+
+// The label in lowercase with runs of anything but letters and digits as `_`, which makes a valid
+// module name in each of the languages.
+fn file_name(label: &str) -> String {
+    let mut name = String::new();
+    for char in label.chars().flat_map(char::to_lowercase) {
+        if char.is_ascii_alphanumeric() {
+            name.push(char);
+        } else if !name.is_empty() && !name.ends_with('_') {
+            name.push('_');
+        }
+        if name.len() >= MAX_NAME_LENGTH {
+            break;
+        }
+    }
+    let name = name.trim_end_matches('_');
+
+    match name.chars().next() {
+        None => String::from("entry"),
+        Some(first) if first.is_ascii_digit() => format!("entry_{}", name),
+        Some(_) => name.to_string(),
+    }
+}
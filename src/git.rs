@@ -0,0 +1,93 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+// The last commit changing a line of a file in a git repository.
+#[derive(Debug, Clone)]
+pub struct Blame {
+    pub hash: String,
+    pub author: String,
+    pub summary: String,
+    // Link to the line in the commit on the hosting service of the `origin` remote.
+    pub permalink: Option<String>,
+}
+
+impl Blame {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(7)]
+    }
+}
+
+// Runs `git blame` for the line (1 based), `None` when the file is not in a repository.
+pub fn blame(file: &str, line: usize) -> Option<Blame> {
+    let directory = Path::new(file).parent()?;
+    let output = git(directory, &["blame", "--porcelain", "-L", &format!("{},{}", line, line), "--", file])?;
+
+    // The first line holds the commit and the line number in it, followed by headers of the commit.
+    let mut lines = output.lines();
+    let mut header = lines.next()?.split_whitespace();
+    let hash = header.next()?.to_string();
+    let original_line = header.next()?.to_string();
+    let header = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name).and_then(|value| value.strip_prefix(' ')))
+            .map(str::to_string)
+    };
+
+    // Lines that are not committed yet have a hash of zeros.
+    if hash.chars().all(|c| c == '0') {
+        return Some(Blame {
+            hash,
+            author: String::from("You"),
+            summary: String::from("Not committed yet"),
+            permalink: None,
+        });
+    }
+
+    let permalink = git(directory, &["remote", "get-url", "origin"])
+        .and_then(|remote| web_url(remote.trim()))
+        .zip(header("filename"))
+        .map(|(url, path)| format!("{}/blob/{}/{}#L{}", url, hash, path, original_line));
+
+    Some(Blame {
+        author: header("author").unwrap_or_default(),
+        summary: header("summary").unwrap_or_default(),
+        hash,
+        permalink,
+    })
+}
+
+// The web address of a remote like `git@github.com:owner/repo.git` or `https://github.com/owner/repo`.
+fn web_url(remote: &str) -> Option<String> {
+    let remote = remote.trim_end_matches('/').trim_end_matches(".git");
+    let address = match remote.split_once("://") {
+        Some((_, rest)) => rest.to_string(),
+        // The scp like syntax, `user@host:path`.
+        None => remote.replacen(':', "/", 1),
+    };
+    let address = match address.split_once('@') {
+        Some((_, rest)) => rest,
+        None => &address,
+    };
+    if !address.contains('/') {
+        return None;
+    }
+
+    Some(format!("https://{}", address))
+}
+
+fn git(directory: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
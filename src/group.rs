@@ -0,0 +1,71 @@
+use crate::config::Config;
+use crate::{BacktraceItem, DebugEntry};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+// Entries sharing where they were sent from: the first frame of their backtrace outside vendor files.
+pub struct Group {
+    // Like `src/Cart.php:42`, `None` for the entries without a backtrace.
+    pub origin: Option<String>,
+    pub count: usize,
+    // Id, label and time of the newest entry.
+    pub latest: u64,
+    pub label: String,
+    pub time: String,
+}
+
+impl Group {
+    pub fn title(&self) -> &str {
+        self.origin.as_deref().unwrap_or("(no backtrace)")
+    }
+
+    // A filter expression for the entries of the group.
+    pub fn filter(&self) -> String {
+        match &self.origin {
+            Some(origin) => format!("origin \"{}\"", origin.replace('"', "")),
+            None => String::from("NOT backtrace"),
+        }
+    }
+}
+
+// The first frame outside vendor files, or the first one when they all are.
+pub fn origin_frame<'a>(entry: &'a DebugEntry, config: &Config) -> Option<&'a BacktraceItem> {
+    entry
+        .backtrace
+        .iter()
+        .find(|frame| !config.is_vendor_file(&frame.file))
+        .or_else(|| entry.backtrace.first())
+}
+
+// The origin frame as `file:line`.
+pub fn origin(entry: &DebugEntry, config: &Config) -> Option<String> {
+    let frame = origin_frame(entry, config)?;
+
+    Some(format!("{}:{}", frame.file, frame.line))
+}
+
+// The groups of the entries, given newest first, with the most entries first.
+pub fn collect<'a>(items: impl Iterator<Item = &'a DebugEntry>, config: &Config) -> Vec<Group> {
+    let mut groups: Vec<Group> = vec![];
+    let mut positions: HashMap<Option<String>, usize> = HashMap::new();
+    for item in items {
+        let origin = origin(item, config);
+        match positions.get(&origin) {
+            Some(position) => groups[*position].count += 1,
+            None => {
+                positions.insert(origin.clone(), groups.len());
+                groups.push(Group {
+                    origin,
+                    count: 1,
+                    latest: item.id,
+                    label: item.label.to_string(),
+                    time: item.time.clone(),
+                });
+            }
+        }
+    }
+    // Stable, so groups with as many entries stay in order of their newest entry.
+    groups.sort_by_key(|group| Reverse(group.count));
+
+    groups
+}
@@ -0,0 +1,211 @@
+use crate::cli::{Cli, Mode};
+use crate::config::Config;
+use crate::filter;
+use crate::label;
+use crate::DebugEntry;
+use async_std::io::BufReader;
+use async_std::task;
+use serde_json::Value;
+use std::error::Error;
+use std::io::{self, Write};
+use std::process;
+use crate::notify::Notifier;
+use crate::port::{self, Conflict};
+use crate::script::Script;
+use crate::xdebug;
+use udl_core::store::Store;
+use udl_core::tail;
+use udl_core::{listen_bounded, read_lines, session, Backlog, Received};
+
+// How entries are written to stdout with `--headless`.
+#[derive(Clone)]
+pub enum Format {
+    // A single line with the time, label, tags and data.
+    Compact,
+    // The whole entry as json on a single line.
+    Json,
+    // The whole entry as indented json.
+    Pretty,
+    // `key=value` pairs, with a `data.` key for every data value.
+    Logfmt,
+    // Text with `{path}` placeholders like `{time} [{label}] {data.message}`, see `filter::resolve`.
+    Template(String),
+}
+
+impl Format {
+    // One of the presets, or a template when the name holds a placeholder.
+    pub fn named(name: &str) -> Option<Format> {
+        if name.contains('{') {
+            return Some(Format::Template(name.to_string()));
+        }
+        match name.trim().to_lowercase().as_str() {
+            "compact" => Some(Format::Compact),
+            "json" => Some(Format::Json),
+            "pretty" => Some(Format::Pretty),
+            "logfmt" => Some(Format::Logfmt),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, entry: &DebugEntry) -> String {
+        match self {
+            Format::Compact => {
+                let mut line = format!("{} {}", entry.time, entry.label);
+                if !entry.tags.is_empty() {
+                    line.push_str(&format!(" [{}]", entry.tags.join(", ")));
+                }
+                if !entry.data.is_empty() {
+                    line.push_str(&format!(" {}", serde_json::to_string(&entry.data).unwrap_or_default()));
+                }
+                line
+            }
+            Format::Json => serde_json::to_string(entry).unwrap_or_default(),
+            Format::Pretty => serde_json::to_string_pretty(entry).unwrap_or_default(),
+            Format::Logfmt => {
+                let mut pairs = vec![
+                    format!("time={}", logfmt_value(&entry.time)),
+                    format!("label={}", logfmt_value(&entry.label)),
+                ];
+                if !entry.tags.is_empty() {
+                    pairs.push(format!("tags={}", logfmt_value(&entry.tags.join(","))));
+                }
+                let mut keys: Vec<&String> = entry.data.keys().collect();
+                keys.sort();
+                for key in keys {
+                    let value = match &entry.data[key] {
+                        Value::String(text) => text.to_string(),
+                        value => value.to_string(),
+                    };
+                    pairs.push(format!("data.{}={}", key, logfmt_value(&value)));
+                }
+                pairs.join(" ")
+            }
+            Format::Template(template) => render_template(template, entry),
+        }
+    }
+}
+
+// Replaces every `{path}` in the template by the value at the path, text without quotes and other
+// values as json. Paths the entry does not have are left empty, `{{` and `}}` are literal braces.
+fn render_template(template: &str, entry: &DebugEntry) -> String {
+    let mut line = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                line.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                line.push('}');
+            }
+            '{' => {
+                let path: String = chars.by_ref().take_while(|char| *char != '}').collect();
+                match filter::resolve(entry, path.trim()) {
+                    Some(Value::String(text)) => line.push_str(&text),
+                    Some(Value::Null) | None => {}
+                    Some(value) => line.push_str(&value.to_string()),
+                }
+            }
+            _ => line.push(char),
+        }
+    }
+
+    line
+}
+
+// Quotes the value when it contains spaces, quotes or an equals sign.
+fn logfmt_value(value: &str) -> String {
+    if value.is_empty() || value.contains([' ', '"', '=']) || value.contains(char::is_control) {
+        format!("{:?}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// Writes the entries to stdout instead of showing them in the terminal interface.
+pub fn run(cli: Cli, config: Config) -> Result<(), Box<dyn Error>> {
+    let format = cli.format.clone();
+    let filter = cli.filter.clone();
+    let print = move |entry: &DebugEntry| {
+        if filter.as_ref().is_none_or(|filter| filter.matches(entry)) {
+            // Stop quietly when the output is closed, like when piping into `head`.
+            if writeln!(io::stdout(), "{}", format.render(entry)).is_err() {
+                process::exit(0);
+            }
+        }
+    };
+
+    if let Mode::Open(path) = &cli.mode {
+        session::load(path)?.iter().for_each(print.clone());
+    }
+    if let Mode::ImportXdebug(path) = &cli.mode {
+        xdebug::load(path)?.iter().for_each(print.clone());
+    }
+    for path in &cli.imports {
+        session::load_entries(path)?.iter().for_each(print.clone());
+    }
+    if let Mode::Replay(path) = &cli.mode {
+        let entries = session::load_entries(path)?;
+        let (speed, print) = (cli.speed, print.clone());
+        task::spawn(crate::replay(entries, speed, move |entry| print(&entry)));
+    }
+
+    let decoder = cli.decoder.unwrap_or_else(|| config.decoder());
+    let mut store = cli.db.as_deref().map(Store::append_to).transpose()?;
+    let mut script = cli.script.as_deref().map(Script::spawn).transpose()?;
+    let notifier = Notifier {
+        rules: cli.notify.clone(),
+        bell: cli.bell,
+        webhooks: config.webhooks,
+        hooks: config.hooks,
+        hooked: None,
+    };
+    let (redaction, label_rules) = (config.redact, config.labels);
+    let handle = |received| match received {
+        Received::Entry(entry) => {
+            let mut entry = *entry;
+            redaction.apply(&mut entry);
+            label::apply(&label_rules, &mut entry);
+            let entry = match script.as_mut().map(|script| script.transform(&entry)) {
+                Some(Ok(Some(transformed))) => transformed,
+                Some(Ok(None)) => return,
+                Some(Err(error)) => {
+                    eprintln!("Script error: {}", error);
+                    entry
+                }
+                None => entry,
+            };
+            if let Some(store) = store.as_mut() {
+                if let Err(error) = store.append(&entry) {
+                    eprintln!("Store error: {}", error);
+                }
+            }
+            notifier.notify(&entry);
+            notifier.run_hooks(&entry);
+            print(&entry);
+        }
+        Received::ParseError(error) => eprintln!("Parse error from {}: {}", error.client, error.error),
+        Received::Diagnostic(_) | Received::Raw(_) => {}
+    };
+    // Reading stdin takes the place of the listener, stopping at its end like any other filter. So
+    // does following a file.
+    match (&cli.mode, cli.stdin) {
+        (Mode::Tail(path, format), _) => task::block_on(tail::follow(path, *format, &Backlog::unbounded(), handle))?,
+        (_, true) => task::block_on(read_lines(BufReader::new(async_std::io::stdin()), "stdin", &Backlog::unbounded(), handle))?,
+        (_, false) => {
+            // Attaching makes no sense without an interface, so only a free port is looked for.
+            let address = match port::check(&cli.host, cli.port, false)? {
+                Some(Conflict::Moved(port)) => {
+                    eprintln!("Port {} is in use, listening on port {} instead", cli.port, port);
+                    format!("{}:{}", cli.host, port)
+                }
+                _ => cli.address(),
+            };
+            task::block_on(listen_bounded(&address, &Backlog::unbounded(), cli.max_payload_size, &cli.access, decoder, handle))?
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,155 @@
+use crate::filter::Filter;
+use crate::regex::Regex;
+use crate::theme::parse_color;
+use crate::DebugEntry;
+use serde::Deserialize;
+use serde_json::Value;
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+
+// Texts longer than this are not searched for patterns, matching backtracks per char.
+const MAX_TEXT_LENGTH: usize = 2000;
+
+// Colors the matches of a pattern in the details pane, configured as `highlights` in the config
+// file. With a filter only the entries matching it are highlighted, and with `row` the rows of
+// those entries are colored in the list too.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HighlightRule {
+    // Regular expression, see `regex.rs` for the syntax.
+    pub pattern: Option<String>,
+    // Filter expression for the entries the rule applies to.
+    pub filter: Option<String>,
+    pub color: Option<String>,
+    pub background: Option<String>,
+    #[serde(default)]
+    pub bold: bool,
+    #[serde(default)]
+    pub row: bool,
+    #[serde(skip)]
+    compiled: Option<(Option<Regex>, Option<Filter>, Style)>,
+}
+
+impl HighlightRule {
+    // Parses the pattern, filter and colors, done once when the config is loaded.
+    pub fn compile(&mut self) -> Result<(), String> {
+        if self.pattern.is_none() && self.filter.is_none() {
+            return Err(String::from("A highlight needs a pattern or a filter"));
+        }
+        let pattern = self.pattern.as_deref().map(Regex::new).transpose()?;
+        let filter = self.filter.as_deref().map(Filter::parse).transpose()?;
+
+        let color = |name: &Option<String>| match name {
+            Some(name) => parse_color(name).map(Some).ok_or_else(|| format!("Unknown color: {}", name)),
+            None => Ok(None),
+        };
+        let mut style = Style::default();
+        if let Some(color) = color(&self.color)? {
+            style = style.fg(color);
+        }
+        if let Some(background) = color(&self.background)? {
+            style = style.bg(background);
+        }
+        if self.bold {
+            style = style.add_modifier(Modifier::BOLD);
+        }
+
+        self.compiled = Some((pattern, filter, style));
+        Ok(())
+    }
+
+    fn applies_to(&self, entry: &DebugEntry) -> bool {
+        match &self.compiled {
+            Some((_, Some(filter), _)) => filter.matches(entry),
+            Some((_, None, _)) => true,
+            None => false,
+        }
+    }
+
+    // Whether the pattern matches the label or a value of the data, or there is no pattern.
+    fn matches_content(&self, entry: &DebugEntry) -> bool {
+        match &self.compiled {
+            Some((Some(pattern), _, _)) => {
+                matches_text(pattern, &entry.label) || entry.data.values().any(|value| matches_value(pattern, value))
+            }
+            _ => true,
+        }
+    }
+}
+
+fn matches_text(pattern: &Regex, text: &str) -> bool {
+    text.len() <= MAX_TEXT_LENGTH && pattern.is_match(text)
+}
+
+fn matches_value(pattern: &Regex, value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.iter().any(|(key, value)| matches_text(pattern, key) || matches_value(pattern, value)),
+        Value::Array(items) => items.iter().any(|item| matches_value(pattern, item)),
+        Value::String(text) => matches_text(pattern, text),
+        value => matches_text(pattern, &value.to_string()),
+    }
+}
+
+// The style of the first `row` rule applying to the entry.
+pub fn row_style(rules: &[HighlightRule], entry: &DebugEntry) -> Option<Style> {
+    rules
+        .iter()
+        .filter(|rule| rule.row)
+        .find(|rule| rule.applies_to(entry) && rule.matches_content(entry))
+        .and_then(|rule| rule.compiled.as_ref().map(|(_, _, style)| *style))
+}
+
+// Styles the matches of the rules applying to the entry in its rendered details.
+pub fn highlight(rules: &[HighlightRule], entry: &DebugEntry, lines: Vec<Spans<'static>>) -> Vec<Spans<'static>> {
+    let patterns: Vec<(&Regex, Style)> = rules
+        .iter()
+        .filter(|rule| rule.applies_to(entry))
+        .filter_map(|rule| match &rule.compiled {
+            Some((Some(pattern), _, style)) => Some((pattern, *style)),
+            _ => None,
+        })
+        .collect();
+    if patterns.is_empty() {
+        return lines;
+    }
+
+    lines
+        .into_iter()
+        .map(|line| Spans::from(line.0.into_iter().flat_map(|span| highlight_span(&patterns, span)).collect::<Vec<_>>()))
+        .collect()
+}
+
+// Splits the span at the matches of the patterns, the first pattern matching a char wins.
+fn highlight_span(patterns: &[(&Regex, Style)], span: Span<'static>) -> Vec<Span<'static>> {
+    let chars: Vec<char> = span.content.chars().collect();
+    if chars.len() > MAX_TEXT_LENGTH {
+        return vec![span];
+    }
+
+    let mut styles: Vec<Option<Style>> = vec![None; chars.len()];
+    for (pattern, style) in patterns {
+        for (start, end) in pattern.find_all(&span.content) {
+            for styled in styles[start..end].iter_mut().filter(|styled| styled.is_none()) {
+                *styled = Some(*style);
+            }
+        }
+    }
+    if styles.iter().all(Option::is_none) {
+        return vec![span];
+    }
+
+    let mut spans: Vec<Span<'static>> = vec![];
+    let mut start = 0;
+    for end in 1..=chars.len() {
+        if end == chars.len() || styles[end] != styles[start] {
+            let text: String = chars[start..end].iter().collect();
+            let style = match styles[start] {
+                Some(style) => span.style.patch(style),
+                None => span.style,
+            };
+            spans.push(Span::styled(text, style));
+            start = end;
+        }
+    }
+
+    spans
+}
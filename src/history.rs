@@ -0,0 +1,72 @@
+use crate::config::Config;
+use crate::prompt::PromptAction;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+// Older values beyond this many per prompt are forgotten.
+const MAX_VALUES: usize = 100;
+
+// Searches and palette commands typed before, oldest first, kept in `history.json` next to the
+// config file so they survive restarting udl.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct History {
+    #[serde(default)]
+    search: Vec<String>,
+    #[serde(default)]
+    command: Vec<String>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl History {
+    // Loads the history, starting empty when there is none or it cannot be read.
+    pub fn load() -> History {
+        let path = Config::path().and_then(|config| Some(config.parent()?.join("history.json")));
+        let mut history: History = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        history.path = path;
+
+        history
+    }
+
+    // The values typed in prompts for the action, oldest first.
+    pub fn values(&self, action: PromptAction) -> &[String] {
+        match action {
+            PromptAction::Search => &self.search,
+            PromptAction::Command => &self.command,
+            _ => &[],
+        }
+    }
+
+    // Remembers a submitted value as the most recent one and saves the history.
+    pub fn add(&mut self, action: PromptAction, value: &str) {
+        let values = match action {
+            PromptAction::Search => &mut self.search,
+            PromptAction::Command => &mut self.command,
+            _ => return,
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        values.retain(|existing| existing != value);
+        values.push(value.to_string());
+        if values.len() > MAX_VALUES {
+            values.remove(0);
+        }
+
+        // Losing the history is not worth interrupting for.
+        if let Some(path) = &self.path {
+            if let Some(directory) = path.parent() {
+                let _ = fs::create_dir_all(directory);
+            }
+            if let Ok(content) = serde_json::to_string_pretty(self) {
+                let _ = fs::write(path, content);
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::filter::Filter;
+use crate::DebugEntry;
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+
+// Hooks started, numbering the files holding their entries.
+static RUNS: AtomicU64 = AtomicU64::new(0);
+
+// Runs a shell command on the entries matching the filter, configured as `hooks` in the config file,
+// like capturing the state of the environment the moment an error is dumped. In the command,
+// `{entry_json_path}` is replaced with a file holding the entry as json, removed once the command
+// finishes, and `{id}` with the id of the entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hook {
+    pub command: String,
+    // Filter expression, the command runs on every entry without one.
+    pub filter: Option<String>,
+    // Whether the output of the command is added to the note of the entry.
+    #[serde(default)]
+    pub note: bool,
+    #[serde(skip)]
+    compiled: Option<Filter>,
+}
+
+// A hook that finished running on an entry.
+pub struct Finished {
+    pub id: u64,
+    pub command: String,
+    pub note: bool,
+    // What the command wrote to stdout, or why it failed.
+    pub output: Result<String, String>,
+}
+
+impl Hook {
+    // Parses the filter, done once when the config is loaded.
+    pub fn compile(&mut self) -> Result<(), String> {
+        self.compiled = self.filter.as_deref().map(Filter::parse).transpose()?;
+        Ok(())
+    }
+
+    pub fn matches(&self, entry: &DebugEntry) -> bool {
+        match (&self.filter, &self.compiled) {
+            (Some(_), Some(filter)) => filter.matches(entry),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    // Runs in the background, calling `on_finished` from there once the command exits.
+    pub fn run<F: FnOnce(Finished) + Send + 'static>(&self, entry: &DebugEntry, on_finished: F) {
+        let (id, command, note) = (entry.id, self.command.clone(), self.note);
+        let json = serde_json::to_string_pretty(entry).unwrap_or_default();
+        thread::spawn(move || {
+            let output = execute(&command, id, &json);
+            on_finished(Finished { id, command, note, output });
+        });
+    }
+}
+
+fn execute(command: &str, id: u64, json: &str) -> Result<String, String> {
+    // Named after the process as well, for hooks of udl instances on other ports.
+    let run = RUNS.fetch_add(1, Ordering::Relaxed);
+    let path = env::temp_dir().join(format!("udl-entry-{}-{}.json", std::process::id(), run));
+    fs::write(&path, json).map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+    let quoted = format!("'{}'", path.display().to_string().replace('\'', "'\\''"));
+    let command = command.replace("{entry_json_path}", &quoted).replace("{id}", &id.to_string());
+
+    let output = Command::new("sh").arg("-c").arg(command).stdin(Stdio::null()).output();
+    let _ = fs::remove_file(&path);
+    let output = output.map_err(|error| error.to_string())?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("{}: {}", output.status, stderr.trim()))
+    }
+}
@@ -0,0 +1,95 @@
+use async_std::{
+    io::{self, BufReader},
+    net::TcpStream,
+    prelude::*,
+};
+
+// The parts of an http request the built in servers look at.
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    // Decoded query parameters, in the order they were given.
+    pub query: Vec<(String, String)>,
+}
+
+impl Request {
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// Reads the request line and skips the headers, returning `None` for a malformed request.
+pub async fn read_request(stream: &TcpStream) -> io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method, target),
+        _ => return Ok(None),
+    };
+    let (path, query) = match target.find('?') {
+        Some(index) => (&target[..index], &target[index + 1..]),
+        None => (target, ""),
+    };
+
+    Ok(Some(Request {
+        method: method.to_string(),
+        path: decode(path),
+        query: query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.find('=') {
+                Some(index) => (decode(&pair[..index]), decode(&pair[index + 1..])),
+                None => (decode(pair), String::new()),
+            })
+            .collect(),
+    }))
+}
+
+pub async fn respond(mut stream: &TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+// Decodes `%xx` escapes and `+` as a space.
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded: Vec<u8> = vec![];
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -0,0 +1,144 @@
+use std::io::{self, Write};
+use tui::buffer::{Buffer, Cell};
+use tui::layout::Rect;
+use tui::style::{Color, Modifier};
+use tui::widgets::Widget;
+
+// Shortest visible part of a text that is still turned into a link when the table truncated it.
+const MIN_MATCH: usize = 8;
+
+pub fn url(template: &str, file: &str, line: i64) -> String {
+    template.replace("{file}", file).replace("{line}", &line.to_string())
+}
+
+// A rendered cell that should become part of a hyperlink.
+pub struct LinkedCell {
+    x: u16,
+    y: u16,
+    cell: Cell,
+    url: String,
+}
+
+// Finds already rendered texts and records their cells. tui measures the width of a cell by its
+// symbol, so the escape sequences cannot be stored in the buffer; instead the recorded cells are
+// written again, wrapped in OSC 8 hyperlinks, with `write_links` once the frame is drawn.
+pub struct Hyperlinks<'a> {
+    // Pairs of the text as it is displayed and the url it links to.
+    pub links: Vec<(String, String)>,
+    pub cells: &'a mut Vec<LinkedCell>,
+}
+
+impl<'a> Widget for Hyperlinks<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            let symbols: Vec<&str> = (area.left()..area.right())
+                .map(|x| buf.get(x, y).symbol.as_str())
+                .collect();
+            if symbols.iter().any(|symbol| symbol.chars().count() != 1) {
+                continue;
+            }
+            let line: Vec<char> = symbols.iter().filter_map(|symbol| symbol.chars().next()).collect();
+
+            for (text, url) in &self.links {
+                let text: Vec<char> = text.chars().collect();
+                if let Some((start, length)) = find_visible(&line, &text) {
+                    for offset in start..start + length {
+                        let x = area.left() + offset as u16;
+                        self.cells.push(LinkedCell {
+                            x,
+                            y,
+                            cell: buf.get(x, y).clone(),
+                            url: url.to_string(),
+                        });
+                    }
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Writes the recorded cells again with their style, each wrapped in its own hyperlink.
+pub fn write_links<W: Write>(out: &mut W, cells: &[LinkedCell]) -> io::Result<()> {
+    for linked in cells {
+        write!(
+            out,
+            "{}\x1b[0;{}m\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\",
+            termion::cursor::Goto(linked.x + 1, linked.y + 1),
+            sgr(&linked.cell),
+            linked.url,
+            linked.cell.symbol
+        )?;
+    }
+    if !cells.is_empty() {
+        write!(out, "\x1b[0m")?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+// Finds the text in the line, allowing it to be cut off after at least `MIN_MATCH` characters.
+fn find_visible(line: &[char], text: &[char]) -> Option<(usize, usize)> {
+    let needle = &text[..text.len().min(MIN_MATCH)];
+    if needle.is_empty() || needle.len() > line.len() {
+        return None;
+    }
+
+    let start = line.windows(needle.len()).position(|window| window == needle)?;
+    let length = line[start..]
+        .iter()
+        .zip(text.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    Some((start, length))
+}
+
+// Select graphic rendition parameters for the style of a cell.
+pub fn sgr(cell: &Cell) -> String {
+    let mut parameters = vec![color_parameter(cell.fg, 30), color_parameter(cell.bg, 40)];
+
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, parameter) in modifiers.iter() {
+        if cell.modifier.contains(*modifier) {
+            parameters.push(parameter.to_string());
+        }
+    }
+
+    parameters.join(";")
+}
+
+// Color parameter where `base` is 30 for the foreground and 40 for the background.
+fn color_parameter(color: Color, base: u8) -> String {
+    let index = match color {
+        Color::Reset => return (base + 9).to_string(),
+        Color::Rgb(r, g, b) => return format!("{};2;{};{};{}", base + 8, r, g, b),
+        Color::Indexed(index) => return format!("{};5;{}", base + 8, index),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 60,
+        Color::LightRed => 61,
+        Color::LightGreen => 62,
+        Color::LightYellow => 63,
+        Color::LightBlue => 64,
+        Color::LightMagenta => 65,
+        Color::LightCyan => 66,
+        Color::White => 67,
+    };
+
+    (base + index).to_string()
+}
@@ -0,0 +1,103 @@
+use crate::DebugEntry;
+use serde_json::Value;
+
+// The searchable content of an entry: the lowercased keys and values of its data and backtrace,
+// with the path they are found at. Built once as the entry arrives, so searching does not walk and
+// serialize the data of every entry again on each change of the filter.
+#[derive(Debug, Default)]
+pub struct Document {
+    // Keys are found at the path of the object holding them. Paths leave out array indexes, like
+    // the filter paths going through arrays.
+    fields: Vec<(String, String)>,
+}
+
+impl Document {
+    pub fn new(entry: &DebugEntry) -> Document {
+        let mut document = Document::default();
+        for (key, value) in entry.data.iter() {
+            document.push(String::from("data"), key);
+            document.add(format!("data.{}", key), value);
+        }
+        for frame in entry.backtrace.iter() {
+            document.push(String::from("backtrace.file"), &frame.file);
+            document.push(String::from("backtrace.line"), &frame.line.to_string());
+            document.push(String::from("backtrace.function"), &frame.function);
+            if let Some(column) = frame.column {
+                document.push(String::from("backtrace.column"), &column.to_string());
+            }
+        }
+
+        document
+    }
+
+    fn add(&mut self, path: String, value: &Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter() {
+                    self.push(path.clone(), key);
+                    self.add(format!("{}.{}", path, key), value);
+                }
+            }
+            Value::Array(items) => {
+                for item in items.iter() {
+                    self.add(path.clone(), item);
+                }
+            }
+            Value::String(text) => self.push(path, text),
+            Value::Null => self.push(path, "null"),
+            value => self.push(path, &value.to_string()),
+        }
+    }
+
+    fn push(&mut self, path: String, text: &str) {
+        self.fields.push((path, text.to_lowercase()));
+    }
+
+    // Whether a key or value at or below the path contains the lowercased text. An empty path
+    // searches all of them.
+    pub fn contains(&self, path: &str, text: &str) -> bool {
+        self.fields.iter().any(|(field, value)| {
+            let within = path.is_empty()
+                || field.strip_prefix(path).is_some_and(|rest| rest.is_empty() || rest.starts_with('.'));
+            within && value.contains(text)
+        })
+    }
+}
+
+// Documents of the buffered entries, at the same positions as the entries: newest first, with the
+// ones paged in from disk at the end.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    documents: Vec<Document>,
+}
+
+impl SearchIndex {
+    pub fn get(&self, index: usize) -> Option<&Document> {
+        self.documents.get(index)
+    }
+
+    pub fn push(&mut self, entry: &DebugEntry) {
+        self.documents.insert(0, Document::new(entry));
+    }
+
+    pub fn extend<'a>(&mut self, entries: impl Iterator<Item = &'a DebugEntry>) {
+        self.documents.extend(entries.map(Document::new));
+    }
+
+    pub fn update(&mut self, index: usize, entry: &DebugEntry) {
+        if let Some(document) = self.documents.get_mut(index) {
+            *document = Document::new(entry);
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.documents.len() {
+            self.documents.remove(index);
+        }
+    }
+
+    // Drops the documents of evicted entries.
+    pub fn truncate(&mut self, len: usize) {
+        self.documents.truncate(len);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::theme::parse_color;
+use crate::DebugEntry;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tui::style::{Color, Style};
+use tui::text::Span;
+
+// What an entry is about, guessed from its data, shown as a marker in front of its label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    ParseError,
+    Exception,
+    Query,
+    Http,
+    Measure,
+    Metrics,
+    Progress,
+    Dump,
+    Log,
+}
+
+// Tags marking an entry as a log message.
+const LOG_LEVELS: [&str; 8] = ["debug", "info", "notice", "warning", "warn", "error", "critical", "alert"];
+
+// Glyph and color of the marker of a kind, set with `type_markers` in the config. An empty glyph
+// hides the marker.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TypeMarker {
+    pub glyph: Option<String>,
+    pub color: Option<String>,
+}
+
+impl Kind {
+    pub fn of(entry: &DebugEntry) -> Option<Kind> {
+        let has = |key: &str| entry.data.contains_key(key);
+        if has("parse_error") || has("payload_too_large") {
+            Some(Kind::ParseError)
+        } else if has("exception") || entry.data.get("error").is_some_and(Value::is_object) {
+            Some(Kind::Exception)
+        } else if has("query") || has("sql") {
+            Some(Kind::Query)
+        } else if has("request") || has("response") || (has("method") && has("url")) {
+            Some(Kind::Http)
+        } else if has("measure_start") || has("measure_stop") {
+            Some(Kind::Measure)
+        } else if has("metrics") {
+            Some(Kind::Metrics)
+        } else if has("progress") {
+            Some(Kind::Progress)
+        } else if has("vardumper") {
+            Some(Kind::Dump)
+        } else if has("level") || entry.tags.iter().any(|tag| LOG_LEVELS.contains(&tag.to_lowercase().as_str())) {
+            Some(Kind::Log)
+        } else {
+            None
+        }
+    }
+
+    fn glyph(self) -> &'static str {
+        match self {
+            Kind::ParseError => "!",
+            Kind::Exception => "✗",
+            Kind::Query => "◆",
+            Kind::Http => "⇄",
+            Kind::Measure => "◷",
+            Kind::Metrics => "▮",
+            Kind::Progress => "▸",
+            Kind::Dump => "◇",
+            Kind::Log => "≡",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Kind::ParseError => Color::LightRed,
+            Kind::Exception => Color::Red,
+            Kind::Query => Color::Cyan,
+            Kind::Http => Color::Magenta,
+            Kind::Measure => Color::Yellow,
+            Kind::Metrics => Color::Green,
+            Kind::Progress => Color::Blue,
+            Kind::Dump => Color::LightBlue,
+            Kind::Log => Color::Gray,
+        }
+    }
+}
+
+// The marker of the kind of the entry followed by a space, or two spaces for entries of no kind to
+// keep the labels aligned.
+pub fn marker(entry: &DebugEntry, markers: &HashMap<Kind, TypeMarker>) -> Span<'static> {
+    let kind = match Kind::of(entry) {
+        Some(kind) => kind,
+        None => return Span::raw("  "),
+    };
+    let configured = markers.get(&kind);
+    let glyph = configured.and_then(|marker| marker.glyph.as_deref()).unwrap_or(kind.glyph());
+    if glyph.is_empty() {
+        return Span::raw("  ");
+    }
+    let color = configured
+        .and_then(|marker| marker.color.as_deref())
+        .and_then(parse_color)
+        .unwrap_or(kind.color());
+
+    Span::styled(format!("{} ", glyph), Style::default().fg(color))
+}
@@ -0,0 +1,58 @@
+use crate::DebugEntry;
+use serde::Deserialize;
+
+// Rewrites the labels of the entries as they are received, configured as `labels` in the config
+// file, so the differing labels of client libraries end up as one, like
+// `{"match": "App\\Jobs\\*", "label": "job"}`. The first rule matching the whole label applies.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabelRule {
+    // The labels to rewrite, where `*` matches any text.
+    #[serde(rename = "match")]
+    pub pattern: String,
+    // The label instead, where `$1` is the text matched by the first `*` and so on.
+    pub label: String,
+}
+
+impl LabelRule {
+    // The rewritten label, when the rule matches it.
+    fn rewrite(&self, label: &str) -> Option<String> {
+        let pattern: Vec<char> = self.pattern.chars().collect();
+        let text: Vec<char> = label.chars().collect();
+        let mut captures = vec![];
+        if !glob(&pattern, &text, &mut captures) {
+            return None;
+        }
+
+        // From the last, so `$1` does not replace the start of `$10`.
+        let mut rewritten = self.label.clone();
+        for (index, capture) in captures.iter().enumerate().rev() {
+            rewritten = rewritten.replace(&format!("${}", index + 1), capture);
+        }
+
+        Some(rewritten)
+    }
+}
+
+pub fn apply(rules: &[LabelRule], entry: &mut DebugEntry) {
+    if let Some(label) = rules.iter().find_map(|rule| rule.rewrite(&entry.label)) {
+        entry.label = label.into();
+    }
+}
+
+// Whether the text matches the pattern, collecting the texts matched by each `*`, shortest first.
+fn glob(pattern: &[char], text: &[char], captures: &mut Vec<String>) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => {
+            for end in 0..=text.len() {
+                captures.push(text[..end].iter().collect());
+                if glob(rest, &text[end..], captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+        Some((expected, rest)) => text.split_first().is_some_and(|(char, text)| char == expected && glob(rest, text, captures)),
+    }
+}
@@ -1,342 +1,3314 @@
-use async_std::{
-    io::BufReader,
-    net::{TcpListener, TcpStream},
-    prelude::*,
-    task,
-};
-use std::{env, io, thread};
+use async_std::task;
+use std::{env, fs, io, process};
 
+pub mod alert;
+pub mod arrangement;
+pub mod chart;
+pub mod cli;
+pub mod clock;
+pub mod clipboard;
+pub mod command;
+pub mod compare;
+pub mod completions;
+pub mod copy;
+pub mod config;
+pub mod daemon;
+pub mod demo;
+pub mod density;
+pub mod diagnostics;
+pub mod editor;
+pub mod export;
+pub mod extract;
+pub mod filter;
+pub mod fixture;
+pub mod git;
+pub mod group;
+pub mod headless;
+pub mod highlight;
+pub mod history;
+pub mod hook;
+pub mod http;
+pub mod hyperlink;
+pub mod index;
+pub mod kind;
+pub mod label;
+pub mod manpage;
+pub mod measure;
+pub mod notify;
+pub mod number;
+pub mod port;
+pub mod pane;
+pub mod preset;
+pub mod redact;
+pub mod progress;
+pub mod menu;
+pub mod raw;
+pub mod metrics;
+pub mod prompt;
+pub mod regex;
+pub mod renderer;
+pub mod run;
+pub mod script;
+pub mod section;
+pub mod send;
+pub mod snapshot;
+pub mod snippet;
+pub mod source;
+pub mod summary;
+pub mod sources;
+pub mod sourcemap;
+pub mod theme;
+pub mod throughput;
 pub mod util;
+pub mod watch;
+pub mod web;
+pub mod webhook;
+pub mod xdebug;
 
-use crate::util::event::{Event, Events};
-use async_std::sync::{Arc, Mutex};
-use serde::Deserialize;
+use crate::alert::{Alerts, FocusTerminal};
+use crate::arrangement::{Arrangement, Sort};
+use crate::chart::Series;
+use crate::cli::{Cli, Mode};
+use crate::clock::TimeZone;
+use crate::command::Command;
+use crate::compare::{Comparison, Line};
+use crate::config::{Column, Config};
+use crate::diagnostics::Diagnostics;
+use crate::git::Blame;
+use crate::group::Group;
+use crate::history::History;
+use crate::filter::{Filter, Moment};
+use crate::hyperlink::{Hyperlinks, LinkedCell};
+use crate::index::SearchIndex;
+use crate::kind::{Kind, TypeMarker};
+use crate::measure::{format_duration, Measure, Timers};
+use crate::menu::{Menu, MenuResult};
+use crate::metrics::{format_bytes, Metrics};
+use crate::density::{Density, Truncation};
+use crate::notify::Notifier;
+use crate::number::NumberFormat;
+use crate::pane::Pane;
+use crate::port::Conflict;
+use crate::preset::Preset;
+use crate::progress::Progress;
+use crate::prompt::{Prompt, PromptAction, PromptResult};
+use crate::raw::RawPayloads;
+use crate::sources::Sources;
+use crate::label::LabelRule;
+use crate::redact::Redaction;
+use crate::renderer::{Renderer, Renderers};
+use crate::run::{OnNewRun, Runs};
+use crate::script::Script;
+use crate::section::Sections;
+use crate::snapshot::{Scope, Snapshot};
+use crate::theme::{parse_color, Theme};
+use crate::throughput::Throughput;
+use crate::util::event::{AppEvent, Config as EventConfig, Events};
+use crate::watch::Watches;
+use udl_core::spill::Spill;
+use udl_core::store::Store;
+use udl_core::tail;
+use udl_core::{listen_bounded, read_lines, session, timestamp, Backlog, Buffer, ParseError, RawPayload, Received};
 use serde_json::{Map, Value};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
-use termion::{event::Key, raw::IntoRawMode};
-use tui::layout::{Alignment, Constraint, Direction, Layout};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::ops::Range;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+use std::io::Write;
+use termion::{event::Key, input::MouseTerminal, raw::IntoRawMode};
+use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Modifier, Style};
-use tui::Terminal;
 use tui::{
-    backend::TermionBackend,
-    text::Spans,
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
+    backend::{Backend, TermionBackend},
+    text::{Span, Spans},
+    symbols,
+    widgets::{Axis, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, Paragraph, Row, Sparkline, Table, TableState, Wrap},
 };
+use tui::{Frame, Terminal};
 
 extern crate jsonxf;
 
-#[derive(Debug, Deserialize)]
-struct BacktraceItem {
-    file: String,
-    line: i64,
-    function: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct DebugEntry {
-    label: String,
-    time: String,
-    data: HashMap<String, Value>,
-    backtrace: Vec<BacktraceItem>,
-}
+pub use udl_core::{BacktraceItem, DebugEntry};
 
 // Table holding all the logging values.
 pub struct StatefulTable {
+    // Selection within the visible entries.
     state: TableState,
-    items: Vec<DebugEntry>,
+    // First of the visible entries shown in the list, only the rows on screen are built.
+    offset: usize,
+    buffer: Buffer,
+    // Searchable content of the buffered entries, at the same indexes.
+    index: SearchIndex,
+    // Indexes of the buffered items matching the filter, newest first like the items.
+    visible: Vec<usize>,
+    filter: Option<Filter>,
+    timers: Timers,
+    // The last error of the `--script` transforming the entries.
+    script_error: Option<String>,
+    // Memory usage reported by `metrics` entries, in order of arrival.
+    memory_history: Vec<u64>,
+    // Counts changes to entries already in the list, after which their rendered details are outdated.
+    revision: u64,
+    // Where clients start a new run, with `--on-new-run`.
+    runs: Option<Runs>,
+    // Id of the entry showing each task in progress, updated in place until it is done.
+    tasks: HashMap<String, u64>,
+    // Id of the first entry with each label and data, by their hash, and of that first entry for each
+    // later one with the same, to spot entries sent twice.
+    first_by_hash: HashMap<u64, u64>,
+    duplicates: HashMap<u64, u64>,
+    // Bytes of data of all the entries received, including the evicted and deleted ones.
+    received_bytes: u64,
+    // Payloads of the latest entries as received, before parsing.
+    raw: RawPayloads,
+    throughput: Throughput,
+    watches: Watches,
+    // Groups sent by clients, putting the entries in between in a section that can be folded.
+    sections: Sections,
+    // Sensitive values masked in the received entries.
+    redaction: Redaction,
+    // Rewrites of the labels of the received entries.
+    label_rules: Vec<LabelRule>,
+    // Entries matching one of these pause the list, like a breakpoint.
+    pause_rules: Vec<Filter>,
+    // While paused, the entries received since with their payload, added once resumed.
+    held: Option<Vec<(DebugEntry, Option<Rc<RawPayload>>)>>,
+    // Id of the entry that paused the list.
+    paused_on: Option<u64>,
+    // Sources muted or held back on their own.
+    sources: Sources,
+    // The list without focus while the entries are split over two lists.
+    other: Option<Pane>,
+    // Order of both lists by a column instead of newest first.
+    sort: Option<Sort>,
+    // When udl started, for the session summary.
+    started: SystemTime,
 }
 
 impl StatefulTable {
     fn new() -> StatefulTable {
         StatefulTable {
             state: TableState::default(),
-            items: Vec::new(),
+            offset: 0,
+            buffer: Buffer::new(),
+            index: SearchIndex::default(),
+            visible: Vec::new(),
+            filter: None,
+            timers: Timers::default(),
+            script_error: None,
+            memory_history: Vec::new(),
+            revision: 0,
+            runs: None,
+            tasks: HashMap::new(),
+            first_by_hash: HashMap::new(),
+            duplicates: HashMap::new(),
+            received_bytes: 0,
+            raw: RawPayloads::new(0),
+            throughput: Throughput::new(),
+            watches: Watches::default(),
+            redaction: Redaction::default(),
+            label_rules: vec![],
+            sections: Sections::default(),
+            pause_rules: vec![],
+            held: None,
+            paused_on: None,
+            sources: Sources::default(),
+            other: None,
+            sort: None,
+            started: SystemTime::now(),
         }
     }
 
-    pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    // Removes the entries from the list, they stay in the `--db` store.
+    fn clear(&mut self) {
+        self.buffer.items.clear();
+        self.buffer.paged = 0;
+        self.index.truncate(0);
+        self.visible.clear();
+        self.state.select(None);
+        self.offset = 0;
+        if let Some(other) = self.other.as_mut() {
+            other.visible.clear();
+            other.state.select(None);
+            other.offset = 0;
+        }
+        self.revision += 1;
+    }
+
+    // Clears or separates the entries so far when the entry starts a new run.
+    fn start_run(&mut self, entry: &DebugEntry) {
+        let runs = match self.runs.as_mut() {
+            Some(runs) => runs,
+            None => return,
         };
-        self.state.select(Some(i));
+        let name = match runs.starts_run(entry) {
+            Some(name) => name,
+            None => return,
+        };
+        match runs.on_new_run {
+            OnNewRun::Clear => self.clear(),
+            OnNewRun::Separate => {
+                self.push(marker_entry(Some(name)));
+            }
+        }
     }
 
-    pub fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len() - 1
-                } else {
-                    i - 1
-                }
+    // Adds a received entry with the payload it came in, returning its id, or holds it back while
+    // paused. An entry matching a pause rule is selected and pauses the list.
+    fn add(&mut self, entry: DebugEntry, raw: Option<Rc<RawPayload>>) -> Option<u64> {
+        let (entry, raw) = self.sources.hold(entry, raw)?;
+        if let Some(held) = self.held.as_mut() {
+            held.push((entry, raw));
+            return None;
+        }
+
+        let pause = self.pause_rules.iter().any(|rule| rule.matches(&entry));
+        self.start_run(&entry);
+        let id = self.push(entry);
+        self.raw.keep(id, raw);
+        if pause {
+            self.held = Some(vec![]);
+            self.paused_on = Some(id);
+            if let Some(position) = self.visible.iter().position(|index| self.buffer.items[*index].id == id) {
+                self.state.select(Some(position));
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
+        }
+
+        Some(id)
     }
-}
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let stdout = io::stdout().into_raw_mode()?;
-    let backend = TermionBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    fn pause(&mut self) {
+        self.held.get_or_insert_with(Vec::new);
+    }
+
+    // Adds the entries held back from a source, or all sources.
+    fn resume_source(&mut self, held: sources::Held) {
+        for (entry, raw) in held {
+            self.add(entry, raw);
+        }
+    }
+
+    // Adds the entries held back, until one of them pauses the list again.
+    fn resume(&mut self) {
+        self.paused_on = None;
+        for (entry, raw) in self.held.take().unwrap_or_default() {
+            self.add(entry, raw);
+        }
+    }
+
+    // Adds the entry, returning the id of the entry showing it.
+    fn push(&mut self, mut entry: DebugEntry) -> u64 {
+        // Updates replace the entry with the same ref, or are added as a new entry when there is none.
+        if entry.update {
+            entry.update = false;
+            let reference = entry.reference.as_ref();
+            if let Some(index) = self.buffer.items.iter().position(|item| item.reference.is_some() && item.reference.as_ref() == reference) {
+                self.update_at(index, entry);
+                return self.buffer.items[index].id;
+            }
+        }
+        self.sections.place(&mut entry);
+        let progress = Progress::from_data(&entry.data);
+        if let Some(progress) = &progress {
+            let task = self.tasks.get(&progress.task).copied();
+            entry = match self.update_progress(progress, entry) {
+                Some(entry) => entry,
+                None => return task.unwrap_or_default(),
+            };
+        }
 
-    let events = Events::new();
+        match Measure::from_data(&entry.data) {
+            Some(Measure::Start(name)) => self.timers.start(name),
+            Some(Measure::Stop(name)) => entry.elapsed = self.timers.stop(&name),
+            None => {}
+        }
+
+        if let Some(memory) = Metrics::from_data(&entry.data).and_then(|metrics| metrics.memory) {
+            self.memory_history.push(memory);
+        }
+
+        for index in self.visible.iter_mut() {
+            *index += 1;
+        }
+        self.index.push(&entry);
+        let matches = self.filter.as_ref().is_none_or(|filter| filter.matches_indexed(&entry, self.index.get(0))) && !self.sections.hides(&entry);
+        let matches_other = self.other.as_ref().is_some_and(|other| {
+            other.filter.as_ref().is_none_or(|filter| filter.matches_indexed(&entry, self.index.get(0))) && !self.sections.hides(&entry)
+        });
+        if let Some(other) = self.other.as_mut() {
+            other.shift();
+        }
 
-    let mutex_table = Arc::new(Mutex::new(StatefulTable::new()));
+        // Json objects are sorted by key, so the same data gives the same text.
+        let payload = serde_json::to_value(&entry.data).unwrap_or_default().to_string();
+        entry.size = payload.len();
+        self.received_bytes += payload.len() as u64;
 
-    let args: Vec<String> = env::args().collect();
+        let marker = filter::is_marker(&entry);
+        let label = entry.label.clone();
+        let id = self.buffer.push(entry);
+        self.index.truncate(self.buffer.items.len());
+        // Gone already when evicted or expired right away.
+        match self.buffer.items.first() {
+            Some(entry) if entry.id == id => self.watches.observe(entry),
+            _ => {
+                self.drop_removed();
+                return id;
+            }
+        }
+        // Sorted lists get the entry where it belongs, others at the top.
+        let items = &self.buffer.items;
+        if matches {
+            let position = self.sort.as_ref().map_or(0, |sort| sort.position(items, &self.visible, 0));
+            self.visible.insert(position, 0);
+        }
+        if let Some(other) = self.other.as_mut().filter(|_| matches_other) {
+            let position = self.sort.as_ref().map_or(0, |sort| sort.position(items, &other.visible, 0));
+            other.visible.insert(position, 0);
+        }
+        if let Some(progress) = progress.filter(|progress| !progress.is_done()) {
+            self.tasks.insert(progress.task, id);
+        }
+        self.find_duplicate(id, &label, &payload);
+        // A new marker hides the entries before it.
+        if marker && self.filter.as_ref().is_some_and(Filter::uses_marker) {
+            let filter = self.filter.clone();
+            self.set_filter(filter);
+        }
+        if marker && self.other.as_ref().is_some_and(|other| other.filter.as_ref().is_some_and(Filter::uses_marker)) {
+            self.refresh_other();
+        }
 
-    let mut port: i32 = 9337;
+        self.drop_removed();
 
-    if args.len() == 2 {
-        port = args[1].parse().unwrap();
+        id
     }
 
-    // Thread to listen for incoming connections.
-    let thread_table = Arc::clone(&mutex_table);
-    thread::spawn(move || {
-        task::block_on(handle_tcp(port, thread_table)).unwrap();
-    });
+    // Drops the entries evicted or expired from the buffer from the visible ones.
+    fn drop_removed(&mut self) {
+        let length = self.buffer.items.len();
+        self.visible.retain(|index| *index < length);
+        if self.state.selected().is_some_and(|index| index >= self.visible.len()) {
+            self.state.select(self.visible.len().checked_sub(1));
+        }
+        if let Some(other) = self.other.as_mut() {
+            other.truncate(self.buffer.items.len());
+        }
+    }
 
-    loop {
-        terminal
-            .draw(|f| {
-                let table: &mut StatefulTable = &mut mutex_table.try_lock().unwrap();
-                let layout = Layout::default()
-                    .direction(Direction::Horizontal)
-                    .constraints([Constraint::Percentage(25), Constraint::Percentage(75)].as_ref())
-                    .split(f.size());
+    // Removes the entries older than the --ttl, returning how many.
+    fn expire(&mut self) -> usize {
+        let expired = self.buffer.expire();
+        if expired > 0 {
+            self.index.truncate(self.buffer.items.len());
+            self.drop_removed();
+            self.revision += 1;
+        }
 
-                let selected_style = Style::default().add_modifier(Modifier::REVERSED);
-                let normal_style = Style::default().bg(Color::Blue);
-                let header_cells = vec![Cell::from("Entry")];
-                let header = Row::new(header_cells).style(normal_style).bottom_margin(1);
-                let rows = table.items.iter().map(|item| {
-                    let cells = vec![Cell::from(item.label.as_str())];
-                    Row::new(cells)
-                });
-                let table_widget = Table::new(rows)
-                    .header(header)
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title("Entries")
-                            .style(Style::default().bg(Color::Black)),
-                    )
-                    .highlight_style(selected_style)
-                    .highlight_symbol("> ")
-                    .widths(&[
-                        Constraint::Percentage(100),
-                        Constraint::Length(30),
-                        Constraint::Max(10),
-                    ]);
-                f.render_stateful_widget(table_widget, layout[0], &mut table.state);
+        expired
+    }
 
-                let detail_rects = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
-                    .split(layout[1]);
+    // Applies filters like `last 2m` again, entries drop out of them as time passes. Returns whether
+    // that changed the listed entries.
+    fn refilter_clock(&mut self) -> bool {
+        let mut changed = false;
+        if self.filter.as_ref().is_some_and(Filter::uses_clock) {
+            let listed = self.visible.len();
+            let filter = self.filter.clone();
+            self.set_filter(filter);
+            changed = self.visible.len() != listed;
+        }
+        if let Some(listed) = self.other.as_ref().filter(|other| other.filter.as_ref().is_some_and(Filter::uses_clock)).map(|other| other.visible.len()) {
+            self.refresh_other();
+            changed |= self.other.as_ref().is_some_and(|other| other.visible.len() != listed);
+        }
 
-                let detail_widget = Block::default()
-                    .borders(Borders::ALL)
-                    .title("Details")
-                    .style(Style::default().bg(Color::Black));
+        changed
+    }
 
-                let backtrace_widget = Block::default()
-                    .borders(Borders::ALL)
-                    .title("Backtrace")
-                    .style(Style::default().bg(Color::Black));
-
-                if table.items.len() > 0 {
-                    // Set the last item to be selected if no selection is active yet.
-                    let item = match table.state.selected() {
-                        None => {
-                            let last_index = table.items.len() - 1;
-                            table.state.select(Option::from(last_index));
-                            table.items.get(last_index).unwrap()
-                        }
-                        Some(index) => table.items.get(index).unwrap(),
-                    };
+    // Remembers the entry just added as a duplicate when an earlier one has the same label and data.
+    fn find_duplicate(&mut self, id: u64, label: &str, payload: &str) {
+        let mut hasher = DefaultHasher::new();
+        label.hash(&mut hasher);
+        payload.hash(&mut hasher);
 
-                    let text: Vec<Spans> = build_paragraph_for_item(&item);
+        let first = *self.first_by_hash.entry(hasher.finish()).or_insert(id);
+        if first != id {
+            self.duplicates.insert(id, first);
+        }
 
-                    let details = Paragraph::new(text)
-                        .block(detail_widget)
-                        .wrap(Wrap { trim: true })
-                        .alignment(Alignment::Left);
-                    f.render_widget(details, detail_rects[0]);
+        // Forget the evicted entries once they make up most of the hashes.
+        if self.first_by_hash.len() > self.buffer.items.len() * 2 + 1024 {
+            let ids: HashSet<u64> = self.buffer.items.iter().map(|item| item.id).collect();
+            self.first_by_hash.retain(|_, id| ids.contains(id));
+            self.duplicates.retain(|id, _| ids.contains(id));
+        }
+    }
 
-                    // Render the backtrace.
-                    let rows = item.backtrace.iter().map(|backtrace_item| {
-                        let cells = vec![
-                            Cell::from(backtrace_item.file.as_str()),
-                            Cell::from(backtrace_item.line.to_string()),
-                            Cell::from(backtrace_item.function.as_str()),
-                        ];
-                        Row::new(cells)
-                    });
-                    let heading = Row::new(vec![
-                        Cell::from("File"),
-                        Cell::from("Line"),
-                        Cell::from("Calling function"),
-                    ])
-                    .style(normal_style)
-                    .bottom_margin(1);
+    // Selects the first entry with the same label and data as the selected one, returning whether it
+    // is listed.
+    fn select_first_occurrence(&mut self) -> Result<(), String> {
+        let id = self.selected().map(|item| item.id).ok_or("No entry selected")?;
+        let first = *self.duplicates.get(&id).ok_or("No earlier entry with the same label and data")?;
+        let position = self
+            .visible
+            .iter()
+            .position(|index| self.buffer.items[*index].id == first)
+            .ok_or_else(|| format!("The first occurrence, entry {}, is not listed", first))?;
+        self.state.select(Some(position));
 
-                    let backtrace_table = Table::new(rows)
-                        .header(heading)
-                        .block(
-                            Block::default()
-                                .borders(Borders::ALL)
-                                .title("Backtrace")
-                                .style(Style::default().bg(Color::Black)),
-                        )
-                        .highlight_style(selected_style)
-                        .highlight_symbol("> ")
-                        .widths(&[
-                            Constraint::Percentage(50),
-                            Constraint::Percentage(10),
-                            Constraint::Percentage(40),
-                        ]);
+        Ok(())
+    }
 
-                    f.render_widget(backtrace_table, detail_rects[1]);
-                } else {
-                    f.render_widget(detail_widget, detail_rects[0]);
-                    f.render_widget(backtrace_widget, detail_rects[1]);
-                }
+    // Selects the first listed entry at or after the time, as shown in the time zone. A time of day is
+    // on the day of the newest listed entry.
+    fn select_time(&mut self, moment: Moment, time_zone: TimeZone) -> Option<u64> {
+        let items = &self.buffer.items;
+        let times: Vec<(usize, f64)> = self
+            .visible
+            .iter()
+            .filter_map(|index| Some((*index, time_zone.wall_clock(&items[*index].time)?)))
+            .collect();
+        let target = match moment {
+            Moment::At(time) => time,
+            Moment::Clock(clock) => {
+                let latest = times.iter().map(|(_, time)| *time).reduce(f64::max)?;
+                latest - latest.rem_euclid(86400.0) + clock
+            }
+        };
+        // Of entries with the same time the one received first, at the higher index.
+        let (index, _) = times
+            .into_iter()
+            .filter(|(_, time)| *time >= target)
+            .min_by(|(a_index, a), (b_index, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal).then(b_index.cmp(a_index)))?;
+        let position = self.visible.iter().position(|visible| *visible == index)?;
+        self.state.select(Some(position));
+
+        Some(items[index].id)
+    }
+
+    // Selects the entry with the id, with or without `#`, or else the newest one with the ref.
+    fn select_entry(&mut self, target: &str) -> Result<u64, String> {
+        let target = target.trim();
+        let id = target.trim_start_matches('#').parse::<u64>().ok();
+        let item = self
+            .buffer
+            .items
+            .iter()
+            .find(|item| Some(item.id) == id)
+            .or_else(|| self.buffer.items.iter().find(|item| item.reference.as_deref() == Some(target)))
+            .ok_or_else(|| format!("No entry {}", target))?;
+        let id = item.id;
+        let position = self
+            .visible
+            .iter()
+            .position(|index| self.buffer.items[*index].id == id)
+            .ok_or_else(|| format!("Entry #{} is not listed with the filter", id))?;
+        self.state.select(Some(position));
+
+        Ok(id)
+    }
+
+    // Updates the entry of the task with the progress, handing back the entry when the task has none
+    // yet.
+    fn update_progress(&mut self, progress: &Progress, entry: DebugEntry) -> Option<DebugEntry> {
+        let id = match self.tasks.get(&progress.task) {
+            Some(id) => *id,
+            None => return Some(entry),
+        };
+        let index = match self.buffer.items.iter().position(|item| item.id == id) {
+            Some(index) => index,
+            None => {
+                self.tasks.remove(&progress.task);
+                return Some(entry);
+            }
+        };
+
+        self.update_at(index, entry);
+        if progress.is_done() {
+            self.tasks.remove(&progress.task);
+        }
+
+        None
+    }
+
+    // Replaces the label, time, data and color of the entry at the index by those of the update.
+    fn update_at(&mut self, index: usize, update: DebugEntry) {
+        let payload = serde_json::to_value(&update.data).unwrap_or_default().to_string();
+        self.received_bytes += payload.len() as u64;
+        let item = &mut self.buffer.items[index];
+        item.label = update.label;
+        item.time = update.time;
+        item.data = update.data;
+        item.color = update.color;
+        item.size = payload.len();
+        self.index.update(index, item);
+        self.watches.observe(item);
+        self.revision += 1;
+        self.buffer.store_again(index);
+        // The update can move the entry within a sorted list.
+        if self.sort.is_some() {
+            self.sort_lists();
+        }
+    }
+
+    fn push_parse_error(&mut self, error: ParseError) -> u64 {
+        if let Some(index) = error.merge_into(&mut self.buffer.items) {
+            self.index.update(index, &self.buffer.items[index]);
+            self.revision += 1;
+            self.buffer.items[index].id
+        } else {
+            self.push(error.to_entry())
+        }
+    }
+
+    fn set_filter(&mut self, mut filter: Option<Filter>) {
+        let selected = self.state.selected().and_then(|index| self.visible.get(index).copied());
+        if let Some(filter) = filter.as_mut().filter(|filter| filter.uses_marker()) {
+            filter.set_marker(self.buffer.items.iter().find(|item| filter::is_marker(item)).map(|item| item.id));
+        }
+
+        let items = &self.buffer.items;
+        self.visible = (0..items.len())
+            .filter(|index| {
+                filter.as_ref().is_none_or(|filter| filter.matches_indexed(&items[*index], self.index.get(*index)))
+                    && !self.sections.hides(&items[*index])
             })
-            .unwrap();
+            .collect();
+        self.filter = filter;
+        if let Some(sort) = &self.sort {
+            sort.apply(items, &mut self.visible);
+        }
 
-        if let Event::Input(input) = events.next()? {
-            match input {
-                Key::Esc => {
-                    // Quit the loop and terminate the application.
-                    break;
-                }
-                Key::Char('j') => {
-                    let table: &mut StatefulTable = &mut mutex_table.try_lock().unwrap();
-                    table.next();
-                }
-                Key::Char('k') => {
-                    let table: &mut StatefulTable = &mut mutex_table.try_lock().unwrap();
-                    table.previous();
-                }
-                _ => {}
+        // Keep the selected entry selected when it is still visible.
+        let position = selected.and_then(|selected| self.visible.iter().position(|index| *index == selected));
+        self.state.select(position);
+    }
+
+    // Orders both lists by the sort, or newest first without one, keeping the selected entries
+    // selected.
+    fn set_sort(&mut self, sort: Option<Sort>) {
+        self.sort = sort;
+        self.sort_lists();
+    }
+
+    fn sort_lists(&mut self) {
+        let (items, order) = (&self.buffer.items, self.sort.as_ref());
+        let sort = |visible: &mut Vec<usize>, state: &mut TableState| {
+            let selected = state.selected().and_then(|index| visible.get(index).copied());
+            match order {
+                Some(sort) => sort.apply(items, visible),
+                None => visible.sort_unstable(),
             }
+            state.select(selected.and_then(|selected| visible.iter().position(|index| *index == selected)));
+        };
+        sort(&mut self.visible, &mut self.state);
+        if let Some(other) = self.other.as_mut() {
+            sort(&mut other.visible, &mut other.state);
         }
     }
 
-    Ok(())
-}
+    // Folds or unfolds the section of the selected entry, selecting the entry starting the group once
+    // folded.
+    fn toggle_section(&mut self) -> Result<(), String> {
+        let name = self
+            .selected()
+            .and_then(|item| item.section.clone())
+            .ok_or("The selected entry is not in a group")?;
+        let folded = self.sections.toggle(&name);
+        let filter = self.filter.clone();
+        self.set_filter(filter);
+        self.refresh_other();
+        if folded {
+            let items = &self.buffer.items;
+            let position = self.visible.iter().position(|index| {
+                section::is_group_start(&items[*index]) && items[*index].section.as_deref() == Some(name.as_str())
+            });
+            self.state.select(position.or(self.state.selected()));
+        }
 
-fn build_paragraph_for_item(item: &DebugEntry) -> Vec<Spans> {
-    let mut result: Vec<Spans> = vec![];
+        Ok(())
+    }
 
-    let text = build_text_vec_from_hashmap(item.data.clone(), 0);
+    // Swaps the selection, scroll position, filter and listed entries with those of the pane.
+    fn exchange(&mut self, pane: &mut Pane) {
+        mem::swap(&mut self.state, &mut pane.state);
+        mem::swap(&mut self.offset, &mut pane.offset);
+        mem::swap(&mut self.visible, &mut pane.visible);
+        mem::swap(&mut self.filter, &mut pane.filter);
+    }
 
-    result.push(Spans::from(format!("Logged on: {}", item.time)));
-    result.push(Spans::from(format!("")));
+    // Splits the entries over two lists, focusing the new one on the right which starts out as a copy
+    // of the current list, or goes back to a single list keeping the focused one.
+    fn toggle_split(&mut self) -> bool {
+        if self.other.take().is_some() {
+            return false;
+        }
+        self.other = Some(Pane {
+            visible: self.visible.clone(),
+            filter: self.filter.clone(),
+            left: false,
+            ..Pane::default()
+        });
+        self.switch_pane();
 
-    for text_node in text {
-        result.push(Spans::from(format!("{}", text_node)));
+        true
     }
 
-    result
-}
+    // Moves the focus to the other list of the split.
+    fn switch_pane(&mut self) {
+        if let Some(mut other) = self.other.take() {
+            self.exchange(&mut other);
+            other.left = !other.left;
+            self.other = Some(other);
+        }
+    }
 
-fn build_text_vec_from_hashmap(map: HashMap<String, Value>, level: usize) -> Vec<String> {
-    let mut result: Vec<String> = vec![];
+    // Lists the entries matching the filter of the list without focus again.
+    fn refresh_other(&mut self) {
+        if let Some(mut other) = self.other.take() {
+            self.exchange(&mut other);
+            let filter = self.filter.clone();
+            self.set_filter(filter);
+            self.exchange(&mut other);
+            self.other = Some(other);
+        }
+    }
 
-    let indent = "-".repeat(level * 2);
+    // Scrolls the list to keep the selection within the rows that fit the height, returning the
+    // range of visible entries to show.
+    fn scroll_to_selection(&mut self, height: usize) -> Range<usize> {
+        scroll_window(&mut self.offset, self.state.selected(), self.visible.len(), height)
+    }
 
-    for (val_type, value) in &map {
-        match value {
-            Value::Null => result.push(format!("{} {}", indent, val_type)),
-            Value::Bool(value) => result.push(format!("{} {} {}", indent, val_type, value)),
-            Value::Number(value) => result.push(format!("{} {} {}", indent, val_type, value)),
-            Value::String(value) => result.push(format!("{} {} {}", indent, val_type, value)),
-            Value::Array(_) => { /* No need to handle */ }
-            Value::Object(value) => {
-                result.extend_from_slice(&build_text_vec_from_object(value.clone(), level + 1, val_type.to_string()))
+    fn visible_items(&self) -> impl DoubleEndedIterator<Item = &DebugEntry> {
+        self.visible.iter().map(move |index| &self.buffer.items[*index])
+    }
+
+    // Pages in the next older entries from the spill file, returning whether there were any.
+    fn load_older(&mut self) -> bool {
+        const PAGE_SIZE: usize = 500;
+
+        let start = self.buffer.items.len();
+        let loaded = self.buffer.load_older(PAGE_SIZE);
+        // The size is not stored with the entries.
+        for item in self.buffer.items[start..].iter_mut() {
+            item.size = serde_json::to_string(&item.data).map_or(0, |payload| payload.len());
+        }
+        self.index.extend(self.buffer.items[start..].iter());
+        for index in start..self.buffer.items.len() {
+            let (entry, document) = (&self.buffer.items[index], self.index.get(index));
+            if self.filter.as_ref().is_none_or(|filter| filter.matches_indexed(entry, document)) {
+                self.visible.push(index);
+            }
+            if let Some(other) = self.other.as_mut() {
+                if other.filter.as_ref().is_none_or(|filter| filter.matches_indexed(entry, document)) {
+                    other.visible.push(index);
+                }
             }
         }
+        if self.sort.is_some() {
+            self.sort_lists();
+        }
+
+        loaded > 0
     }
 
-    result
-}
+    // Removes the selected entry from the list, it stays in the `--db` store.
+    fn delete_selected(&mut self) {
+        let index = match self.state.selected().and_then(|selected| self.visible.get(selected).copied()) {
+            Some(index) => index,
+            None => return,
+        };
+        self.buffer.items.remove(index);
+        self.index.remove(index);
+        if index >= self.buffer.items.len() + 1 - self.buffer.paged {
+            self.buffer.paged -= 1;
+        }
+        self.visible.retain(|visible| *visible != index);
+        for visible in self.visible.iter_mut().filter(|visible| **visible > index) {
+            *visible -= 1;
+        }
+        if self.state.selected().is_some_and(|selected| selected >= self.visible.len()) {
+            self.state.select(self.visible.len().checked_sub(1));
+        }
+        if let Some(other) = self.other.as_mut() {
+            other.visible.retain(|visible| *visible != index);
+            for visible in other.visible.iter_mut().filter(|visible| **visible > index) {
+                *visible -= 1;
+            }
+            other.truncate(self.buffer.items.len());
+        }
+        self.revision += 1;
+    }
 
-fn build_text_vec_from_object(value: Map<String, Value>, level: usize, label: String) -> Vec<String> {
-    let mut result: Vec<String> = vec![];
-    let indent = "-".repeat(level * 2);
-    for (item_key, item_value) in &value {
-        match item_value {
-            Value::Null => result.push(format!("{} {} NULL", indent, item_key)),
-            Value::Bool(value) => result.push(format!("{} {} {}", indent, item_key, value)),
-            Value::Number(value) => result.push(format!("{} {} {}", indent, item_key, value)),
-            Value::String(value) => result.push(format!("{} {} {}", indent, item_key, value)),
-            Value::Array(_) => { /* No need to handle */ }
-            Value::Object(value) => {
-                // Otherwise we duplicate this information. To consider: Would this break values
-                // with the name "array"?
-                if item_key != "array" {
-                    result.push(format!("{} {} ({})", indent, item_key, label));
+    fn selected(&self) -> Option<&DebugEntry> {
+        let index = *self.visible.get(self.state.selected()?)?;
+        self.buffer.items.get(index)
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut DebugEntry> {
+        self.revision += 1;
+        let index = *self.visible.get(self.state.selected()?)?;
+        self.buffer.items.get_mut(index)
+    }
+
+    pub fn next(&mut self) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.visible.len() - 1 {
+                    // Continue into older entries on disk before wrapping around.
+                    let visible = self.visible.len();
+                    while self.visible.len() == visible && self.load_older() {}
+                    if self.visible.len() > visible {
+                        i + 1
+                    } else {
+                        0
+                    }
+                } else {
+                    i + 1
                 }
-                result.extend_from_slice(&build_text_vec_from_object(value.clone(), level + 1, item_key.to_string()))
             }
+            None => 0,
         };
+        self.state.select(Some(i));
     }
 
-    result
+    pub fn previous(&mut self) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.visible.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
 }
 
-type SomeResult<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
-
-async fn handle_tcp(port: i32, table: Arc<Mutex<StatefulTable>>) -> SomeResult<()> {
-    let listener: TcpListener = TcpListener::bind(format!("127.0.0.1:{}", port)).await?;
-    let mut incoming = listener.incoming();
-    while let Some(stream) = incoming.next().await {
-        let stream = stream?;
-        let handle = task::spawn(connection_loop(stream, table.clone()));
-        handle.await?
+// Moves the first row shown of a list of the length to keep the selection within the height,
+// returning the range of rows to show.
+fn scroll_window(offset: &mut usize, selected: Option<usize>, length: usize, height: usize) -> Range<usize> {
+    let height = height.max(1);
+    *offset = (*offset).min(length.saturating_sub(height));
+    match selected {
+        Some(selected) if selected < *offset => *offset = selected,
+        Some(selected) if selected >= *offset + height => *offset = selected + 1 - height,
+        _ => {}
     }
 
-    Ok(())
+    *offset..(*offset + height).min(length)
 }
 
-async fn read(stream: &Arc<TcpStream>) -> String {
-    let stream = stream.clone();
+type DetailsKey = (u64, u64, Option<String>, bool);
+
+// Details of a huge payload are cut off at this many lines and characters per line, until expanded
+// with `e`. Wrapping all of them on every draw makes the pane unusable.
+const MAX_DETAIL_LINES: usize = 500;
+const MAX_DETAIL_LINE_LENGTH: usize = 2000;
 
-    let mut reader = BufReader::new(stream.as_ref());
+// Layout preferences that can be changed while running.
+struct View {
+    show_backtrace: bool,
+    expand_vendor_frames: bool,
+    // Selected row in the backtrace table, for the source preview.
+    frame_state: TableState,
+    // Arrangement of the panes.
+    preset: Preset,
+    // Time zone the times of the entries are shown in.
+    time_zone: TimeZone,
+    // Height of the details pane as a percentage, the backtrace gets the rest.
+    details_percentage: u16,
+    // Feedback shown in the status bar until the next key press.
+    message: Option<String>,
+    // Path of the data shown in the details pane instead of the whole entry.
+    extract_path: Option<String>,
+    show_diagnostics: bool,
+    // Details of the selected entry as rendered last, by entry id, table revision, extract path and
+    // whether they were expanded, with the number of lines cut off.
+    details: Option<(DetailsKey, Vec<Spans<'static>>, usize)>,
+    // Last commits of the previewed lines, by file and line.
+    blames: HashMap<(String, usize), Option<Blame>>,
+    // Styles of the highlight rules for entry rows, by entry id, for the table revision.
+    row_styles: (u64, HashMap<u64, Option<Style>>),
+    // Id of the entry whose details are shown in full.
+    expanded: Option<u64>,
+    // Lines of the details scrolled past with page up and down, and the height of a page as drawn.
+    details_scroll: u16,
+    details_height: u16,
+    // Line of the details under the cursor and the line the selection of lines started on, while
+    // picking lines to copy with `c`.
+    details_cursor: Option<(usize, usize)>,
+    // Levels of nested objects shown in the details, collapsing deeper ones, or `None` for all of them.
+    depth: Option<usize>,
+    // Whether to plot the numbers of the entries with the label of the selected one.
+    show_chart: bool,
+    // Whether the details show the payload of the selected entry as received.
+    show_raw: bool,
+    // Id of the entry the others are compared with, chosen with `D`.
+    diff_base: Option<u64>,
+    // Label whose newest entry is shown compared with the one before, chosen with `W`.
+    watched_label: Option<String>,
+    // Column of the entries list to sort by with `O` or move with `{` and `}`, chosen with `<` and `>`.
+    column: Option<usize>,
+    // Id of the entry the time of the selected one is measured from, chosen with `m`.
+    time_base: Option<u64>,
+    // Selected group while the list shows the entries grouped by origin, toggled with `G`.
+    groups: Option<TableState>,
+    // Whether the list column shows the throughput panel, toggled with `T`.
+    show_throughput: bool,
+    // Whether strings holding json are shown parsed in the details, toggled with `i`.
+    embedded_json: bool,
+    // Area of the entries list as drawn, and the index of the visible entry on each of its rows, to
+    // find the entry clicked on.
+    list_area: Rect,
+    list_rows: Vec<Option<usize>>,
+    // Area of the list without focus while the entries are split over two lists.
+    other_list_area: Rect,
+    // Area of the details pane as drawn, for snapshots of the details.
+    details_area: Rect,
+    // Where to write a snapshot of the screen or the details, once drawn again without the prompt.
+    snapshot: Option<(Scope, String)>,
+}
 
-    let mut content: Vec<u8> = Vec::new();
+impl View {
+    fn new() -> View {
+        View {
+            show_backtrace: true,
+            expand_vendor_frames: false,
+            frame_state: TableState::default(),
+            preset: Preset::Default,
+            time_zone: TimeZone::Original,
+            details_percentage: 70,
+            message: None,
+            extract_path: None,
+            show_diagnostics: false,
+            details: None,
+            blames: HashMap::new(),
+            row_styles: (0, HashMap::new()),
+            expanded: None,
+            details_scroll: 0,
+            details_height: 0,
+            details_cursor: None,
+            depth: None,
+            show_chart: false,
+            show_raw: false,
+            diff_base: None,
+            watched_label: None,
+            column: None,
+            groups: None,
+            time_base: None,
+            show_throughput: false,
+            embedded_json: true,
+            list_area: Rect::default(),
+            list_rows: Vec::new(),
+            other_list_area: Rect::default(),
+            details_area: Rect::default(),
+            snapshot: None,
+        }
+    }
 
-    // The second read will read everything until the next null byte which is the end of the
-    // message.
-    let result = reader.read_until(b'\0', &mut content).await;
+    fn detail_constraints(&self) -> [Constraint; 2] {
+        if self.show_backtrace {
+            [
+                Constraint::Percentage(self.details_percentage),
+                Constraint::Percentage(100 - self.details_percentage),
+            ]
+        } else {
+            [Constraint::Percentage(100), Constraint::Length(0)]
+        }
+    }
 
-    match result {
-        Ok(_) => String::from_utf8_lossy(&content).to_string(),
-        Err(_) => String::from("error"),
+    fn select_frame(&mut self, step: isize, frame_count: usize) {
+        if frame_count == 0 {
+            return;
+        }
+        let index = match self.frame_state.selected() {
+            Some(index) => (index as isize + step).rem_euclid(frame_count as isize) as usize,
+            None => 0,
+        };
+        self.frame_state.select(Some(index));
     }
-}
 
-async fn connection_loop(stream: TcpStream, table: Arc<Mutex<StatefulTable>>) -> SomeResult<()> {
-    let stream = Arc::new(stream);
+    fn blame(&mut self, file: &str, line: usize) -> Option<Blame> {
+        self.blames
+            .entry((file.to_string(), line))
+            .or_insert_with(|| git::blame(file, line))
+            .clone()
+    }
 
-    let value = read(&stream).await;
+    fn set_preset(&mut self, preset: Preset) {
+        self.preset = preset;
+        self.details_percentage = preset.details_percentage();
+    }
 
-    let debug_entry: DebugEntry = serde_json::from_str(value.as_str()).unwrap();
+    fn resize_details(&mut self, step: i16) {
+        self.details_percentage = (self.details_percentage as i16 + step).clamp(20, 90) as u16;
+    }
+}
 
-    table.try_lock().unwrap().items.insert(0, debug_entry);
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = Config::load()?;
 
-    Ok(())
+    let args: Vec<String> = env::args().skip(1).collect();
+    let cli = match Cli::parse(&args) {
+        Ok(Some(cli)) => cli,
+        Ok(None) => {
+            print!("{}", cli::usage());
+            return Ok(());
+        }
+        Err(error) => {
+            eprintln!("{}\nRun `udl --help` for the available options.", error);
+            process::exit(2);
+        }
+    };
+    if let Mode::Snippet(language) = &cli.mode {
+        match snippet::for_language(language, &cli.host, cli.port) {
+            Some(code) => print!("{}", code),
+            None => {
+                eprintln!("Unknown language: {}, use one of {}", language, snippet::LANGUAGES.join(", "));
+                process::exit(2);
+            }
+        }
+        return Ok(());
+    }
+    if let Mode::Completions(shell) = &cli.mode {
+        match completions::script(shell) {
+            Some(script) => print!("{}", script),
+            None => {
+                eprintln!("Unknown shell: {}, use one of {}", shell, completions::SHELLS.join(", "));
+                process::exit(2);
+            }
+        }
+        return Ok(());
+    }
+    if let Mode::Manpage = cli.mode {
+        print!("{}", manpage::render());
+        return Ok(());
+    }
+    if let Mode::Send(arguments) = &cli.mode {
+        if let Err(error) = send::run(&cli, arguments) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    if cli.headless {
+        return headless::run(cli, config);
+    }
+    if let Mode::Daemon = cli.mode {
+        return daemon::run(cli, config);
+    }
+
+    task::block_on(run(cli, config))
+}
+
+// Runs the terminal interface. Listening, input and the ticker are tasks on the same runtime,
+// sending their events to the main loop.
+async fn run(mut cli: Cli, mut config: Config) -> Result<(), Box<dyn Error>> {
+    // A taken port shows the daemon on it, or moves to the next free one, instead of a bind error.
+    let mut notice = None;
+    let mut taken_port = None;
+    if !matches!(cli.mode, Mode::Attach) {
+        match port::check(&cli.host, cli.port, matches!(cli.mode, Mode::Listen))? {
+            Some(Conflict::Daemon) => {
+                notice = Some(format!("Port {} is taken by udl daemon, showing its entries instead", cli.port));
+                cli.mode = Mode::Attach;
+            }
+            Some(Conflict::Moved(port)) => {
+                notice = Some(format!("Port {} is in use, listening on port {} instead: point the clients at it", cli.port, port));
+                taken_port = Some(cli.port);
+                cli.port = port;
+            }
+            None => {}
+        }
+    }
+    let source = match (&cli.mode, taken_port) {
+        (Mode::Attach, _) => format!("Attached to {}", daemon::socket_path(cli.port).display()),
+        (_, Some(taken)) => format!("Listening on {} (port {} is in use)", cli.address(), taken),
+        _ => format!("Listening on {}", cli.address()),
+    };
+    let listen_address = cli.address();
+    let attach = matches!(cli.mode, Mode::Attach);
+    let attach_path = daemon::socket_path(cli.port);
+    let cli_theme = cli.theme;
+    let mut theme = cli_theme.unwrap_or_else(|| config.theme());
+
+    let mut table = StatefulTable::new();
+    table.buffer.max_entries = cli.max_entries;
+    table.buffer.ttl = cli.ttl;
+    table.runs = cli.on_new_run.map(Runs::new);
+    // Payloads as received would show the values masked in the entries.
+    table.raw = RawPayloads::new(if config.redact.is_empty() { config.raw_payloads } else { 0 });
+    table.redaction = config.redact.clone();
+    table.label_rules = config.labels.clone();
+    table.pause_rules = cli.pause_on;
+    let mut arrangement = Arrangement::load();
+    arrangement.arrange(&mut config.columns);
+    table.sort = arrangement.sort.clone();
+    for path in &config.watches {
+        table.watches.add(path, &[]);
+    }
+    table.set_filter(match cli.filter {
+        Some(filter) => Some(filter),
+        None => config.filter()?,
+    });
+    if let Some(path) = cli.db {
+        let (store, entries) = Store::open(&path)?;
+        for entry in entries {
+            table.push(entry);
+        }
+        table.buffer.store = Some(store);
+    }
+    if let Mode::Open(path) = &cli.mode {
+        for entry in session::load(path)? {
+            table.push(entry);
+        }
+    }
+    if let Mode::ImportXdebug(path) = &cli.mode {
+        for entry in xdebug::load(path)? {
+            table.push(entry);
+        }
+    }
+    let mut comparison = match &cli.mode {
+        Mode::Compare(baseline, compared) => {
+            if let Some(path) = compared {
+                for entry in session::load_entries(path)? {
+                    table.push(entry);
+                }
+            }
+            Some(Comparison::new(baseline.display().to_string(), session::load_entries(baseline)?))
+        }
+        _ => None,
+    };
+    for path in cli.imports {
+        for entry in session::load_entries(&path)? {
+            table.push(entry);
+        }
+    }
+    // Only spill entries received from now on, the loaded ones are already stored elsewhere.
+    if let Some(path) = cli.spill {
+        table.buffer.spill = Some(Spill::open(&path)?);
+    }
+
+    // Keys come from the terminal when stdin is taken by the entries.
+    let mut events = Events::with_config(EventConfig {
+        keys_from_tty: cli.stdin,
+        ..EventConfig::default()
+    });
+    // Quitting is handled below, so typing a `q` in a prompt does not stop the input thread.
+    events.disable_exit_key();
+
+    if let Mode::Replay(path) = &cli.mode {
+        let entries = session::load_entries(path)?;
+        let speed = cli.speed;
+        let sender = events.sender();
+        task::spawn(replay(entries, speed, move |entry| {
+            let _ = sender.try_send(AppEvent::Replayed(Box::new(entry)));
+        }));
+    }
+
+    let mut alerts = Alerts::new(cli.alerts);
+    let stdout: Box<dyn Write> = match config.mouse {
+        true => Box::new(MouseTerminal::from(io::stdout().into_raw_mode()?)),
+        false => Box::new(io::stdout().into_raw_mode()?),
+    };
+    // Alerts are for when udl is in the background, which the terminal then reports.
+    let stdout: Box<dyn Write> = match alerts.is_enabled() {
+        true => Box::new(FocusTerminal::from(stdout)),
+        false => stdout,
+    };
+    let backend = TermionBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    if let Mode::Demo = cli.mode {
+        task::spawn(demo::run(listen_address.clone(), cli.rate));
+    }
+
+    if let Some(web_address) = cli.web.clone() {
+        let sender = events.sender();
+        task::spawn(web::serve(web_address, sender));
+    }
+
+    task::spawn(config::watch(events.sender()));
+
+    let mut prompt: Option<Prompt> = None;
+    let mut menu: Option<Menu> = None;
+    let mut history = History::load();
+    // The filter to go back to when a search is cancelled.
+    let mut filter_before_search: Option<Filter> = None;
+    // The filter to go back to when showing only the entries with a label is toggled off.
+    let mut filter_before_label: Option<Filter> = None;
+    let mut view = View::new();
+    view.message = notice;
+    view.depth = config.expand_depth;
+    view.set_preset(config.layout);
+    view.time_zone = config.time_zone;
+    let mut diagnostics = Diagnostics::default();
+    let renderers = Renderers::new();
+
+    let mut script = cli.script.as_deref().map(Script::spawn).transpose()?;
+    let mut notifier = Notifier {
+        rules: cli.notify,
+        bell: cli.bell,
+        webhooks: config.webhooks.clone(),
+        hooks: config.hooks.clone(),
+        hooked: Some(events.sender()),
+    };
+
+    // Task to listen for incoming connections, or to receive them from the daemon.
+    let backlog = Backlog::new(cli.queue_size, cli.drop_when_full);
+    let sender = events.sender();
+    let listener_backlog = backlog.clone();
+    let max_payload_size = cli.max_payload_size;
+    let access = cli.access.clone();
+    let decoder = cli.decoder.unwrap_or_else(|| config.decoder());
+    let listener = task::spawn(async move {
+        if attach {
+            return daemon::attach(attach_path, sender).await;
+        }
+        let result = listen_bounded(&listen_address, &listener_backlog, max_payload_size, &access, decoder, |received| {
+            let _ = sender.try_send(AppEvent::Received(received));
+        })
+        .await;
+        if let Err(error) = result {
+            let _ = sender.try_send(AppEvent::Error(format!("Cannot listen on {}: {}", listen_address, error)));
+        }
+    });
+
+    if let Mode::Tail(path, format) = &cli.mode {
+        let (path, format) = (path.clone(), *format);
+        let sender = events.sender();
+        let backlog = backlog.clone();
+        task::spawn(async move {
+            let result = tail::follow(&path, format, &backlog, |received| {
+                let _ = sender.try_send(AppEvent::Received(received));
+            })
+            .await;
+            if let Err(error) = result {
+                let _ = sender.try_send(AppEvent::Error(format!("Cannot follow {}: {}", path.display(), error)));
+            }
+        });
+    }
+
+    // Entries piped into stdin, next to the ones received by the listener.
+    if cli.stdin {
+        let sender = events.sender();
+        let backlog = backlog.clone();
+        task::spawn(async move {
+            let stdin = async_std::io::BufReader::new(async_std::io::stdin());
+            let result = read_lines(stdin, "stdin", &backlog, |received| {
+                let _ = sender.try_send(AppEvent::Received(received));
+            })
+            .await;
+            let _ = sender.try_send(AppEvent::Received(Received::Diagnostic(match result {
+                Ok(()) => String::from("Reached the end of stdin"),
+                Err(error) => format!("Could not read from stdin: {}", error),
+            })));
+        });
+    }
+
+    loop {
+        let mut linked_cells: Vec<LinkedCell> = vec![];
+        let mut snapshot_lines = vec![];
+        // While watching a label, the newest entry with it is selected.
+        if let Some(label) = &view.watched_label {
+            let newest = table.visible.iter().enumerate().filter(|(_, index)| table.buffer.items[**index].label.as_ref() == label).min_by_key(|(_, index)| **index);
+            if let Some((position, _)) = newest {
+                table.state.select(Some(position));
+            }
+        }
+        terminal
+            .draw(|f| {
+                let screen = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+                    .split(f.size());
+                // Comparing shows the baseline as a list before the entries.
+                let list = view.preset.list_percentage();
+                let columns = match comparison {
+                    Some(_) => {
+                        let list = list.min(50);
+                        vec![Constraint::Percentage(list), Constraint::Percentage(list), Constraint::Percentage(100 - 2 * list)]
+                    }
+                    None => vec![Constraint::Percentage(list), Constraint::Percentage(100 - list)],
+                };
+                let mut layout = Layout::default()
+                    .direction(view.preset.direction())
+                    .constraints(columns)
+                    .split(screen[0]);
+                let baseline_rect = comparison.as_ref().map(|_| layout.remove(0));
+                // The throughput panel and the watches take the bottom of the list column.
+                if view.show_throughput {
+                    let rects = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(0), Constraint::Length(9.min(layout[0].height / 2))].as_ref())
+                        .split(layout[0]);
+                    layout[0] = rects[0];
+                    render_throughput(f, rects[1], &table.throughput, &theme);
+                }
+                if !table.watches.is_empty() {
+                    let height = (table.watches.watches.len() as u16 * 2 + 2).min(layout[0].height / 2);
+                    let rects = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Min(0), Constraint::Length(height)].as_ref())
+                        .split(layout[0]);
+                    layout[0] = rects[0];
+                    f.render_widget(build_watches(&table.watches, &theme), rects[1]);
+                }
+                if let Some(comparison) = comparison.as_mut() {
+                    comparison.pair(&table.buffer.items, table.revision);
+                }
+
+                let status_line = match (&prompt, alerts.flash()) {
+                    (Some(prompt), _) => Spans::from(prompt.line()),
+                    (None, Some((label, _))) => Spans::from(format!(" Alert: {}", label)),
+                    (None, None) => match &view.message {
+                        Some(message) => Spans::from(format!(" {}", message)),
+                        None => build_status_line(&table, &backlog, &source, comparison.as_ref(), view.time_base),
+                    },
+                };
+                let status_style = match alerts.flash() {
+                    Some((_, true)) => Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+                    _ => Style::default(),
+                };
+                f.render_widget(Paragraph::new(status_line).style(status_style), screen[1]);
+
+                let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+                let normal_style = Style::default().bg(theme.header);
+                match view.groups.as_mut() {
+                    Some(groups_state) => {
+                        let groups = group::collect(table.visible_items(), &config);
+                        f.render_stateful_widget(build_groups(&groups, view.time_zone, &theme), layout[0], groups_state);
+                        view.list_rows.clear();
+                    }
+                    None => {
+                        // Split over two lists, the one with focus is drawn last to know its rows.
+                        let mut list_rect = layout[0];
+                        if let Some(left) = table.other.as_ref().map(|other| other.left) {
+                            let rects = Layout::default()
+                                .direction(Direction::Horizontal)
+                                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                                .split(layout[0]);
+                            let (focused, other) = if left { (rects[1], rects[0]) } else { (rects[0], rects[1]) };
+                            list_rect = focused;
+                            let mut pane = table.other.take().unwrap_or_default();
+                            table.exchange(&mut pane);
+                            render_entry_list(f, other, &mut table, &mut view, Some(false), &config, &theme, comparison.as_ref());
+                            table.exchange(&mut pane);
+                            table.other = Some(pane);
+                            view.other_list_area = other;
+                        }
+                        let focused = table.other.as_ref().map(|_| true);
+                        let (window, row_indexes) =
+                            render_entry_list(f, list_rect, &mut table, &mut view, focused, &config, &theme, comparison.as_ref());
+                        view.list_area = list_rect;
+                        view.list_rows = vec![None; row_indexes.iter().max().map_or(0, |last| last + 1)];
+                        for (position, row) in row_indexes.iter().enumerate() {
+                            view.list_rows[*row] = Some(window.start + position);
+                        }
+                    }
+                }
+
+                if let (Some(comparison), Some(rect)) = (comparison.as_mut(), baseline_rect) {
+                    comparison.follow(table.selected());
+                    let window = scroll_window(
+                        &mut comparison.offset,
+                        comparison.selected,
+                        comparison.baseline.len(),
+                        (rect.height.saturating_sub(4) / config.density.row_height()) as usize,
+                    );
+                    let baseline: Vec<&DebugEntry> = comparison.baseline[window.clone()].iter().collect();
+                    // Entries the session does not have are marked and dimmed.
+                    let styles: Vec<Option<Style>> = window
+                        .clone()
+                        .map(|index| Some(Style::default().add_modifier(Modifier::DIM)).filter(|_| comparison.is_missing(index)))
+                        .collect();
+                    let markers: Vec<&str> = window.clone().map(|index| if comparison.is_missing(index) { "- " } else { "  " }).collect();
+                    let (rows, row_indexes) = build_entry_rows(
+                        &baseline,
+                        &styles,
+                        &markers,
+                        &[],
+                        &[],
+                        config.large_entry_size,
+                        &config.type_markers,
+                        &Sections::default(),
+                        config.density,
+                        None,
+                    );
+                    let baseline_widget = Table::new(rows)
+                        .header(Row::new(vec![Cell::from("Baseline"), Cell::from("Tags")]).style(normal_style).bottom_margin(1))
+                        .block(Block::default().borders(Borders::ALL).title(comparison.name.as_str()).style(theme.panel()))
+                        .highlight_style(selected_style)
+                        .highlight_symbol("> ")
+                        .widths(&[Constraint::Percentage(70), Constraint::Percentage(30)]);
+                    let mut baseline_state = TableState::default();
+                    baseline_state.select(
+                        comparison
+                            .selected
+                            .and_then(|index| row_indexes.get(index.checked_sub(window.start)?).copied()),
+                    );
+                    f.render_stateful_widget(baseline_widget, rect, &mut baseline_state);
+                }
+
+                let mut right = layout[1];
+                if view.show_diagnostics {
+                    let rects = Layout::default()
+                        .direction(Direction::Vertical)
+                        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+                        .split(right);
+                    right = rects[0];
+                    f.render_widget(build_diagnostics(&diagnostics, rects[1].height, &theme), rects[1]);
+                }
+
+                let detail_rects = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(view.detail_constraints().as_ref())
+                    .split(right);
+                view.details_area = detail_rects[0];
+
+                // While typing an extraction path the details follow the input.
+                let extract_path = match &prompt {
+                    Some(prompt) if prompt.action == PromptAction::Extract => Some(prompt.value.as_str()),
+                    _ => view.extract_path.as_deref(),
+                }
+                .filter(|path| !path.trim().is_empty());
+
+                // Set the last item to be selected if no selection is active yet.
+                if table.state.selected().is_none() {
+                    table.state.select(table.visible.len().checked_sub(1));
+                }
+
+                let renderer = table.selected().and_then(|item| renderers.find(item));
+                let diff_base = view
+                    .diff_base
+                    .filter(|base| table.selected().is_some_and(|item| item.id != *base))
+                    .and_then(|base| table.buffer.items.iter().find(|item| item.id == base));
+                let previous = view.watched_label.as_ref().and(table.selected()).and_then(|item| {
+                    table.buffer.items.iter().skip_while(|other| other.id != item.id).skip(1).find(|other| other.label == item.label)
+                });
+                let detail_title = match (extract_path, renderer) {
+                    _ if view.show_raw => String::from("Raw payload"),
+                    (Some(path), _) => format!("Details {}", path),
+                    (None, _) if view.watched_label.is_some() => match previous {
+                        Some(previous) => format!("Changes since entry {}, the previous one with this label", previous.id),
+                        None => String::from("Watching the label, no earlier entry with it yet"),
+                    },
+                    (None, _) if diff_base.is_some() => format!("Differences with entry {}", view.diff_base.unwrap_or_default()),
+                    (None, _) if comparison.is_some() => String::from("Differences with the baseline"),
+                    (None, Some(renderer)) => format!("Details ({})", renderer.name()),
+                    (None, None) => String::from("Details"),
+                };
+                let detail_widget = Block::default()
+                    .borders(Borders::ALL)
+                    .title(detail_title.clone())
+                    .style(theme.panel());
+
+                let backtrace_widget = Block::default()
+                    .borders(Borders::ALL)
+                    .title("Backtrace")
+                    .style(theme.panel());
+
+                if let Some(item) = table.selected() {
+                    // Rendering large data is slow, so it is only done again when the entry or the path changed.
+                    let expanded = view.expanded == Some(item.id);
+                    let key = (item.id, table.revision, extract_path.map(str::to_string), expanded);
+                    if view.details.as_ref().is_none_or(|(cached, _, _)| cached.0 != item.id) {
+                        view.details_scroll = 0;
+                        view.details_cursor = view.details_cursor.map(|_| (0, 0));
+                    }
+                    if view.details.as_ref().is_none_or(|(cached, _, _)| *cached != key) {
+                        let text = match (extract_path, &comparison) {
+                            _ if view.show_raw => build_raw_paragraph(&table.raw, item),
+                            (Some(path), _) => build_extracted_paragraph(item, path),
+                            (None, _) if view.watched_label.is_some() => build_diff_paragraph(item, previous, "the previous entry"),
+                            (None, _) if diff_base.is_some() => build_diff_paragraph(item, diff_base, &format!("entry {}", view.diff_base.unwrap_or_default())),
+                            (None, Some(comparison)) => build_diff_paragraph(item, comparison.counterpart(item), "the baseline"),
+                            (None, None) => build_paragraph_for_item(item, renderer, view.depth, view.time_zone, view.embedded_json, &config.number_formats, config.summary_fields(&item.label)),
+                        };
+                        let (text, hidden) = if expanded { (text, 0) } else { truncate_details(text) };
+                        view.details = Some((key, highlight::highlight(&config.highlights, item, text), hidden));
+                    }
+                    let mut text: Vec<Spans> = view.details.as_ref().map_or(vec![], |(_, text, _)| borrow_spans(text));
+                    // The note on the last line is usually out of sight.
+                    let detail_widget = match view.details.as_ref().map_or(0, |(_, _, hidden)| *hidden) {
+                        0 => detail_widget,
+                        hidden => detail_widget.title(format!(
+                            "{} … {} more lines (press e to expand)",
+                            detail_title,
+                            format_count(hidden)
+                        )),
+                    };
+
+                    let mut details_rect = detail_rects[0];
+                    if let Some(metrics) = Metrics::from_data(&item.data) {
+                        let metric_rects = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(3), Constraint::Length(5), Constraint::Min(0)].as_ref())
+                            .split(details_rect);
+                        render_metrics(f, &metric_rects, &metrics, &table.memory_history, &theme);
+                        details_rect = metric_rects[2];
+                    }
+                    if let Some(progress) = Progress::from_data(&item.data) {
+                        let progress_rects = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+                            .split(details_rect);
+                        f.render_widget(build_progress_gauge(&progress, &theme), progress_rects[0]);
+                        details_rect = progress_rects[1];
+                    }
+                    if view.show_chart && chart::has_numbers(item) {
+                        let chart_rects = Layout::default()
+                            .direction(Direction::Vertical)
+                            .constraints([Constraint::Percentage(50), Constraint::Min(0)].as_ref())
+                            .split(details_rect);
+                        let series = chart::collect(&table.buffer.items, &item.label);
+                        f.render_widget(build_chart(&item.label, &series, &theme), chart_rects[0]);
+                        details_rect = chart_rects[1];
+                    }
+
+                    view.details_height = details_rect.height.saturating_sub(2);
+                    view.details_scroll = view.details_scroll.min(text.len().saturating_sub(1) as u16);
+                    if let Some((cursor, anchor)) = view.details_cursor {
+                        let last = text.len().saturating_sub(1);
+                        let (cursor, anchor) = (cursor.min(last), anchor.min(last));
+                        view.details_cursor = Some((cursor, anchor));
+                        for line in text.iter_mut().take(cursor.max(anchor) + 1).skip(cursor.min(anchor)) {
+                            for span in line.0.iter_mut() {
+                                span.style = span.style.add_modifier(Modifier::REVERSED);
+                            }
+                        }
+                        // Keep the cursor in sight, counting wrapped lines as one.
+                        let height = view.details_height.max(1) as usize;
+                        let scroll = (view.details_scroll as usize).clamp((cursor + 1).saturating_sub(height), cursor);
+                        view.details_scroll = scroll as u16;
+                    }
+                    let details = Paragraph::new(text)
+                        .block(detail_widget)
+                        .wrap(Wrap { trim: true })
+                        .scroll((view.details_scroll, 0))
+                        .alignment(Alignment::Left);
+                    f.render_widget(details, details_rect);
+
+                    // Render the backtrace.
+                    let (rows, frame_indexes) = build_backtrace_rows(&item.backtrace, &config, view.expand_vendor_frames);
+                    let heading = Row::new(vec![
+                        Cell::from("File"),
+                        Cell::from("Line"),
+                        Cell::from("Calling function"),
+                    ])
+                    .style(normal_style)
+                    .bottom_margin(1);
+
+                    let backtrace_table = Table::new(rows)
+                        .header(heading)
+                        .block(
+                            Block::default()
+                                .borders(Borders::ALL)
+                                .title("Backtrace")
+                                .style(theme.panel()),
+                        )
+                        .highlight_style(selected_style)
+                        .highlight_symbol("> ")
+                        .widths(&[
+                            Constraint::Percentage(50),
+                            Constraint::Percentage(10),
+                            Constraint::Percentage(40),
+                        ]);
+
+                    if view.show_backtrace {
+                        if view.frame_state.selected().is_some_and(|row| row >= frame_indexes.len()) {
+                            view.frame_state.select(frame_indexes.len().checked_sub(1));
+                        }
+                        // Start at the first frame of the project, past the framework internals.
+                        if view.frame_state.selected().is_none() {
+                            let first = config.first_project_frame(&item.backtrace);
+                            view.frame_state.select(first.and_then(|first| frame_indexes.iter().position(|frame| *frame == Some(first))));
+                        }
+
+                        // Preview the source of the selected frame when the file exists locally.
+                        let preview = view
+                            .frame_state
+                            .selected()
+                            .and_then(|row| frame_indexes[row])
+                            .and_then(|frame| {
+                                let (file, line) = config.locate(&item.backtrace[frame]);
+                                let line = line.max(1) as usize;
+                                let lines = source::preview(&file, line, 3)?;
+                                Some((line, lines, view.blame(&file, line)))
+                            });
+
+                        let backtrace_rect = match preview {
+                            Some((line, lines, blame)) => {
+                                let backtrace_rects = Layout::default()
+                                    .direction(Direction::Horizontal)
+                                    .constraints([Constraint::Percentage(55), Constraint::Percentage(45)].as_ref())
+                                    .split(detail_rects[1]);
+                                f.render_widget(build_source_preview(line, lines, blame.as_ref(), &theme), backtrace_rects[1]);
+                                backtrace_rects[0]
+                            }
+                            None => detail_rects[1],
+                        };
+                        f.render_stateful_widget(backtrace_table, backtrace_rect, &mut view.frame_state);
+
+                        if let Some(template) = config.hyperlink_template() {
+                            let links = item
+                                .backtrace
+                                .iter()
+                                .map(|frame| {
+                                    let (file, line) = config.locate(frame);
+                                    let url = hyperlink::url(template, &file, line);
+                                    (config.display_path(&file), url)
+                                })
+                                .collect();
+                            let hyperlinks = Hyperlinks {
+                                links,
+                                cells: &mut linked_cells,
+                            };
+                            f.render_widget(hyperlinks, backtrace_rect);
+                        }
+                    }
+                } else {
+                    f.render_widget(detail_widget, detail_rects[0]);
+                    if view.show_backtrace {
+                        f.render_widget(backtrace_widget, detail_rects[1]);
+                    }
+                }
+
+                if let Some(menu) = menu.as_mut() {
+                    let (list, rect) = Menu::widget(f.size(), theme.panel());
+                    f.render_widget(Clear, rect);
+                    f.render_stateful_widget(list, rect, menu.state());
+                }
+
+                if let Some((scope, _)) = &view.snapshot {
+                    let area = match scope {
+                        Scope::Details => view.details_area,
+                        Scope::Screen => f.size(),
+                    };
+                    f.render_widget(Snapshot { lines: &mut snapshot_lines }, area);
+                }
+            })
+            .unwrap();
+        hyperlink::write_links(terminal.backend_mut(), &linked_cells)?;
+        // Draw again right away to show whether the snapshot was written.
+        if let Some((_, path)) = view.snapshot.take() {
+            view.message = Some(match snapshot::write(Path::new(&path), &snapshot_lines) {
+                Ok(()) => format!("Wrote a snapshot to {}", path),
+                Err(error) => format!("Could not write the snapshot: {}", error),
+            });
+            continue;
+        }
+
+        // Only draw again once something changed. Ticks only update the running timers, expire entries
+        // and apply filters like `last 2m` again.
+        let event = loop {
+            match events.next().await? {
+                AppEvent::Query(query) => query(&table),
+                AppEvent::Tick => {
+                    let expired = table.expire();
+                    if expired > 0 {
+                        diagnostics.log(&format!("Expired {} entries older than --ttl", expired));
+                    }
+                    let refiltered = table.refilter_clock();
+                    if expired > 0 || refiltered || !table.timers.running().is_empty() || alerts.flash().is_some() || view.show_throughput {
+                        break AppEvent::Tick;
+                    }
+                }
+                event => break event,
+            }
+        };
+
+        match event {
+            AppEvent::RightClick(x, y) if prompt.is_none() => {
+                // Select the entry clicked on, in the list.
+                let (column, row) = (x.saturating_sub(1), y.saturating_sub(1));
+                let other = view.other_list_area;
+                if table.other.is_some() && column >= other.x && column < other.x + other.width {
+                    table.switch_pane();
+                    view.list_rows.clear();
+                }
+                let area = view.list_area;
+                if column >= area.x && column < area.x + area.width && row >= area.y + 3 {
+                    if let Some(Some(index)) = view.list_rows.get(((row - area.y - 3) / config.density.row_height()) as usize) {
+                        table.state.select(Some(*index));
+                        view.frame_state.select(None);
+                    }
+                }
+                if table.selected().is_some() {
+                    menu = Some(Menu::new());
+                }
+            }
+            AppEvent::Input(input) => {
+                alerts.input();
+                // A chosen action is handled as the key doing the same.
+                let input = match menu.as_mut().map(|active| active.handle_key(input)) {
+                    Some(MenuResult::Pending) => continue,
+                    Some(MenuResult::Cancelled) => {
+                        menu = None;
+                        continue;
+                    }
+                    Some(MenuResult::Chosen(key)) => {
+                        menu = None;
+                        key
+                    }
+                    None => input,
+                };
+                if let Some(active) = prompt.as_mut() {
+                    let action = active.action;
+                    match active.handle_key(input) {
+                        // Searches as you type, the index keeps this fast for large sessions.
+                        PromptResult::Pending if action == PromptAction::Search => {
+                            let search = Some(&active.value).filter(|text| !text.is_empty()).map(|text| Filter::text(text));
+                            table.set_filter(search.or_else(|| filter_before_search.clone()));
+                        }
+                        PromptResult::Pending => {}
+                        PromptResult::Complete => {
+                            if action == PromptAction::Copy {
+                                active.value = copy::next_format(&active.value).to_string();
+                            }
+                            if action == PromptAction::Extract {
+                                if let Some(item) = table.selected() {
+                                    if let Some(completed) = extract::complete(&item.data, &active.value) {
+                                        active.value = completed;
+                                    }
+                                }
+                            }
+                        }
+                        PromptResult::Cancelled => {
+                            if action == PromptAction::Search {
+                                table.set_filter(filter_before_search.take());
+                            }
+                            prompt = None;
+                        }
+                        PromptResult::Submitted(value) => {
+                            history.add(action, &value);
+                            match action {
+                                PromptAction::Note => {
+                                    if let Some(item) = table.selected_mut() {
+                                        item.note = Some(value).filter(|note| !note.trim().is_empty());
+                                        let id = item.id;
+                                        table.buffer.annotate(id);
+                                    }
+                                }
+                                PromptAction::ExportSelected | PromptAction::ExportAll => {
+                                    let entries: Vec<&DebugEntry> = if action == PromptAction::ExportSelected {
+                                        table.selected().into_iter().collect()
+                                    } else {
+                                        table.visible_items().rev().collect()
+                                    };
+                                    view.message = Some(match export::export(Path::new(&value), &entries) {
+                                        Ok(()) => format!("Exported {} entries to {}", entries.len(), value),
+                                        Err(error) => format!("Could not export: {}", error),
+                                    });
+                                }
+                                PromptAction::Tag => {
+                                    if let Some(item) = table.selected_mut() {
+                                        apply_tags(&mut item.tags, &value);
+                                        let id = item.id;
+                                        table.buffer.annotate(id);
+                                    }
+                                    let filter = table.filter.clone();
+                                    table.set_filter(filter);
+                                }
+                                PromptAction::Search => filter_before_search = None,
+                                PromptAction::Copy => {
+                                    let copied = match (copy::Format::named(&value), table.selected()) {
+                                        (Some(format), Some(item)) => {
+                                            let details = build_paragraph_for_item(item, renderers.find(item), None, view.time_zone, view.embedded_json, &config.number_formats, config.summary_fields(&item.label));
+                                            copy::format(item, format, &spans_to_text(&details))
+                                        }
+                                        (None, _) => Err(format!("Unknown format {}, use {}", value, copy::FORMATS.join(", "))),
+                                        (_, None) => Err(String::from("No entry selected")),
+                                    };
+                                    view.message = Some(match copied.map(|text| clipboard::copy(terminal.backend_mut(), &text)) {
+                                        Ok(Ok(())) => format!("Copied the entry as {}", value.trim()),
+                                        Ok(Err(error)) => format!("Could not copy: {}", error),
+                                        Err(error) => error,
+                                    });
+                                }
+                                PromptAction::Snapshot(scope) => view.snapshot = Some((scope, value)),
+                                PromptAction::Extract => {
+                                    view.extract_path = Some(value).filter(|path| !path.trim().is_empty());
+                                }
+                                PromptAction::Command => match Command::parse(&value) {
+                                    Ok(command) => execute(command, &mut table, &mut view),
+                                    Err(error) => view.message = Some(error),
+                                },
+                                PromptAction::SaveSession => {
+                                    view.message = Some(match session::save(Path::new(&value), &table.buffer.items) {
+                                        Ok(()) => format!("Saved {} entries to {}", table.buffer.items.len(), value),
+                                        Err(error) => format!("Could not save session: {}", error),
+                                    });
+                                }
+                            }
+                            prompt = None;
+                        }
+                    }
+                    continue;
+                }
+
+                view.message = None;
+                // The group list takes the keys moving through the list.
+                if let Some(groups_state) = view.groups.as_mut() {
+                    let groups = group::collect(table.visible_items(), &config);
+                    let selected = groups_state.selected().unwrap_or(0);
+                    match input {
+                        Key::Char('j') => groups_state.select(Some((selected + 1).min(groups.len().saturating_sub(1)))),
+                        Key::Char('k') => groups_state.select(Some(selected.saturating_sub(1))),
+                        Key::Char('\n') => {
+                            if let Some(group) = groups.get(selected) {
+                                table.set_filter(Filter::parse(&group.filter()).ok());
+                                table.state.select(Some(0));
+                            }
+                            view.groups = None;
+                        }
+                        Key::Char(' ') => {
+                            if let Some(group) = groups.get(selected) {
+                                if let Err(error) = table.select_entry(&format!("#{}", group.latest)) {
+                                    view.message = Some(error);
+                                }
+                                view.frame_state.select(None);
+                            }
+                            view.groups = None;
+                        }
+                        Key::Char('o') => {
+                            let frame = groups
+                                .get(selected)
+                                .and_then(|group| table.buffer.items.iter().find(|item| item.id == group.latest))
+                                .and_then(|item| group::origin_frame(item, &config));
+                            view.message = match (frame, config.editor_command()) {
+                                (Some(frame), Some(command)) => {
+                                    let (file, line) = config.locate(frame);
+                                    editor::open(command, &file, line).err().map(|error| format!("Could not open the editor: {}", error))
+                                }
+                                (None, _) => Some(String::from("No backtrace frame to open")),
+                                (_, None) => Some(String::from("Set an editor in the config to open files")),
+                            };
+                        }
+                        Key::Char('G') => view.groups = None,
+                        _ => {}
+                    }
+                    if !matches!(input, Key::Esc | Key::Char('q')) {
+                        continue;
+                    }
+                }
+                // The details cursor takes the keys moving through the list, and esc.
+                if let Some((cursor, anchor)) = view.details_cursor {
+                    let lines = view.details.as_ref().map_or(&[][..], |(_, text, _)| &text[..]);
+                    let last = lines.len().saturating_sub(1);
+                    let page = view.details_height.max(1) as usize;
+                    let selected = lines.get(cursor.min(anchor)..=cursor.max(anchor).min(last)).unwrap_or_default();
+                    let copied = match input {
+                        Key::Char('y') if selected.len() == 1 => Some((detail_line(&spans_to_text(selected)).to_string(), String::from("the line"))),
+                        Key::Char('y') => Some((spans_to_text(selected), format!("{} lines", selected.len()))),
+                        Key::Char('\n') => Some((detail_value(&spans_to_text(&selected[..selected.len().min(1)])).to_string(), String::from("the value"))),
+                        _ => None,
+                    };
+                    match input {
+                        Key::Char('j') => view.details_cursor = Some(((cursor + 1).min(last), (cursor + 1).min(last))),
+                        Key::Char('k') => view.details_cursor = Some((cursor.saturating_sub(1), cursor.saturating_sub(1))),
+                        Key::Char('J') => view.details_cursor = Some(((cursor + 1).min(last), anchor)),
+                        Key::Char('K') => view.details_cursor = Some((cursor.saturating_sub(1), anchor)),
+                        Key::PageDown => view.details_cursor = Some(((cursor + page).min(last), (cursor + page).min(last))),
+                        Key::PageUp => view.details_cursor = Some((cursor.saturating_sub(page), cursor.saturating_sub(page))),
+                        Key::Char('c') | Key::Esc => view.details_cursor = None,
+                        _ => {}
+                    }
+                    if let Some((text, what)) = copied {
+                        view.message = Some(match clipboard::copy(terminal.backend_mut(), &text) {
+                            Ok(()) => format!("Copied {}", what),
+                            Err(error) => format!("Could not copy: {}", error),
+                        });
+                    }
+                    if matches!(input, Key::Char('j' | 'k' | 'J' | 'K' | 'y' | 'c' | '\n') | Key::PageDown | Key::PageUp | Key::Esc) {
+                        continue;
+                    }
+                }
+                // Keys bound in the config run their commands instead of what they do otherwise.
+                if let Some(commands) = config.commands(input) {
+                    for input in commands {
+                        match Command::parse(input) {
+                            Ok(command) => execute(command, &mut table, &mut view),
+                            Err(error) => view.message = Some(error),
+                        }
+                    }
+                    if view.message.is_none() {
+                        view.message = Some(format!("Ran {}", commands.join(", ")));
+                    }
+                    continue;
+                }
+                match input {
+                    Key::Char('c') if table.selected().is_some() => {
+                        view.details_cursor = Some((view.details_scroll as usize, view.details_scroll as usize));
+                        view.message = Some(String::from("j/k move through the lines, J/K select more, y copies them, enter the value, c or esc to stop"));
+                    }
+                    Key::Char('T') => view.show_throughput = !view.show_throughput,
+                    Key::Char('G') => {
+                        let mut groups_state = TableState::default();
+                        groups_state.select(Some(0));
+                        view.groups = Some(groups_state);
+                    }
+                    Key::Esc | Key::Char('q') => {
+                        // Quit the loop and terminate the application.
+                        break;
+                    }
+                    Key::Char('j') => {
+                        table.next();
+                        view.frame_state.select(None);
+                    }
+                    Key::Char('k') => {
+                        table.previous();
+                        view.frame_state.select(None);
+                    }
+                    Key::Char('J') | Key::Char('K') => {
+                        if let Some(item) = table.selected() {
+                            let (rows, _) = build_backtrace_rows(&item.backtrace, &config, view.expand_vendor_frames);
+                            let step = if input == Key::Char('J') { 1 } else { -1 };
+                            view.select_frame(step, rows.len());
+                        }
+                    }
+                    Key::Char('o') => {
+                        if let Some(item) = table.selected() {
+                            // The selected frame, or the first one that is not a vendor file.
+                            let frame = match view.frame_state.selected() {
+                                Some(row) => {
+                                    let (_, frame_indexes) =
+                                        build_backtrace_rows(&item.backtrace, &config, view.expand_vendor_frames);
+                                    frame_indexes.get(row).copied().flatten()
+                                }
+                                None => config
+                                    .first_project_frame(&item.backtrace)
+                                    .or_else(|| item.backtrace.iter().position(|frame| !config.is_vendor_file(&frame.file))),
+                            };
+                            view.message = match (frame.map(|frame| &item.backtrace[frame]), config.editor_command()) {
+                                (Some(frame), Some(command)) => {
+                                    let (file, line) = config.locate(frame);
+                                    editor::open(command, &file, line).err()
+                                }
+                                    .map(|error| format!("Could not open the editor: {}", error)),
+                                (None, _) => Some(String::from("No backtrace frame to open")),
+                                (_, None) => Some(String::from("Set an editor in the config to open files")),
+                            };
+                        }
+                    }
+                    Key::Char('y') => {
+                        let frame = table.selected().and_then(|item| {
+                            let row = view.frame_state.selected()?;
+                            let (_, frame_indexes) =
+                                build_backtrace_rows(&item.backtrace, &config, view.expand_vendor_frames);
+                            Some(config.locate(&item.backtrace[frame_indexes.get(row).copied().flatten()?]))
+                        });
+                        let permalink = frame.and_then(|(file, line)| view.blame(&file, line.max(1) as usize)?.permalink);
+                        view.message = Some(match permalink {
+                            Some(permalink) => match clipboard::copy(terminal.backend_mut(), &permalink) {
+                                Ok(()) => format!("Copied {}", permalink),
+                                Err(error) => format!("Could not copy: {}", error),
+                            },
+                            None => String::from("Select a frame of a committed file with a remote (J/K) to copy its permalink"),
+                        });
+                    }
+                    Key::Char(' ') if table.selected().is_some() => menu = Some(Menu::new()),
+                    Key::Char('d') => table.delete_selected(),
+                    Key::Char('D') => {
+                        if let Some(item) = table.selected() {
+                            view.diff_base = if view.diff_base == Some(item.id) { None } else { Some(item.id) };
+                            view.details = None;
+                            if view.diff_base.is_some() {
+                                view.message = Some(format!("Select other entries to compare them with entry {}, D again to stop", item.id));
+                            }
+                        }
+                    }
+                    Key::Char('Y') if table.selected().is_some() => {
+                        prompt = Some(Prompt::new(PromptAction::Copy, "Copy as json, text or curl (tab for the next)", "json"));
+                    }
+                    Key::Char('L') => {
+                        if let Some(item) = table.selected() {
+                            let only = Filter::label(&item.label, false);
+                            if table.filter.as_ref().is_some_and(|filter| filter.source == only.source) {
+                                table.set_filter(filter_before_label.take());
+                            } else {
+                                filter_before_label = table.filter.clone();
+                                table.set_filter(Some(only));
+                            }
+                        }
+                    }
+                    Key::Char('H') => {
+                        if let Some(item) = table.selected() {
+                            let hide = Filter::label(&item.label, true);
+                            view.message = Some(format!("Hiding the entries labeled {:?}, : and filter to change", item.label));
+                            let filter = match table.filter.clone() {
+                                Some(filter) => filter.and(hide),
+                                None => hide,
+                            };
+                            table.set_filter(Some(filter));
+                        }
+                    }
+                    Key::Char('W') => {
+                        view.watched_label = match (&view.watched_label, table.selected()) {
+                            (None, Some(item)) => Some(item.label.to_string()),
+                            _ => None,
+                        };
+                        view.details = None;
+                        view.message = view.watched_label.as_ref().map(|label| {
+                            format!("Showing the newest entry labeled {:?} with what changed since the one before, W to stop", label)
+                        });
+                    }
+                    Key::Char('g') => view.show_chart = !view.show_chart,
+                    Key::Char('M') | Key::Char('h') => {
+                        let source = table.selected().and_then(sources::of).map(str::to_string);
+                        view.message = Some(match (source, input) {
+                            (None, _) => String::from("The selected entry was not received from a client"),
+                            (Some(source), Key::Char('M')) => match table.sources.toggle_mute(&source) {
+                                Some(dropped) => format!("Unmuted {}, {} entries were dropped", source, dropped),
+                                None => format!("Muted {}, its entries are dropped and counted, M again to unmute", source),
+                            },
+                            (Some(source), _) => match table.sources.toggle_hold(&source) {
+                                Some(held) => {
+                                    let count = held.len();
+                                    table.resume_source(held);
+                                    format!("Resumed {}, added the {} entries held back", source, count)
+                                }
+                                None => format!("Holding back the entries of {}, h again to add them", source),
+                            },
+                        });
+                    }
+                    Key::Char('<') | Key::Char('>') => {
+                        let column = view.column.unwrap_or(0).min(config.columns.len() + 1);
+                        view.column = Some(match input {
+                            Key::Char('<') => column.saturating_sub(1),
+                            _ => (column + 1).min(config.columns.len() + 1),
+                        });
+                        view.message = Some(String::from("O sorts by the underlined column, { and } move it"));
+                    }
+                    Key::Char('O') => {
+                        let paths = arrangement::column_paths(&config.columns);
+                        let path = paths[view.column.unwrap_or(0).min(paths.len() - 1)];
+                        // Ascending, descending, and newest first again.
+                        let sort = match &table.sort {
+                            Some(sort) if sort.path == path && sort.descending => None,
+                            Some(sort) if sort.path == path => Some(Sort { path: path.to_string(), descending: true }),
+                            _ => Some(Sort { path: path.to_string(), descending: false }),
+                        };
+                        view.message = Some(match &sort {
+                            Some(sort) => format!("Sorted by {} {}", path, if sort.descending { "descending" } else { "ascending" }),
+                            None => String::from("Sorted newest first"),
+                        });
+                        table.set_sort(sort.clone());
+                        arrangement.update(&config.columns, sort);
+                    }
+                    Key::Char('{') | Key::Char('}') => {
+                        // Only the configured columns move, they stay between the entry and its tags.
+                        let column = view.column.unwrap_or(0);
+                        let target = match input {
+                            Key::Char('{') => column.saturating_sub(1),
+                            _ => column + 1,
+                        };
+                        let configured = 1..=config.columns.len();
+                        if configured.contains(&column) && configured.contains(&target) {
+                            config.columns.swap(column - 1, target - 1);
+                            view.column = Some(target);
+                            arrangement.update(&config.columns, table.sort.clone());
+                        } else if !configured.contains(&column) {
+                            view.message = Some(String::from("Select a configured column with < and > to move it"));
+                        }
+                    }
+                    Key::Char('P') => {
+                        if table.held.is_some() {
+                            table.resume();
+                        } else {
+                            table.pause();
+                        }
+                    }
+                    Key::Char('r') => {
+                        view.show_raw = !view.show_raw;
+                        view.details = None;
+                    }
+                    Key::Char('i') => {
+                        view.embedded_json = !view.embedded_json;
+                        view.details = None;
+                        view.message = Some(String::from(match view.embedded_json {
+                            true => "Showing json within strings parsed",
+                            false => "Showing json within strings as sent",
+                        }));
+                    }
+                    Key::Char('|') => {
+                        view.message = Some(String::from(match table.toggle_split() {
+                            true => "Split the entries over two lists, Tab moves to the other list",
+                            false => "Showing a single list",
+                        }));
+                        view.frame_state.select(None);
+                    }
+                    Key::Char('\t') if table.other.is_some() => {
+                        table.switch_pane();
+                        view.frame_state.select(None);
+                    }
+                    Key::Char('z') => {
+                        if let Err(error) = table.toggle_section() {
+                            view.message = Some(error);
+                        }
+                        view.frame_state.select(None);
+                    }
+                    Key::Char('f') => {
+                        if let Err(error) = table.select_first_occurrence() {
+                            view.message = Some(error);
+                        }
+                        view.frame_state.select(None);
+                    }
+                    Key::Char('E') | Key::Char('C') => {
+                        view.depth = if input == Key::Char('E') { None } else { Some(1) };
+                        view.details = None;
+                    }
+                    Key::Char('e') => {
+                        if let Some(item) = table.selected() {
+                            view.expanded = if view.expanded == Some(item.id) { None } else { Some(item.id) };
+                        }
+                    }
+                    Key::PageDown => view.details_scroll = view.details_scroll.saturating_add(view.details_height.max(1)),
+                    Key::PageUp => view.details_scroll = view.details_scroll.saturating_sub(view.details_height.max(1)),
+                    Key::Char('b') => view.show_backtrace = !view.show_backtrace,
+                    Key::Char('v') => view.expand_vendor_frames = !view.expand_vendor_frames,
+                    Key::Char('~') => view.show_diagnostics = !view.show_diagnostics,
+                    Key::Char('m') => {
+                        if let Some(item) = table.selected() {
+                            view.time_base = if view.time_base == Some(item.id) { None } else { Some(item.id) };
+                        }
+                    }
+                    Key::Char('Z') => {
+                        view.time_zone = view.time_zone.next();
+                        view.details = None;
+                        view.message = Some(format!("Showing times {}", view.time_zone.name()));
+                    }
+                    Key::Char('l') => {
+                        view.set_preset(view.preset.next());
+                        view.message = Some(format!("Layout: {}", view.preset.name()));
+                    }
+                    Key::Char('+') => view.resize_details(10),
+                    Key::Char('-') => view.resize_details(-10),
+                    Key::Char('p') => {
+                        if let Some(item) = table.selected_mut() {
+                            item.pinned = !item.pinned;
+                            let id = item.id;
+                            table.buffer.annotate(id);
+                        }
+                    }
+                    Key::Char('s') => prompt = Some(Prompt::new(PromptAction::Snapshot(Scope::Details), "Snapshot of the details to", "snapshot.txt")),
+                    Key::Char('S') => prompt = Some(Prompt::new(PromptAction::Snapshot(Scope::Screen), "Snapshot of the screen to", "snapshot.txt")),
+                    Key::Char('w') => prompt = Some(Prompt::new(PromptAction::SaveSession, "Save session to", "session.json")),
+                    Key::Char('t') if table.selected().is_some() => {
+                        prompt = Some(Prompt::new(PromptAction::Tag, "Tags (-tag removes)", ""));
+                    }
+                    Key::Char('.') => {
+                        let path = view.extract_path.clone().unwrap_or_else(|| String::from("."));
+                        prompt = Some(Prompt::new(PromptAction::Extract, "Extract", &path));
+                    }
+                    Key::Char(':') => {
+                        let values = history.values(PromptAction::Command);
+                        prompt = Some(Prompt::new(PromptAction::Command, "Command", "").with_history(values));
+                    }
+                    Key::Char('/') => {
+                        filter_before_search = table.filter.clone();
+                        let values = history.values(PromptAction::Search);
+                        prompt = Some(Prompt::new(PromptAction::Search, "Search", "").with_history(values));
+                    }
+                    Key::Char('x') if table.selected().is_some() => {
+                        prompt = Some(Prompt::new(PromptAction::ExportSelected, "Export entry to", "entry.json"));
+                    }
+                    Key::Char('X') => prompt = Some(Prompt::new(PromptAction::ExportAll, "Export entries to", "entries.json")),
+                    Key::Char('a') => {
+                        if let Some(item) = table.selected_mut() {
+                            let note = item.note.clone().unwrap_or_default();
+                            prompt = Some(Prompt::new(PromptAction::Note, "Note", &note));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            AppEvent::Received(received) => {
+                if !matches!(received, Received::Diagnostic(_) | Received::Raw(_)) {
+                    backlog.done();
+                }
+                if receive(&mut table, received, &mut script, &notifier, &mut alerts, &mut diagnostics) {
+                    alert::ring_bell(terminal.backend_mut())?;
+                }
+            }
+            AppEvent::Hooked(finished) => {
+                let (id, command) = (finished.id, finished.command);
+                match finished.output {
+                    Ok(output) => {
+                        diagnostics.log(&format!("Hook `{}` finished on entry {}", command, id));
+                        // Added to the note, after one typed in meanwhile.
+                        let item = table.buffer.items.iter_mut().find(|item| item.id == id);
+                        if let (true, false, Some(item)) = (finished.note, output.is_empty(), item) {
+                            item.note = Some(match item.note.take() {
+                                Some(note) => format!("{}\n{}", note, output),
+                                None => output,
+                            });
+                            table.buffer.annotate(id);
+                        }
+                    }
+                    Err(error) => {
+                        diagnostics.log(&format!("Hook `{}` failed on entry {}: {}", command, id, error));
+                        view.message = Some(format!("Hook `{}` failed: {}", command, error));
+                    }
+                }
+            }
+            AppEvent::Replayed(entry) => {
+                let mut entry = *entry;
+                table.redaction.apply(&mut entry);
+                label::apply(&table.label_rules, &mut entry);
+                table.push(entry);
+            }
+            AppEvent::Query(query) => query(&table),
+            AppEvent::ConfigChanged => match Config::load() {
+                Ok(mut reloaded) => {
+                    arrangement.arrange(&mut reloaded.columns);
+                    if cli_theme.is_none() {
+                        theme = reloaded.theme();
+                    }
+                    // Only replace the filter when the config changed it, to keep one typed in the palette.
+                    if reloaded.filter != config.filter {
+                        table.set_filter(reloaded.filter().unwrap_or_default());
+                    }
+                    notifier.webhooks = reloaded.webhooks.clone();
+                    notifier.hooks = reloaded.hooks.clone();
+                    table.redaction = reloaded.redact.clone();
+                    table.label_rules = reloaded.labels.clone();
+                    if reloaded.expand_depth != config.expand_depth {
+                        view.depth = reloaded.expand_depth;
+                    }
+                    if reloaded.layout != config.layout {
+                        view.set_preset(reloaded.layout);
+                    }
+                    if reloaded.time_zone != config.time_zone {
+                        view.time_zone = reloaded.time_zone;
+                    }
+                    // Watches added to the config start following, removed ones are kept until `unwatch`.
+                    for path in &reloaded.watches {
+                        table.watches.add(path, &table.buffer.items);
+                    }
+                    config = reloaded;
+                    // Highlights may have changed.
+                    view.details = None;
+                    view.row_styles.1.clear();
+                    view.message = Some(String::from("Reloaded config"));
+                    diagnostics.log("Reloaded config");
+                }
+                Err(error) => {
+                    let message = format!("Could not reload config: {}", error);
+                    diagnostics.log(&message);
+                    view.message = Some(message);
+                }
+            },
+            AppEvent::Error(error) => {
+                diagnostics.log(&error);
+                view.message = Some(error);
+            }
+            AppEvent::Focus(focused) => alerts.set_focus(focused),
+            AppEvent::Tick | AppEvent::Resize | AppEvent::RightClick(_, _) => {}
+        }
+    }
+
+    // Stop listening, so the port is free again right away.
+    listener.cancel().await;
+
+    if config.summary_on_exit {
+        // Printed once the terminal is restored.
+        drop(terminal);
+        print!("\n{}", summary::render(&table.buffer, table.started));
+    }
+
+    Ok(())
+}
+
+// Runs a command of the palette, or of a key bound in the config.
+fn execute(command: Command, table: &mut StatefulTable, view: &mut View) {
+    match command {
+        Command::Filter(filter) => table.set_filter(filter),
+        Command::Mark(name) => {
+            table.push(marker_entry(name));
+        }
+        Command::Watch(path) => table.watches.add(&path, &table.buffer.items),
+        Command::Goto(target) => {
+            let selected = table.select_entry(&target).or_else(|error| match filter::parse_moment(&target, false) {
+                Some(moment) => table.select_time(moment, view.time_zone).ok_or_else(|| format!("No entry at or after {}", target)),
+                None => Err(error),
+            });
+            if let Err(error) = selected {
+                view.message = Some(error);
+            }
+            view.frame_state.select(None);
+        }
+        Command::Unwatch(path) => {
+            if !table.watches.remove(path.as_deref()) {
+                view.message = Some(String::from("Not watching that path"));
+            }
+        }
+        Command::Follow(true) => {
+            if table.held.is_some() {
+                table.resume();
+            }
+            table.state.select(Some(0));
+            view.frame_state.select(None);
+        }
+        Command::Follow(false) => table.pause(),
+        Command::Summary(path) => {
+            view.message = Some(match fs::write(&path, summary::render(&table.buffer, table.started)) {
+                Ok(()) => format!("Wrote the session summary to {}", path),
+                Err(error) => format!("Could not write {}: {}", path, error),
+            });
+        }
+        Command::Unmute => {
+            let held = table.sources.clear();
+            table.resume_source(held);
+        }
+    }
+}
+
+// Adds what a client sent, after transforming it with the `--script`. Returns whether it raised an
+// alert.
+fn receive(
+    table: &mut StatefulTable,
+    received: Received,
+    script: &mut Option<Script>,
+    notifier: &Notifier,
+    alerts: &mut Alerts,
+    diagnostics: &mut Diagnostics,
+) -> bool {
+    match received {
+        Received::Entry(entry) => {
+            let mut entry = *entry;
+            table.redaction.apply(&mut entry);
+            label::apply(&table.label_rules, &mut entry);
+            if table.sources.drops(&entry) {
+                diagnostics.log(&format!("Dropped entry {:?}, its source is muted", entry.label));
+                table.raw.pending();
+                return false;
+            }
+            let entry = match script.as_mut().map(|script| script.transform(&entry)) {
+                Some(Ok(Some(transformed))) => transformed,
+                Some(Ok(None)) => {
+                    diagnostics.log(&format!("Script dropped entry {:?}", entry.label));
+                    return false;
+                }
+                // Keep the entry as it was received when the script fails.
+                Some(Err(error)) => {
+                    diagnostics.log(&format!("Script failed on entry {:?}: {}", entry.label, error));
+                    table.script_error = Some(error.to_string());
+                    entry
+                }
+                None => entry,
+            };
+            notifier.notify(&entry);
+            let alerted = alerts.check(&entry);
+
+            let (evicted, store_error) = (table.buffer.evicted, table.buffer.error.clone());
+            let label = entry.label.clone();
+            let raw = table.raw.pending();
+            if let Some(raw) = &raw {
+                table.throughput.entry(&raw.client);
+            }
+            match table.add(entry, raw) {
+                Some(id) => {
+                    diagnostics.log(&format!("Added entry {} {:?}", id, label));
+                    if let Some(item) = table.buffer.items.iter().find(|item| item.id == id) {
+                        notifier.run_hooks(item);
+                    }
+                }
+                None => diagnostics.log(&format!("Held back entry {:?} while the list or its source is paused", label)),
+            }
+            if table.buffer.evicted > evicted {
+                diagnostics.log(&format!("Evicted {} entries beyond --max-entries", table.buffer.evicted - evicted));
+            }
+            if table.buffer.error != store_error {
+                if let Some(error) = &table.buffer.error {
+                    diagnostics.log(error);
+                }
+            }
+            alerted
+        }
+        Received::ParseError(mut error) => {
+            diagnostics.log(&format!("Parse error from {}: {}", error.client, error.error));
+            error.payload = table.redaction.text(&error.payload);
+            let id = table.push_parse_error(error);
+            let raw = table.raw.pending();
+            table.raw.keep(id, raw);
+            false
+        }
+        Received::Diagnostic(message) => {
+            diagnostics.log(&message);
+            false
+        }
+        Received::Raw(payload) => {
+            table.throughput.payload(&payload.client, payload.size);
+            table.raw.receive(payload);
+            false
+        }
+    }
+}
+
+// Builds the entry list rows with a separator row whenever the section changes, marking the type of
+// the entries and flagging the ones with more data than `large_size` bytes. The second value maps each item index to its row index.
+#[allow(clippy::too_many_arguments)]
+fn build_entry_rows<'a>(
+    items: &[&'a DebugEntry],
+    highlights: &[Option<Style>],
+    markers: &[&str],
+    columns: &[Column],
+    number_formats: &[NumberFormat],
+    large_size: usize,
+    type_markers: &HashMap<Kind, TypeMarker>,
+    sections: &Sections,
+    density: Density,
+    label_width: Option<usize>,
+) -> (Vec<Row<'a>>, Vec<usize>) {
+    let margin = density.row_height() - 1;
+    let mut rows: Vec<Row> = vec![];
+    let mut row_indexes: Vec<usize> = vec![];
+    let mut previous_section: Option<&Option<String>> = None;
+
+    for ((item, highlight), marker) in items.iter().zip(highlights).zip(markers) {
+        if previous_section.map_or(item.section.is_some(), |section| *section != item.section) {
+            let title = match &item.section {
+                Some(section) if sections.is_folded(section) => format!("── ▸ {} ──", section),
+                Some(section) => format!("── {} ──", section),
+                None => String::from("──"),
+            };
+            rows.push(Row::new(vec![Cell::from(title)]).style(Style::default().fg(Color::DarkGray)).bottom_margin(margin));
+        }
+        previous_section = Some(&item.section);
+
+        let pin = if item.pinned { "* " } else { "" };
+        let large = item.size > large_size;
+        let prefix = format!("{}{}", marker, pin);
+        let mut suffix = match (item.elapsed, Progress::from_data(&item.data)) {
+            (Some(elapsed), _) => format!(" ({})", format_duration(elapsed)),
+            (None, Some(progress)) => format!(" ({})", progress.percentage()),
+            (None, None) => String::new(),
+        };
+        if large {
+            suffix.push_str(&format!(" [{}]", format_bytes(item.size as u64)));
+        }
+        if item.arrival.as_ref().is_some_and(|arrival| !arrival.invalid_utf8.is_empty()) {
+            suffix.push_str(" [not UTF-8]");
+        }
+        let type_marker = kind::marker(item, type_markers);
+        // The label gets what is left of the column after the markers and the suffix.
+        let name = match label_width {
+            Some(width) => density::truncate_middle(
+                &item.label,
+                width.saturating_sub(type_marker.width() + prefix.chars().count() + suffix.chars().count()),
+            ),
+            None => item.label.to_string(),
+        };
+        let label = format!("{}{}{}", prefix, name, suffix);
+        let mut cells = vec![Cell::from(Spans::from(vec![type_marker, Span::raw(label)]))];
+        cells.extend(columns.iter().map(|column| Cell::from(column_value(item, &column.path, number_formats))));
+        cells.push(Cell::from(item.tags.join(", ")));
+        let mut row = Row::new(cells).bottom_margin(margin);
+        let mut style = Style::default();
+        if let Some(color) = item.color.as_deref().and_then(parse_color) {
+            style = style.fg(color);
+        }
+        if large {
+            style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+        }
+        if let Some(highlight) = highlight {
+            style = style.patch(*highlight);
+        }
+        if style != Style::default() {
+            row = row.style(style);
+        }
+
+        row_indexes.push(rows.len());
+        rows.push(row);
+    }
+
+    (rows, row_indexes)
+}
+
+// Widths of the entry, configured and tags columns. The configured ones get their width, the entry
+// the rest.
+fn entry_column_widths(columns: &[Column]) -> Vec<Constraint> {
+    if columns.is_empty() {
+        return vec![Constraint::Percentage(70), Constraint::Percentage(30)];
+    }
+
+    let mut widths = vec![Constraint::Min(10)];
+    widths.extend(columns.iter().map(|column| Constraint::Length(column.width)));
+    widths.push(Constraint::Percentage(20));
+    widths
+}
+
+// Width of the entry column as the table lays it out, for truncating the labels to it.
+fn label_column_width(area: Rect, widths: &[Constraint]) -> usize {
+    // Without the borders and the highlight symbol, with a space between the columns.
+    let inner = Rect {
+        width: area.width.saturating_sub(4),
+        ..area
+    };
+    let mut constraints = vec![];
+    for (index, width) in widths.iter().enumerate() {
+        if index > 0 {
+            constraints.push(Constraint::Length(1));
+        }
+        constraints.push(*width);
+    }
+
+    Layout::default().direction(Direction::Horizontal).constraints(constraints).split(inner)[0].width as usize
+}
+
+// The value at the path of a configured column, empty when the entry has none.
+fn column_value(entry: &DebugEntry, path: &str, formats: &[NumberFormat]) -> String {
+    fn text(value: &Value, path: &str, formats: &[NumberFormat]) -> String {
+        match value {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            Value::Number(number) => number::format(formats, path, number),
+            Value::Array(items) => items.iter().map(|item| text(item, path, formats)).collect::<Vec<String>>().join(", "),
+            value => value.to_string(),
+        }
+    }
+
+    filter::resolve(entry, path).map_or_else(String::new, |value| text(&value, path, formats))
+}
+
+// Builds the backtrace rows, collapsing each run of vendor frames into a single row unless expanded.
+// The second value holds the frame index for each row, if it is not a collapsed row.
+fn build_backtrace_rows<'a>(
+    backtrace: &'a [BacktraceItem],
+    config: &Config,
+    expand_vendor: bool,
+) -> (Vec<Row<'a>>, Vec<Option<usize>>) {
+    let mut rows: Vec<Row> = vec![];
+    let mut frame_indexes: Vec<Option<usize>> = vec![];
+    let mut collapsed = 0;
+
+    let first = config.first_project_frame(backtrace);
+    let vendor_row = |count: usize| {
+        let plural = if count == 1 { "" } else { "s" };
+        Row::new(vec![Cell::from(format!("… {} vendor frame{} …", count, plural))])
+            .style(Style::default().fg(Color::DarkGray))
+    };
+
+    for (index, backtrace_item) in backtrace.iter().enumerate() {
+        let is_vendor = config.is_vendor_file(&backtrace_item.file);
+        if is_vendor && !expand_vendor {
+            collapsed += 1;
+            continue;
+        }
+        if collapsed > 0 {
+            rows.push(vendor_row(collapsed));
+            frame_indexes.push(None);
+            collapsed = 0;
+        }
+
+        let (file, line) = config.locate(backtrace_item);
+        let cells = vec![
+            Cell::from(config.display_path(&file)),
+            Cell::from(line.to_string()),
+            Cell::from(backtrace_item.function.as_str()),
+        ];
+        let mut row = Row::new(cells);
+        if is_vendor {
+            row = row.style(Style::default().fg(Color::DarkGray));
+        } else if first == Some(index) {
+            row = row.style(Style::default().add_modifier(Modifier::BOLD));
+        }
+        rows.push(row);
+        frame_indexes.push(Some(index));
+    }
+    if collapsed > 0 {
+        rows.push(vendor_row(collapsed));
+        frame_indexes.push(None);
+    }
+
+    (rows, frame_indexes)
+}
+
+fn build_diagnostics(diagnostics: &Diagnostics, height: u16, theme: &Theme) -> Paragraph<'static> {
+    let lines: Vec<Spans> = diagnostics
+        .last(height.saturating_sub(2) as usize)
+        .map(|line| Spans::from(line.clone()))
+        .collect();
+
+    Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Diagnostics")
+            .style(theme.panel()),
+    )
+}
+
+fn build_source_preview(line: usize, lines: Vec<(usize, String)>, blame: Option<&Blame>, theme: &Theme) -> Paragraph<'static> {
+    let mut text: Vec<Spans> = vec![];
+    if let Some(blame) = blame {
+        let info = format!("{} {}, {}", blame.short_hash(), blame.author, blame.summary);
+        text.push(Spans::from(Span::styled(info, Style::default().fg(Color::DarkGray))));
+    }
+    text.extend(lines.into_iter()
+        .map(|(number, content)| {
+            let span = Span::raw(format!("{:>5} {}", number, content));
+            if number == line {
+                Spans::from(Span::styled(span.content, Style::default().fg(Color::Black).bg(Color::Yellow)))
+            } else {
+                Spans::from(span)
+            }
+        }));
+
+    Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Source")
+            .style(theme.panel()),
+    )
+}
+
+// Renders the memory gauge and the session memory trend in the first two of the given areas.
+fn render_metrics<B: Backend>(f: &mut Frame<B>, areas: &[Rect], metrics: &Metrics, history: &[u64], theme: &Theme) {
+    let title = match metrics.execution_time {
+        // Whatever number the client sent, negative or too large ones as is.
+        Some(seconds) => match Duration::try_from_secs_f64(seconds) {
+            Ok(duration) => format!("Memory (executed in {})", format_duration(duration)),
+            Err(_) => format!("Memory (executed in {}s)", seconds),
+        },
+        None => String::from("Memory"),
+    };
+    let label = match (metrics.memory, metrics.peak_memory) {
+        (Some(memory), Some(peak)) => format!("{} of {} peak", format_bytes(memory), format_bytes(peak)),
+        (Some(memory), None) => format_bytes(memory),
+        (None, Some(peak)) => format!("{} peak", format_bytes(peak)),
+        (None, None) => String::from("No memory reported"),
+    };
+    let gauge = Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .gauge_style(Style::default().fg(Color::Green).bg(theme.background))
+        .ratio(metrics.memory_ratio().unwrap_or(0.0))
+        .label(label);
+    f.render_widget(gauge, areas[0]);
+
+    // Only the most recent values that fit inside the borders are shown.
+    let width = areas[1].width.saturating_sub(2) as usize;
+    let history = &history[history.len().saturating_sub(width)..];
+    let trend = Sparkline::default()
+        .block(Block::default().borders(Borders::ALL).title("Memory trend"))
+        .style(Style::default().fg(Color::Yellow))
+        .data(history);
+    f.render_widget(trend, areas[1]);
+}
+
+// Draws the entries listed by the table in the area, returning the range of listed entries shown and
+// the row each of them is on. `focused` is set while the entries are split over two lists, the title
+// then shows the filter of each and marks the one with focus.
+#[allow(clippy::too_many_arguments)]
+fn render_entry_list<B: Backend>(
+    f: &mut Frame<B>,
+    area: Rect,
+    table: &mut StatefulTable,
+    view: &mut View,
+    focused: Option<bool>,
+    config: &Config,
+    theme: &Theme,
+    comparison: Option<&Comparison>,
+) -> (Range<usize>, Vec<usize>) {
+    let selected_style = Style::default().add_modifier(Modifier::REVERSED);
+    let normal_style = Style::default().bg(theme.header);
+    let mut titles = vec!["Entry"];
+    titles.extend(config.columns.iter().map(Column::title));
+    titles.push("Tags");
+    // The sorted column shows its direction, the one chosen with `<` and `>` is underlined.
+    let header_cells = titles.into_iter().zip(arrangement::column_paths(&config.columns)).enumerate().map(|(index, (title, path))| {
+        let cell = match &table.sort {
+            Some(sort) if sort.path == path => Cell::from(format!("{} {}", title, if sort.descending { "▼" } else { "▲" })),
+            _ => Cell::from(title),
+        };
+        match view.column {
+            Some(column) if column.min(config.columns.len() + 1) == index => cell.style(Style::default().add_modifier(Modifier::UNDERLINED)),
+            _ => cell,
+        }
+    });
+    let header = Row::new(header_cells).style(normal_style).bottom_margin(1);
+    // Borders, header and its margin take four lines.
+    let window = table.scroll_to_selection((area.height.saturating_sub(4) / config.density.row_height()) as usize);
+    let items = &table.buffer.items;
+    let visible: Vec<&DebugEntry> = table.visible[window.clone()].iter().map(|index| &items[*index]).collect();
+    if view.row_styles.0 != table.revision {
+        view.row_styles = (table.revision, HashMap::new());
+    }
+    let row_styles: Vec<Option<Style>> = visible
+        .iter()
+        .map(|item| *view.row_styles.1.entry(item.id).or_insert_with(|| highlight::row_style(&config.highlights, item)))
+        .collect();
+    // Entries sent before with the same label and data are marked with `=`.
+    let markers: Vec<String> = visible
+        .iter()
+        .map(|item| {
+            let status = comparison.map_or("", |comparison| comparison.status(item).marker());
+            let duplicate = if table.duplicates.contains_key(&item.id) { "= " } else { "" };
+            format!("{}{}", status, duplicate)
+        })
+        .collect();
+    let markers: Vec<&str> = markers.iter().map(String::as_str).collect();
+    let widths = entry_column_widths(&config.columns);
+    let (rows, row_indexes) = build_entry_rows(
+        &visible,
+        &row_styles,
+        &markers,
+        &config.columns,
+        &config.number_formats,
+        config.large_entry_size,
+        &config.type_markers,
+        &table.sections,
+        config.density,
+        Some(label_column_width(area, &widths)).filter(|_| config.truncate_labels == Truncation::Middle),
+    );
+    let filter = table.filter.as_ref().map_or("all", |filter| filter.source.as_str());
+    let title = match focused {
+        None => Span::raw("Entries"),
+        Some(true) => Span::styled(format!("Entries: {}", filter), Style::default().add_modifier(Modifier::BOLD)),
+        Some(false) => Span::styled(format!("Entries: {}", filter), Style::default().add_modifier(Modifier::DIM)),
+    };
+    let table_widget = Table::new(rows)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title).style(theme.panel()))
+        .highlight_style(selected_style)
+        .highlight_symbol(if focused == Some(false) { "  " } else { "> " })
+        .widths(&widths);
+    // Section separators are extra rows, so the selected row differs from the selected entry.
+    let mut list_state = TableState::default();
+    list_state.select(
+        table
+            .state
+            .selected()
+            .and_then(|index| row_indexes.get(index.checked_sub(window.start)?).copied()),
+    );
+    f.render_stateful_widget(table_widget, area, &mut list_state);
+
+    (window, row_indexes)
+}
+
+// Renders the current entries and bytes per second with the busiest clients, above a graph of the
+// entries per second over the last minute.
+fn render_throughput<B: Backend>(f: &mut Frame<B>, area: Rect, throughput: &Throughput, theme: &Theme) {
+    const SHOWN_CLIENTS: usize = 3;
+
+    let block = Block::default().borders(Borders::ALL).title("Throughput").style(theme.panel());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rects = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1 + SHOWN_CLIENTS as u16), Constraint::Min(0)].as_ref())
+        .split(inner);
+
+    let (entries, bytes) = throughput.rates();
+    let mut lines = vec![Spans::from(Span::styled(
+        format!("{:.1} entries/s, {}/s", entries, format_bytes(bytes as u64)),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    let clients = throughput.clients();
+    for client in clients.iter().take(SHOWN_CLIENTS) {
+        lines.push(Spans::from(format!(
+            "{} {:.1}/s, {}/s",
+            client.client,
+            client.entries_per_second,
+            format_bytes(client.bytes_per_second as u64)
+        )));
+    }
+    if clients.is_empty() {
+        lines.push(Spans::from(Span::styled("Nothing received lately", Style::default().add_modifier(Modifier::DIM))));
+    }
+    f.render_widget(Paragraph::new(lines), rects[0]);
+
+    let history = throughput.history(rects[1].width as usize);
+    f.render_widget(Sparkline::default().style(Style::default().fg(Color::Cyan)).data(&history), rects[1]);
+}
+
+// Plots each series in its own color, over the seconds since the first entry.
+fn build_chart<'a>(label: &str, series: &'a [Series], theme: &Theme) -> Chart<'a> {
+    const COLORS: [Color; 6] = [Color::Cyan, Color::Yellow, Color::Magenta, Color::Green, Color::Red, Color::Blue];
+
+    let datasets = series
+        .iter()
+        .zip(COLORS.iter().cycle())
+        .map(|(series, color)| {
+            Dataset::default()
+                .name(series.name.as_str())
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(&series.points)
+        })
+        .collect();
+    let x_bounds = chart::bounds(series, |point| point.0);
+    let y_bounds = chart::bounds(series, |point| point.1);
+    let labels = |bounds: [f64; 2]| vec![Span::raw(format!("{:.1}", bounds[0])), Span::raw(format!("{:.1}", bounds[1]))];
+
+    Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(format!("{} over time", label)).style(theme.panel()))
+        .x_axis(Axis::default().title("seconds").bounds(x_bounds).labels(labels(x_bounds)))
+        .y_axis(Axis::default().bounds(y_bounds).labels(labels(y_bounds)))
+}
+
+// Groups of entries by origin, with the most entries first and a bar for how many they are next to
+// the origin with the most.
+fn build_groups<'a>(groups: &'a [Group], time_zone: TimeZone, theme: &Theme) -> Table<'a> {
+    const BAR_WIDTH: usize = 6;
+
+    let most = groups.first().map_or(1, |group| group.count.max(1));
+    let rows: Vec<Row> = groups
+        .iter()
+        .map(|group| {
+            let width = (group.count * BAR_WIDTH).div_ceil(most);
+            let heat = match width {
+                0..=2 => Color::Green,
+                3..=4 => Color::Yellow,
+                _ => Color::Red,
+            };
+            Row::new(vec![
+                Cell::from(format_count(group.count)),
+                Cell::from(Span::styled("█".repeat(width), Style::default().fg(heat))),
+                Cell::from(group.title()),
+                Cell::from(Span::styled(group.label.clone(), Style::default().add_modifier(Modifier::DIM))),
+                Cell::from(time_zone.display(&group.time)),
+            ])
+        })
+        .collect();
+
+    Table::new(rows)
+        .header(
+            Row::new(vec!["Entries", "", "Origin", "Latest", "Last seen"]).style(Style::default().bg(theme.header)).bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Top dump sites, enter shows the entries, space the latest, o opens the origin")
+                .style(theme.panel()),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ")
+        .widths(&[
+            Constraint::Length(8),
+            Constraint::Length(BAR_WIDTH as u16),
+            Constraint::Percentage(45),
+            Constraint::Percentage(25),
+            Constraint::Percentage(30),
+        ])
+}
+
+// The latest value of each watched path, with the values it had before it on the line below.
+fn build_watches(watches: &Watches, theme: &Theme) -> Paragraph<'static> {
+    const SHOWN_CHANGES: usize = 5;
+
+    let mut lines = vec![];
+    for watch in &watches.watches {
+        let latest = match watch.latest() {
+            Some(latest) => format!(" = {} (entry {}, {})", latest.value, latest.id, latest.time),
+            None => String::from(" not seen yet"),
+        };
+        lines.push(Spans::from(vec![
+            Span::styled(watch.path.clone(), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(latest),
+        ]));
+        let start = watch.changes.len().saturating_sub(SHOWN_CHANGES);
+        let history: Vec<&str> = watch.changes[start..].iter().map(|change| change.value.as_str()).collect();
+        let earlier = if start > 0 { format!("{} earlier, ", start) } else { String::new() };
+        lines.push(Spans::from(Span::styled(
+            format!("  {}{}", earlier, history.join(" → ")),
+            Style::default().add_modifier(Modifier::DIM),
+        )));
+    }
+
+    Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Watches").style(theme.panel()))
+}
+
+fn build_progress_gauge(progress: &Progress, theme: &Theme) -> Gauge<'static> {
+    let title = match &progress.message {
+        Some(message) => format!("{}: {}", progress.task, message),
+        None => progress.task.clone(),
+    };
+    let color = if progress.is_done() { Color::Green } else { Color::Cyan };
+
+    Gauge::default()
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .gauge_style(Style::default().fg(color).bg(theme.background))
+        .ratio(progress.ratio().unwrap_or(0.0))
+        .label(progress.summary())
+}
+
+// Passes on the entries one by one, waiting between them as long as between their original times.
+async fn replay<F: FnMut(DebugEntry)>(entries: Vec<DebugEntry>, speed: f64, mut push: F) {
+    let mut previous: Option<f64> = None;
+    for entry in entries {
+        let time = timestamp::parse(&entry.time);
+        if let (Some(previous), Some(time)) = (previous, time) {
+            // Times too far apart for a Duration, like a timestamp of 1e300, are not waited for.
+            task::sleep(Duration::try_from_secs_f64((time - previous).max(0.0) / speed).unwrap_or_default()).await;
+        }
+        previous = time.or(previous);
+
+        push(entry);
+    }
+}
+
+fn build_status_line(
+    table: &StatefulTable,
+    backlog: &Backlog,
+    source: &str,
+    comparison: Option<&Comparison>,
+    time_base: Option<u64>,
+) -> Spans<'static> {
+    let mut status = format!(
+        " {} | {} entries | {} received",
+        source,
+        table.buffer.items.len(),
+        format_bytes(table.received_bytes)
+    );
+
+    if let Some(comparison) = comparison {
+        let (changed, added, missing) = comparison.summary();
+        status.push_str(&format!(" | Baseline: {} changed, {} new, {} missing", changed, added, missing));
+    }
+
+    if let Some(filter) = &table.filter {
+        status.push_str(&format!(" | Filter: {} ({} shown)", filter.source, table.visible.len()));
+    }
+
+    if let Some(base) = time_base {
+        status.push_str(&format!(" | {}", measure_time(table, base)));
+    }
+
+    if let Some(held) = &table.held {
+        let on = table.paused_on.map_or_else(String::new, |id| format!(" on entry {}", id));
+        status.push_str(&format!(" | Paused{}, {} held back, P to resume", on, held.len()));
+    }
+
+    for source in table.sources.status() {
+        status.push_str(&format!(" | {}", source));
+    }
+
+    if table.buffer.evicted > 0 {
+        status.push_str(&format!(" | {} evicted", table.buffer.evicted));
+    }
+
+    if backlog.pending() > 0 {
+        status.push_str(&format!(" | {} queued", backlog.pending()));
+    }
+
+    if backlog.dropped() > 0 {
+        status.push_str(&format!(" | {} dropped, the queue was full", backlog.dropped()));
+    }
+
+    if table.buffer.paged > 0 {
+        status.push_str(&format!(" | {} paged in from disk", table.buffer.paged));
+    }
+
+    if let Some(error) = &table.buffer.error {
+        status.push_str(&format!(" | Store error: {}", error));
+    }
+
+    if let Some(error) = &table.script_error {
+        status.push_str(&format!(" | Script error: {}", error));
+    }
+
+    let timers = table.timers.running();
+    if !timers.is_empty() {
+        let timers: Vec<String> = timers
+            .iter()
+            .map(|(name, elapsed)| format!("{} {}", name, format_duration(*elapsed)))
+            .collect();
+        status.push_str(&format!(" | Running timers: {}", timers.join(", ")));
+    }
+
+    Spans::from(status)
+}
+
+// The time between the entry with the id and the selected one.
+fn measure_time(table: &StatefulTable, base: u64) -> String {
+    let base_entry = match table.buffer.items.iter().find(|item| item.id == base) {
+        Some(entry) => entry,
+        None => return format!("Entry {} to measure from is gone, m to measure from another", base),
+    };
+    let selected = match table.selected() {
+        Some(selected) if selected.id != base => selected,
+        _ => return format!("Measuring from entry {}, select another one", base),
+    };
+
+    match (timestamp::parse(&base_entry.time), timestamp::parse(&selected.time)) {
+        (Some(from), Some(to)) => {
+            // Times far apart, like a timestamp of 1e300, are no duration.
+            let elapsed = Duration::try_from_secs_f64((to - from).abs()).map_or_else(|_| String::from("n/a"), format_duration);
+            let direction = if to >= from { "after" } else { "before" };
+            format!("Entry {} is {} {} entry {}", selected.id, elapsed, direction, base)
+        }
+        _ => String::from("Cannot measure, the time of an entry cannot be read"),
+    }
+}
+
+// Spans borrowing the text of the given ones, to draw them without copying.
+fn borrow_spans<'a>(lines: &'a [Spans<'static>]) -> Vec<Spans<'a>> {
+    lines
+        .iter()
+        .map(|line| Spans::from(line.0.iter().map(|span| Span::styled(span.content.as_ref(), span.style)).collect::<Vec<Span>>()))
+        .collect()
+}
+
+fn build_paragraph_for_item(
+    item: &DebugEntry,
+    renderer: Option<&dyn Renderer>,
+    depth: Option<usize>,
+    time_zone: TimeZone,
+    embedded: bool,
+    formats: &[NumberFormat],
+    summary: &[String],
+) -> Vec<Spans<'static>> {
+    let mut result: Vec<Spans> = vec![];
+
+    result.push(Spans::from(match &item.reference {
+        Some(reference) => format!("Entry: #{} (ref {})", item.id, reference),
+        None => format!("Entry: #{}", item.id),
+    }));
+    result.push(Spans::from(format!("Logged on: {}", time_zone.display(&item.time))));
+    if !item.connection.is_empty() {
+        let fields: Vec<String> = item
+            .connection
+            .iter()
+            .map(|(key, value)| match value {
+                Value::String(text) => format!("{}={}", key, text),
+                value => format!("{}={}", key, value),
+            })
+            .collect();
+        result.push(Spans::from(format!("Connection: {}", fields.join(" "))));
+    }
+    if let Some(arrival) = &item.arrival {
+        result.push(Spans::from(format!(
+            "Received: {} from {}, payload #{} of {}",
+            time_zone.display(&arrival.time),
+            arrival.client,
+            arrival.sequence,
+            format_bytes(arrival.bytes as u64)
+        )));
+        if !arrival.invalid_utf8.is_empty() {
+            let paths: Vec<&str> = arrival.invalid_utf8.iter().map(|text| text.path.as_str()).collect();
+            result.push(Spans::from(Span::styled(
+                format!("Not UTF-8: {}, decoded as Latin-1 (r shows the bytes)", paths.join(", ")),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+    }
+    if item.size > 0 {
+        result.push(Spans::from(format!("Size: {}", format_bytes(item.size as u64))));
+    }
+    if let Some(elapsed) = item.elapsed {
+        result.push(Spans::from(format!("Elapsed: {}", format_duration(elapsed))));
+    }
+    if let Some(note) = &item.note {
+        // Hooks can add lines of output.
+        for (index, line) in note.lines().enumerate() {
+            let prefix = if index == 0 { "Note: " } else { "" };
+            result.push(Spans::from(format!("{}{}", prefix, line)));
+        }
+    }
+    result.push(Spans::from(String::new()));
+
+    // The fields configured for the label, the few usually looked for, aligned above all of the data.
+    let fields: Vec<(&str, String)> = summary
+        .iter()
+        .map(|path| (path.strip_prefix("data.").unwrap_or(path), column_value(item, path, formats)))
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    if !fields.is_empty() {
+        let width = fields.iter().map(|(name, _)| name.chars().count()).max().unwrap_or_default();
+        for (name, value) in fields {
+            result.push(Spans::from(vec![
+                Span::styled(format!("{:width$}  ", name, width = width), Style::default().add_modifier(Modifier::BOLD)),
+                Span::raw(value),
+            ]));
+        }
+        result.push(Spans::from(String::new()));
+    }
+
+    match renderer {
+        Some(renderer) => result.extend(renderer.render(item)),
+        None => {
+            for text_node in build_text_vec_from_hashmap(&item.data, 0, depth, embedded, formats) {
+                result.push(Spans::from(text_node));
+            }
+        }
+    }
+
+    result
+}
+
+// The lines of the entry with those of the other one, like its counterpart in the baseline, removed
+// lines in red and added ones in green.
+fn build_diff_paragraph(item: &DebugEntry, other: Option<&DebugEntry>, name: &str) -> Vec<Spans<'static>> {
+    let lines = compare::comparable_lines(item);
+    let other = match other {
+        Some(other) => other,
+        None => {
+            let mut result = vec![Spans::from(format!("Not in {}", name)), Spans::from(String::new())];
+            result.extend(lines.into_iter().map(Spans::from));
+            return result;
+        }
+    };
+
+    let mut result = vec![
+        Spans::from(format!("Logged on: {}, {} on: {}", item.time, name, other.time)),
+        Spans::from(String::new()),
+    ];
+    let diff = compare::diff(&compare::comparable_lines(other), &lines);
+    if diff.iter().all(|line| matches!(line, Line::Same(_))) {
+        result.push(Spans::from(format!("Same as {}", name)));
+        result.push(Spans::from(String::new()));
+    }
+    result.extend(diff.into_iter().map(|line| match line {
+        Line::Same(text) => Spans::from(format!("  {}", text)),
+        Line::Removed(text) => Spans::from(Span::styled(format!("- {}", text), Style::default().fg(Color::Red))),
+        Line::Added(text) => Spans::from(Span::styled(format!("+ {}", text), Style::default().fg(Color::Green))),
+    }));
+
+    result
+}
+
+// An entry separating the runs of a session, for filtering with `since marker`.
+fn marker_entry(name: Option<String>) -> DebugEntry {
+    let mut data = HashMap::new();
+    data.insert(String::from("marker"), name.clone().map_or(Value::Bool(true), Value::String));
+
+    DebugEntry {
+        label: format!("── {} ──", name.as_deref().unwrap_or("marker")).into(),
+        time: timestamp::now(),
+        data,
+        color: Some(String::from("cyan")),
+        ..DebugEntry::default()
+    }
+}
+
+// A line of the details without the dashes indenting nested keys.
+fn detail_line(line: &str) -> &str {
+    line.trim_start_matches('-').trim()
+}
+
+// The value on a line of the details, after its key.
+fn detail_value(line: &str) -> &str {
+    let line = detail_line(line);
+    line.split_once(' ').map_or(line, |(_, value)| value.trim())
+}
+
+// The lines as plain text, without their styles.
+fn spans_to_text(lines: &[Spans]) -> String {
+    lines
+        .iter()
+        .map(|line| line.0.iter().map(|span| span.content.as_ref()).collect::<String>())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+// Adds the comma separated tags, or removes them when prefixed with a `-`.
+fn apply_tags(tags: &mut Vec<String>, input: &str) {
+    for tag in input.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        match tag.strip_prefix('-') {
+            Some(removed) => tags.retain(|existing| existing != removed),
+            None => {
+                if !tags.iter().any(|existing| existing == tag) {
+                    tags.push(tag.to_string());
+                }
+            }
+        }
+    }
+}
+
+// Cuts off long details and lines, saying how much is left out. Returns the number of lines cut off.
+fn truncate_details(mut lines: Vec<Spans<'static>>) -> (Vec<Spans<'static>>, usize) {
+    let hidden = lines.len().saturating_sub(MAX_DETAIL_LINES);
+    lines.truncate(MAX_DETAIL_LINES);
+
+    let note = Style::default().add_modifier(Modifier::DIM);
+    for line in lines.iter_mut().filter(|line| line.width() > MAX_DETAIL_LINE_LENGTH) {
+        let text: String = line.0.iter().map(|span| span.content.as_ref()).collect();
+        let style = line.0.first().map_or_else(Style::default, |span| span.style);
+        let cut: String = text.chars().take(MAX_DETAIL_LINE_LENGTH).collect();
+        let more = text.chars().count() - MAX_DETAIL_LINE_LENGTH;
+        *line = Spans::from(vec![
+            Span::styled(cut, style),
+            Span::styled(format!(" … {} more characters (press e to expand)", format_count(more)), note),
+        ]);
+    }
+    if hidden > 0 {
+        lines.push(Spans::from(Span::styled(
+            format!("… {} more lines (press e to expand)", format_count(hidden)),
+            note,
+        )));
+    }
+
+    (lines, hidden)
+}
+
+// A count with thousands separators, like `4,200`.
+fn format_count(count: usize) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+
+    formatted
+}
+
+// The payload of the entry as received, after the bytes of its strings that are not valid UTF-8.
+fn build_raw_paragraph(raw: &RawPayloads, item: &DebugEntry) -> Vec<Spans<'static>> {
+    let mut lines = vec![];
+    for text in item.arrival.iter().flat_map(|arrival| &arrival.invalid_utf8) {
+        lines.push(format!("{}, not valid UTF-8 and shown decoded as Latin-1:", text.path));
+        lines.extend(raw::dump(&text.bytes));
+        lines.push(String::new());
+    }
+    match raw.get(item.id) {
+        Some(payload) => lines.extend(raw::lines(payload)),
+        None => lines.push(format!(
+            "The payload of entry {} was not kept, only those of the latest entries received are (see raw_payloads in the config)",
+            item.id
+        )),
+    }
+
+    lines.into_iter().map(Spans::from).collect()
+}
+
+fn build_extracted_paragraph(item: &DebugEntry, path: &str) -> Vec<Spans<'static>> {
+    let values = match extract::extract(&item.data, path) {
+        Ok(values) => values,
+        Err(error) => return vec![Spans::from(error)],
+    };
+    if values.is_empty() {
+        return vec![Spans::from(format!("Nothing found at {}", path))];
+    }
+
+    values
+        .iter()
+        .flat_map(|value| {
+            let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+            pretty.lines().map(|line| Spans::from(line.to_string())).collect::<Vec<Spans>>()
+        })
+        .collect()
+}
+
+// Nested objects beyond the depth are collapsed into a line with their number of keys. With `embedded`
+// strings holding json are shown parsed, like any other nested object. Numbers are shown in the
+// format configured for their path.
+fn build_text_vec_from_hashmap(
+    map: &HashMap<String, Value>,
+    level: usize,
+    depth: Option<usize>,
+    embedded: bool,
+    formats: &[NumberFormat],
+) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+
+    let indent = "-".repeat(level * 2);
+
+    for (val_type, value) in map {
+        let path = format!("data.{}", val_type);
+        match value {
+            Value::Null => result.push(format!("{} {}", indent, val_type)),
+            Value::Bool(value) => result.push(format!("{} {} {}", indent, val_type, value)),
+            Value::Number(value) => result.push(format!("{} {} {}", indent, val_type, number::format(formats, &path, value))),
+            Value::String(value) => match embedded_json(value).filter(|_| embedded) {
+                Some(object) => {
+                    result.push(format!("{} {} (json)", indent, val_type));
+                    result.extend_from_slice(&build_text_vec_from_object(&object, level + 1, val_type, &path, depth, embedded, formats))
+                }
+                None => result.push(format!("{} {} {}", indent, val_type, value)),
+            },
+            Value::Array(_) => { /* No need to handle */ }
+            Value::Object(value) => {
+                result.extend_from_slice(&build_text_vec_from_object(value, level + 1, val_type, &path, depth, embedded, formats))
+            }
+        }
+    }
+
+    result
+}
+
+// The json within a string, like a serialized column or the body of an api response, as an object
+// to show nested in the details. Arrays are keyed by their indexes.
+fn embedded_json(text: &str) -> Option<Map<String, Value>> {
+    let text = text.trim();
+    if !matches!((text.chars().next(), text.chars().last()), (Some('{'), Some('}')) | (Some('['), Some(']'))) {
+        return None;
+    }
+
+    match serde_json::from_str(text).ok()? {
+        Value::Object(object) => Some(object),
+        Value::Array(items) if !items.is_empty() => {
+            Some(items.into_iter().enumerate().map(|(index, item)| (index.to_string(), item)).collect())
+        }
+        _ => None,
+    }
+}
+
+// The lines of an object at the path of the data, with `label` as the key it is under.
+fn build_text_vec_from_object(
+    value: &Map<String, Value>,
+    level: usize,
+    label: &str,
+    path: &str,
+    depth: Option<usize>,
+    embedded: bool,
+    formats: &[NumberFormat],
+) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    let indent = "-".repeat(level * 2);
+    for (item_key, item_value) in value {
+        let item_path = format!("{}.{}", path, item_key);
+        match item_value {
+            Value::Null => result.push(format!("{} {} NULL", indent, item_key)),
+            Value::Bool(value) => result.push(format!("{} {} {}", indent, item_key, value)),
+            Value::Number(value) => result.push(format!("{} {} {}", indent, item_key, number::format(formats, &item_path, value))),
+            Value::String(value) => match embedded_json(value).filter(|_| embedded) {
+                Some(object) if depth.is_some_and(|depth| level >= depth) => {
+                    let plural = if object.len() == 1 { "" } else { "s" };
+                    result.push(format!("{} {} (json) … {} key{}, press E to expand", indent, item_key, object.len(), plural));
+                }
+                Some(object) => {
+                    result.push(format!("{} {} (json)", indent, item_key));
+                    result.extend_from_slice(&build_text_vec_from_object(&object, level + 1, item_key, &item_path, depth, embedded, formats))
+                }
+                None => result.push(format!("{} {} {}", indent, item_key, value)),
+            },
+            Value::Array(_) => { /* No need to handle */ }
+            Value::Object(value) if depth.is_some_and(|depth| level >= depth) => {
+                let plural = if value.len() == 1 { "" } else { "s" };
+                result.push(format!("{} {} ({}) … {} key{}, press E to expand", indent, item_key, label, value.len(), plural));
+            }
+            Value::Object(value) => {
+                // Otherwise we duplicate this information. To consider: Would this break values
+                // with the name "array"?
+                if item_key != "array" {
+                    result.push(format!("{} {} ({})", indent, item_key, label));
+                }
+                result.extend_from_slice(&build_text_vec_from_object(value, level + 1, item_key, &item_path, depth, embedded, formats))
+            }
+        };
+    }
+
+    result
 }
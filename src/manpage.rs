@@ -0,0 +1,60 @@
+use crate::cli::{Takes, COMMANDS, OPTIONS};
+
+// The man page printed by `udl manpage`, in roff, built from the options and subcommands of
+// `cli.rs`. Install it with `udl manpage > /usr/local/share/man/man1/udl.1`.
+pub fn render() -> String {
+    let mut page = format!(".TH UDL 1 \"\" \"udl {}\" \"User Commands\"\n", env!("CARGO_PKG_VERSION"));
+    page.push_str(".SH NAME\nudl \\- universal debug logger, showing what applications dump in the terminal\n");
+
+    page.push_str(".SH SYNOPSIS\n");
+    for (index, (name, arguments, _)) in COMMANDS.iter().enumerate() {
+        if index > 0 {
+            page.push_str(".br\n");
+        }
+        let name = if *name == "listen" { "[listen]" } else { name };
+        page.push_str(&format!(".B udl\n{}\n", escape(format!("{} {}", name, arguments).trim_end())));
+    }
+
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(&escape(
+        "udl listens for debug entries sent by applications over tcp, json terminated by a null byte by default, and \
+         shows them in an interactive list with their data and backtrace. The readme describes the keys, the payload \
+         format and the config file.",
+    ));
+    page.push('\n');
+
+    page.push_str(".SH COMMANDS\n");
+    for (name, arguments, description) in COMMANDS {
+        page.push_str(&format!(".TP\n.B {}\n", escape(format!("{} {}", name, arguments).trim_end())));
+        page.push_str(&format!("{}.\n", escape(description)));
+    }
+
+    page.push_str(".SH OPTIONS\n");
+    for option in OPTIONS {
+        let mut names = vec![];
+        if !option.short.is_empty() {
+            names.push(format!("\\fB\\-{}\\fR", option.short));
+        }
+        names.push(format!("\\fB\\-\\-{}\\fR", escape(option.long)));
+        let value = match option.takes {
+            Takes::Nothing => String::new(),
+            Takes::Value(hint) | Takes::Values(hint) => format!(" \\fI{}\\fR", escape(hint)),
+        };
+        page.push_str(&format!(".TP\n{}{}\n{}\n", names.join(", "), value, escape(option.description)));
+    }
+
+    page.push_str(".SH FILES\n.TP\n.I ~/.config/udl/config.json\nThe config file, see the readme for its keys.\n");
+    page.push_str(".SH ENVIRONMENT\n.TP\n.B UDL_CONFIG\nPath of the config file to use instead.\n");
+
+    page
+}
+
+// Escapes backslashes and dashes, and lines starting with a control character.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
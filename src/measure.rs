@@ -0,0 +1,59 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+// A measurement marker sent by a client as `measure_start` or `measure_stop` in the data map.
+pub enum Measure {
+    Start(String),
+    Stop(String),
+}
+
+impl Measure {
+    pub fn from_data(data: &HashMap<String, Value>) -> Option<Measure> {
+        if let Some(Value::String(name)) = data.get("measure_start") {
+            return Some(Measure::Start(name.to_string()));
+        }
+        if let Some(Value::String(name)) = data.get("measure_stop") {
+            return Some(Measure::Stop(name.to_string()));
+        }
+
+        None
+    }
+}
+
+// Stopwatch-style timers keyed by the name the client gave them.
+#[derive(Default)]
+pub struct Timers {
+    running: HashMap<String, Instant>,
+}
+
+impl Timers {
+    pub fn start(&mut self, name: String) {
+        self.running.insert(name, Instant::now());
+    }
+
+    pub fn stop(&mut self, name: &str) -> Option<Duration> {
+        self.running.remove(name).map(|started| started.elapsed())
+    }
+
+    // Running timers sorted by name, with the time elapsed so far.
+    pub fn running(&self) -> Vec<(&str, Duration)> {
+        let mut result: Vec<(&str, Duration)> = self
+            .running
+            .iter()
+            .map(|(name, started)| (name.as_str(), started.elapsed()))
+            .collect();
+        result.sort_by(|a, b| a.0.cmp(b.0));
+
+        result
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    if millis < 1000.0 {
+        format!("{:.2}ms", millis)
+    } else {
+        format!("{:.2}s", millis / 1000.0)
+    }
+}
@@ -0,0 +1,102 @@
+use termion::event::Key;
+use tui::layout::Rect;
+use tui::style::{Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, List, ListItem, ListState};
+
+// Actions on the selected entry, with the key that does the same.
+pub const ENTRY_ACTIONS: [(&str, char); 16] = [
+    ("Copy as json, text or curl", 'Y'),
+    ("Export to a file", 'x'),
+    ("Pin or unpin", 'p'),
+    ("Tag", 't'),
+    ("Add a note", 'a'),
+    ("Delete", 'd'),
+    ("Diff other entries with this one", 'D'),
+    ("Go to the first identical entry", 'f'),
+    ("Show only entries with this label", 'L'),
+    ("Hide entries with this label", 'H'),
+    ("Mute its source", 'M'),
+    ("Hold back its source", 'h'),
+    ("Measure the time from this entry", 'm'),
+    ("Open the frame in the editor", 'o'),
+    ("Show in full", 'e'),
+    ("Show the payload as received", 'r'),
+];
+
+pub enum MenuResult {
+    Pending,
+    Cancelled,
+    // The key of the chosen action, handled as if it was pressed.
+    Chosen(Key),
+}
+
+// A popup listing the actions on the selected entry, opened with space or a right click.
+pub struct Menu {
+    state: ListState,
+}
+
+impl Menu {
+    pub fn new() -> Menu {
+        let mut state = ListState::default();
+        state.select(Some(0));
+
+        Menu { state }
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> MenuResult {
+        let selected = self.state.selected().unwrap_or(0);
+        match key {
+            Key::Esc | Key::Char(' ') => MenuResult::Cancelled,
+            Key::Char('j') | Key::Down => {
+                self.state.select(Some((selected + 1) % ENTRY_ACTIONS.len()));
+                MenuResult::Pending
+            }
+            Key::Char('k') | Key::Up => {
+                self.state.select(Some((selected + ENTRY_ACTIONS.len() - 1) % ENTRY_ACTIONS.len()));
+                MenuResult::Pending
+            }
+            Key::Char('\n') => MenuResult::Chosen(Key::Char(ENTRY_ACTIONS[selected].1)),
+            // The key of an action runs it right away.
+            Key::Char(c) if ENTRY_ACTIONS.iter().any(|(_, key)| *key == c) => MenuResult::Chosen(key),
+            _ => MenuResult::Pending,
+        }
+    }
+
+    pub fn state(&mut self) -> &mut ListState {
+        &mut self.state
+    }
+
+    // The list of actions and the area to draw it in, centered in the given one.
+    pub fn widget(area: Rect, style: Style) -> (List<'static>, Rect) {
+        let items: Vec<ListItem> = ENTRY_ACTIONS
+            .iter()
+            .map(|(label, key)| {
+                ListItem::new(Spans::from(vec![
+                    Span::styled(format!(" {} ", key), Style::default().add_modifier(Modifier::BOLD)),
+                    Span::raw(*label),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Actions").style(style))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+        let width = 40.min(area.width);
+        let height = (ENTRY_ACTIONS.len() as u16 + 2).min(area.height);
+        let rect = Rect::new(
+            area.x + (area.width - width) / 2,
+            area.y + (area.height - height) / 2,
+            width,
+            height,
+        );
+
+        (list, rect)
+    }
+}
+
+impl Default for Menu {
+    fn default() -> Menu {
+        Menu::new()
+    }
+}
@@ -0,0 +1,43 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Application metrics sent by a client as a `metrics` object in the data map.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Metrics {
+    pub memory: Option<u64>,
+    pub peak_memory: Option<u64>,
+    // Execution time in seconds.
+    pub execution_time: Option<f64>,
+}
+
+impl Metrics {
+    pub fn from_data(data: &HashMap<String, Value>) -> Option<Metrics> {
+        data.get("metrics")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    // Memory usage relative to the peak memory, clamped between 0 and 1.
+    pub fn memory_ratio(&self) -> Option<f64> {
+        match (self.memory, self.peak_memory) {
+            (Some(memory), Some(peak)) if peak > 0 => Some((memory as f64 / peak as f64).min(1.0)),
+            _ => None,
+        }
+    }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, units[unit])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
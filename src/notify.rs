@@ -0,0 +1,81 @@
+use crate::filter::Filter;
+use crate::hook::{Finished, Hook};
+use crate::util::event::AppEvent;
+use crate::webhook::Webhook;
+use crate::DebugEntry;
+use async_std::channel::Sender;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+// Desktop notifications, and optionally a terminal bell, for entries matching one of the `--notify`
+// filters. Notifications are sent with `notify-send` on Linux and `osascript` on macOS. Entries
+// matching a webhook from the config are posted to it as well, and run its hooks once added.
+pub struct Notifier {
+    pub rules: Vec<Filter>,
+    pub bell: bool,
+    pub webhooks: Vec<Webhook>,
+    pub hooks: Vec<Hook>,
+    // Receives the hooks that finished, to add their output to the notes. Without it, failing hooks
+    // are reported on stderr.
+    pub hooked: Option<Sender<AppEvent>>,
+}
+
+impl Notifier {
+    pub fn notify(&self, entry: &DebugEntry) {
+        for webhook in self.webhooks.iter().filter(|webhook| webhook.matches(entry)) {
+            webhook.post(entry);
+        }
+
+        if !self.rules.iter().any(|rule| rule.matches(entry)) {
+            return;
+        }
+
+        let body = format!("{} at {}", entry.label, entry.time);
+        // Failing to notify should not interrupt receiving entries.
+        let _ = desktop_notification("udl", &body);
+        if self.bell {
+            // On stderr, to keep it out of the `--headless` output.
+            let mut stderr = io::stderr();
+            let _ = stderr.write_all(b"\x07").and_then(|_| stderr.flush());
+        }
+    }
+}
+
+impl Notifier {
+    // Runs the hooks matching the entry, which has its id once added.
+    pub fn run_hooks(&self, entry: &DebugEntry) {
+        for hook in self.hooks.iter().filter(|hook| hook.matches(entry)) {
+            let hooked = self.hooked.clone();
+            hook.run(entry, move |finished: Finished| match hooked {
+                Some(sender) => {
+                    let _ = sender.try_send(AppEvent::Hooked(finished));
+                }
+                None => {
+                    if let Err(error) = finished.output {
+                        eprintln!("Hook `{}` failed: {}", finished.command, error);
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn desktop_notification(title: &str, body: &str) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            body.replace('"', "'"),
+            title
+        ));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(body);
+        command
+    };
+
+    command.stdout(Stdio::null()).stderr(Stdio::null()).spawn()?;
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use crate::metrics::format_bytes;
+use serde::Deserialize;
+use serde_json::Number;
+
+// How the numbers at a path of the data are shown, configured as `number_formats` in the config
+// file, like `{"path": "data.response.size", "unit": "bytes"}`. The first format matching the path
+// of a number applies.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NumberFormat {
+    // Path of the numbers, like `data.duration`, where `*` matches any key.
+    pub path: String,
+    // Shows the numbers as a size or duration instead.
+    pub unit: Option<Unit>,
+    // Digits after the decimal point.
+    pub decimals: Option<usize>,
+    // Separates the thousands with commas, like `4,200`.
+    #[serde(default)]
+    pub thousands: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    // A count of bytes, like `1.2 MB`.
+    Bytes,
+    // A duration in milliseconds, like `1m 12s`.
+    #[serde(alias = "milliseconds")]
+    Ms,
+    // A duration in seconds.
+    #[serde(alias = "seconds")]
+    S,
+}
+
+impl NumberFormat {
+    fn matches(&self, path: &str) -> bool {
+        let mut keys = path.split('.');
+        self.path.split('.').all(|pattern| keys.next().is_some_and(|key| pattern == "*" || pattern == key)) && keys.next().is_none()
+    }
+
+    fn apply(&self, number: &Number) -> String {
+        let value = number.as_f64().unwrap_or_default();
+        match self.unit {
+            Some(Unit::Bytes) if value < 0.0 => format!("-{}", format_bytes(-value as u64)),
+            Some(Unit::Bytes) => format_bytes(value as u64),
+            Some(Unit::Ms) => humanize_millis(value),
+            Some(Unit::S) => humanize_millis(value * 1000.0),
+            None => {
+                let text = match self.decimals {
+                    Some(decimals) => format!("{:.*}", decimals, value),
+                    None => number.to_string(),
+                };
+                if self.thousands {
+                    separate_thousands(&text)
+                } else {
+                    text
+                }
+            }
+        }
+    }
+}
+
+// The number at the path of the data, like `data.user.id`, as the first matching format shows it.
+pub fn format(formats: &[NumberFormat], path: &str, number: &Number) -> String {
+    match formats.iter().find(|format| format.matches(path)) {
+        Some(format) => format.apply(number),
+        None => number.to_string(),
+    }
+}
+
+// A duration like `850ms`, `1.25s`, `2m 5s` or `3h 20m`.
+fn humanize_millis(millis: f64) -> String {
+    let sign = if millis < 0.0 { "-" } else { "" };
+    let millis = millis.abs();
+    let seconds = (millis / 1000.0) as u64;
+    let text = match seconds {
+        _ if millis < 1000.0 => format!("{}ms", (millis * 100.0).round() / 100.0),
+        0..=59 => format!("{:.2}s", millis / 1000.0),
+        60..=3599 => format!("{}m {}s", seconds / 60, seconds % 60),
+        3600..=86399 => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {}h", seconds / 86400, seconds % 86400 / 3600),
+    };
+
+    format!("{}{}", sign, text)
+}
+
+// Inserts commas between the thousands of the whole part of the number.
+fn separate_thousands(text: &str) -> String {
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let (whole, fraction) = match rest.find(['.', 'e', 'E']) {
+        Some(position) => rest.split_at(position),
+        None => (rest, ""),
+    };
+
+    let mut separated = String::from(sign);
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index).is_multiple_of(3) {
+            separated.push(',');
+        }
+        separated.push(digit);
+    }
+    separated.push_str(fraction);
+
+    separated
+}
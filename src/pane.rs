@@ -0,0 +1,33 @@
+use crate::filter::Filter;
+use tui::widgets::TableState;
+
+// A second list of the entries next to the first one, with a filter of its own, opened with `|`. The
+// lists share the buffered entries, the list without focus keeps its selection, scroll position,
+// filter and listed entries here and they are swapped with those of the table when moving the focus.
+#[derive(Default)]
+pub struct Pane {
+    pub state: TableState,
+    pub offset: usize,
+    // Indexes of the buffered items matching the filter, newest first like the items.
+    pub visible: Vec<usize>,
+    pub filter: Option<Filter>,
+    // Whether this is the left list, so the lists stay in place when the focus moves.
+    pub left: bool,
+}
+
+impl Pane {
+    // Shifts the listed entries for an entry added at the front of the buffer.
+    pub fn shift(&mut self) {
+        for index in self.visible.iter_mut() {
+            *index += 1;
+        }
+    }
+
+    // Drops the entries evicted from the buffer, keeping `length` entries.
+    pub fn truncate(&mut self, length: usize) {
+        self.visible.retain(|index| *index < length);
+        if self.state.selected().is_some_and(|index| index >= self.visible.len()) {
+            self.state.select(self.visible.len().checked_sub(1));
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::daemon;
+use std::io::ErrorKind;
+use std::net::TcpListener;
+use std::os::unix::net::UnixStream;
+
+// Ports after the one asked for that are tried when it is taken.
+const FALLBACK_PORTS: u16 = 20;
+
+// How the port asked for is taken, checked on start so another udl or app on it does not leave this
+// one without a listener.
+pub enum Conflict {
+    // A `udl daemon` listens on the port, its entries can be shown by attaching to it.
+    Daemon,
+    // Another application listens on the port, this is the next free one.
+    Moved(u16),
+}
+
+// `None` when the port is free, or when binding fails for another reason that listening reports.
+// A daemon on the port is only looked for with `attach`.
+pub fn check(host: &str, port: u16, attach: bool) -> Result<Option<Conflict>, String> {
+    match TcpListener::bind((host, port)) {
+        Err(error) if error.kind() == ErrorKind::AddrInUse => {}
+        _ => return Ok(None),
+    }
+    if attach && UnixStream::connect(daemon::socket_path(port)).is_ok() {
+        return Ok(Some(Conflict::Daemon));
+    }
+
+    (port.saturating_add(1)..=port.saturating_add(FALLBACK_PORTS))
+        .find(|port| TcpListener::bind((host, *port)).is_ok())
+        .map(|port| Some(Conflict::Moved(port)))
+        .ok_or_else(|| format!("Port {} is in use, and so are the {} ports after it. Use --port to pick another one", port, FALLBACK_PORTS))
+}
@@ -0,0 +1,67 @@
+use serde::Deserialize;
+use tui::layout::Direction;
+
+// Arrangements of the entries list, details and backtrace, switched with `l` or set with `layout` in
+// the config.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Preset {
+    // The list left of the details, with the backtrace below them.
+    #[default]
+    Default,
+    // A narrow list, leaving most of the width to the details.
+    WideDetail,
+    // The list above the details, for narrow terminals.
+    Stacked,
+    // The backtrace taking most of the height next to the list.
+    Backtrace,
+    // Only the list.
+    Compact,
+}
+
+pub const PRESETS: [Preset; 5] = [Preset::Default, Preset::WideDetail, Preset::Stacked, Preset::Backtrace, Preset::Compact];
+
+impl Preset {
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Default => "default",
+            Preset::WideDetail => "wide-detail",
+            Preset::Stacked => "stacked",
+            Preset::Backtrace => "backtrace",
+            Preset::Compact => "compact",
+        }
+    }
+
+    pub fn next(self) -> Preset {
+        let index = PRESETS.iter().position(|preset| *preset == self).unwrap_or(0);
+        PRESETS[(index + 1) % PRESETS.len()]
+    }
+
+    // How the list and the details are placed next to each other.
+    pub fn direction(self) -> Direction {
+        match self {
+            Preset::Stacked => Direction::Vertical,
+            _ => Direction::Horizontal,
+        }
+    }
+
+    // Part of the screen taken by the list, and by the baseline as well when comparing.
+    pub fn list_percentage(self) -> u16 {
+        match self {
+            Preset::Default | Preset::Backtrace => 25,
+            Preset::WideDetail => 15,
+            Preset::Stacked => 35,
+            Preset::Compact => 100,
+        }
+    }
+
+    // Part of the details and backtrace taken by the details, until resized with `+` and `-`.
+    pub fn details_percentage(self) -> u16 {
+        match self {
+            Preset::Default | Preset::Compact => 70,
+            Preset::WideDetail => 80,
+            Preset::Stacked => 60,
+            Preset::Backtrace => 30,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Progress of a long running task sent by a client as a `progress` object in the data map. Updates
+// for the same task replace the entry of the previous one instead of adding a row each.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Progress {
+    pub task: String,
+    pub current: f64,
+    pub total: Option<f64>,
+    pub message: Option<String>,
+}
+
+impl Progress {
+    pub fn from_data(data: &HashMap<String, Value>) -> Option<Progress> {
+        data.get("progress")
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+    }
+
+    // Part done, clamped between 0 and 1, when the total is known.
+    pub fn ratio(&self) -> Option<f64> {
+        match self.total {
+            Some(total) if total > 0.0 => Some((self.current / total).clamp(0.0, 1.0)),
+            _ => None,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.ratio().is_some_and(|ratio| ratio >= 1.0)
+    }
+
+    // Like `40 of 100 (40%)`, or only the current count without a total.
+    pub fn summary(&self) -> String {
+        match (self.total, self.ratio()) {
+            (Some(total), Some(ratio)) => format!("{} of {} ({:.0}%)", self.current, total, ratio * 100.0),
+            _ => self.current.to_string(),
+        }
+    }
+
+    // Short form for the entries list.
+    pub fn percentage(&self) -> String {
+        match self.ratio() {
+            Some(ratio) => format!("{:.0}%", ratio * 100.0),
+            None => self.current.to_string(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::snapshot::Scope;
+use termion::event::Key;
+
+// What to do with the value once the prompt is submitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptAction {
+    Note,
+    SaveSession,
+    ExportSelected,
+    ExportAll,
+    Command,
+    Extract,
+    Tag,
+    Search,
+    Copy,
+    Snapshot(Scope),
+}
+
+pub enum PromptResult {
+    Pending,
+    // Tab was pressed to complete the value.
+    Complete,
+    Cancelled,
+    Submitted(String),
+}
+
+// A single line text input shown in place of the status bar.
+pub struct Prompt {
+    pub action: PromptAction,
+    pub title: String,
+    pub value: String,
+    // Values submitted before, oldest first, to go through with up and down.
+    history: Vec<String>,
+    // Position in the history shown, and the value typed before going through it.
+    history_position: Option<usize>,
+    draft: String,
+}
+
+impl Prompt {
+    pub fn new(action: PromptAction, title: &str, value: &str) -> Prompt {
+        Prompt {
+            action,
+            title: title.to_string(),
+            value: value.to_string(),
+            history: Vec::new(),
+            history_position: None,
+            draft: String::new(),
+        }
+    }
+
+    pub fn with_history(mut self, history: &[String]) -> Prompt {
+        self.history = history.to_vec();
+        self
+    }
+
+    pub fn handle_key(&mut self, key: Key) -> PromptResult {
+        match key {
+            Key::Esc => return PromptResult::Cancelled,
+            Key::Char('\n') => return PromptResult::Submitted(self.value.clone()),
+            Key::Char('\t') => return PromptResult::Complete,
+            Key::Backspace => {
+                self.value.pop();
+            }
+            Key::Char(c) => self.value.push(c),
+            Key::Up => {
+                let position = match self.history_position {
+                    Some(position) => position.saturating_sub(1),
+                    None if self.history.is_empty() => return PromptResult::Pending,
+                    None => {
+                        self.draft = self.value.clone();
+                        self.history.len() - 1
+                    }
+                };
+                self.history_position = Some(position);
+                self.value = self.history[position].clone();
+            }
+            Key::Down => match self.history_position {
+                Some(position) if position + 1 < self.history.len() => {
+                    self.history_position = Some(position + 1);
+                    self.value = self.history[position + 1].clone();
+                }
+                Some(_) => {
+                    self.history_position = None;
+                    self.value = self.draft.clone();
+                }
+                None => {}
+            },
+            _ => {}
+        }
+
+        PromptResult::Pending
+    }
+
+    pub fn line(&self) -> String {
+        format!(" {}: {}_", self.title, self.value)
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+use udl_core::RawPayload;
+
+// Bytes of a payload shown in the raw view, the rest is only counted.
+const MAX_SHOWN_BYTES: usize = 64 * 1024;
+
+// Bytes per line of the dump.
+const LINE_BYTES: usize = 16;
+
+// The payloads the latest entries were parsed from, to see what a client sent when parsing goes wrong.
+pub struct RawPayloads {
+    // Entries to keep the payload of, the oldest ones are forgotten.
+    limit: usize,
+    // Payload of the entries received next, shared when it holds several.
+    pending: Option<Rc<RawPayload>>,
+    kept: VecDeque<(u64, Rc<RawPayload>)>,
+}
+
+impl RawPayloads {
+    pub fn new(limit: usize) -> RawPayloads {
+        RawPayloads {
+            limit,
+            pending: None,
+            kept: VecDeque::new(),
+        }
+    }
+
+    pub fn receive(&mut self, payload: RawPayload) {
+        self.pending = Some(Rc::new(payload));
+    }
+
+    // The payload received last, for the entries parsed from it.
+    pub fn pending(&self) -> Option<Rc<RawPayload>> {
+        self.pending.clone()
+    }
+
+    // Keeps the payload for the entry with the id.
+    pub fn keep(&mut self, id: u64, payload: Option<Rc<RawPayload>>) {
+        let payload = match payload {
+            Some(payload) if self.limit > 0 => payload,
+            _ => return,
+        };
+        self.kept.retain(|(kept, _)| *kept != id);
+        self.kept.push_back((id, payload));
+        while self.kept.len() > self.limit {
+            self.kept.pop_front();
+        }
+    }
+
+    pub fn get(&self, id: u64) -> Option<&RawPayload> {
+        self.kept.iter().rev().find(|(kept, _)| *kept == id).map(|(_, payload)| payload.as_ref())
+    }
+}
+
+// How the payload was framed, followed by a dump of its bytes with their offset, hex values and
+// printable characters. The terminating null byte is part of the dump.
+pub fn lines(payload: &RawPayload) -> Vec<String> {
+    let ending = if payload.terminated {
+        "ended by a null byte"
+    } else {
+        "ended by closing the connection, without a null byte"
+    };
+    let mut lines = vec![format!("From {}, {} bytes, {}", payload.client, payload.size, ending)];
+    if payload.bytes.len() < payload.size {
+        lines.push(format!("Too large, only the first {} bytes were kept", payload.bytes.len()));
+    }
+    lines.push(String::new());
+
+    let mut bytes = payload.bytes.clone();
+    if payload.terminated && bytes.len() == payload.size {
+        bytes.push(b'\0');
+    }
+    lines.extend(dump(&bytes));
+
+    lines
+}
+
+// The bytes with their offset, hex values and printable characters, 16 to a line.
+pub fn dump(bytes: &[u8]) -> Vec<String> {
+    let mut lines = vec![];
+    let shown = bytes.len().min(MAX_SHOWN_BYTES);
+    for (line, chunk) in bytes[..shown].chunks(LINE_BYTES).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let text: String = chunk
+            .iter()
+            .map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' })
+            .collect();
+        lines.push(format!("{:08x}  {:<47}  |{}|", line * LINE_BYTES, hex.join(" "), text));
+    }
+    if bytes.len() > shown {
+        lines.push(format!("… {} more bytes", bytes.len() - shown));
+    }
+
+    lines
+}
@@ -0,0 +1,121 @@
+use crate::regex::Regex;
+use crate::DebugEntry;
+use serde::Deserialize;
+use serde_json::Value;
+
+// Replaces the masked values and matches.
+const MASK: &str = "[redacted]";
+
+// Texts longer than this are masked whole instead of searched for the patterns, which takes too
+// long for the payloads clients can send.
+const MAX_TEXT_LENGTH: usize = 100_000;
+
+// Masks sensitive values of the entries as they are received, before they are shown, stored,
+// exported or sent anywhere, configured as `redact` in the config file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Redaction {
+    // The values of keys containing one of these, ignoring case, are masked at any depth of the data.
+    pub fields: Vec<String>,
+    // Regular expressions, see `regex.rs`, whose matches in the label and texts of the data are masked.
+    pub patterns: Vec<String>,
+    #[serde(skip)]
+    compiled: Vec<Regex>,
+}
+
+impl Redaction {
+    // Parses the patterns, done once when the config is loaded.
+    pub fn compile(&mut self) -> Result<(), String> {
+        self.compiled = self.patterns.iter().map(|pattern| Regex::new(pattern)).collect::<Result<_, _>>()?;
+        self.fields = self.fields.iter().map(|field| field.to_lowercase()).collect();
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.patterns.is_empty()
+    }
+
+    pub fn apply(&self, entry: &mut DebugEntry) {
+        if self.is_empty() {
+            return;
+        }
+        if !self.compiled.is_empty() {
+            entry.label = self.text(&entry.label).into();
+        }
+        for (key, value) in entry.data.iter_mut() {
+            match self.is_sensitive(key) {
+                true => *value = Value::String(String::from(MASK)),
+                false => self.value(value),
+            }
+        }
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        let key = key.to_lowercase();
+        self.fields.iter().any(|field| key.contains(field.as_str()))
+    }
+
+    fn value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => *text = self.text(text),
+            Value::Array(items) => items.iter_mut().for_each(|item| self.value(item)),
+            Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    match self.is_sensitive(key) {
+                        true => *value = Value::String(String::from(MASK)),
+                        false => self.value(value),
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The text with the matches of the patterns masked, like the payload of a parse error.
+    pub fn text(&self, text: &str) -> String {
+        if self.compiled.is_empty() {
+            return text.to_string();
+        }
+        let mut chars: Vec<char> = text.chars().collect();
+        if chars.len() > MAX_TEXT_LENGTH {
+            return String::from(MASK);
+        }
+        for pattern in &self.compiled {
+            // From the end, so the char indexes of the earlier matches stay valid.
+            for (start, end) in pattern.find_all(&chars.iter().collect::<String>()).into_iter().rev() {
+                chars.splice(start..end, MASK.chars());
+            }
+        }
+
+        chars.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Redaction;
+
+    fn redaction(patterns: &[&str]) -> Redaction {
+        let mut redaction = Redaction {
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            ..Redaction::default()
+        };
+        redaction.compile().unwrap();
+        redaction
+    }
+
+    #[test]
+    fn masks_matches() {
+        let redaction = redaction(&[r"\d{4} \d{4}"]);
+        assert_eq!(redaction.text("card 1234 5678 paid"), "card [redacted] paid");
+    }
+
+    #[test]
+    fn long_texts() {
+        let redaction = redaction(&["[A-Za-z0-9]{20,}"]);
+        let text = format!("token {} end", "a".repeat(50_000));
+        assert_eq!(redaction.text(&text), "token [redacted] end");
+        assert_eq!(redaction.text(&"a".repeat(200_000)), "[redacted]");
+    }
+}
@@ -0,0 +1,387 @@
+use std::collections::HashSet;
+
+// A small backtracking regular expression matcher for the highlight rules. Supports literals, `.`,
+// classes like `[a-z_]` and `[^0-9]`, the escapes `\d`, `\w`, `\s` and their negations, anchors `^`
+// and `$`, groups with alternatives `(a|b)`, and the quantifiers `*`, `+`, `?` and `{n,m}`. A
+// leading `(?i)` makes it case insensitive.
+#[derive(Debug, Clone)]
+pub struct Regex {
+    nodes: Vec<Node>,
+    ignore_case: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+    Repeat(Box<Node>, usize, usize),
+}
+
+#[derive(Debug, Clone)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match *self {
+            ClassItem::Range(from, to) => from <= c && c <= to,
+            ClassItem::Digit(negated) => c.is_ascii_digit() != negated,
+            ClassItem::Word(negated) => (c.is_alphanumeric() || c == '_') != negated,
+            ClassItem::Space(negated) => c.is_whitespace() != negated,
+        }
+    }
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, String> {
+        let (pattern, ignore_case) = match pattern.strip_prefix("(?i)") {
+            Some(pattern) => (pattern, true),
+            None => (pattern, false),
+        };
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut parser = Parser { chars, position: 0 };
+        let alternatives = parser.alternatives()?;
+        if parser.position < parser.chars.len() {
+            return Err(format!("Unmatched ) at position {} in {}", parser.position + 1, pattern));
+        }
+
+        Ok(Regex {
+            nodes: vec![Node::Group(alternatives)],
+            ignore_case,
+        })
+    }
+
+    // Start and end, as char indexes, of the matches that do not overlap.
+    pub fn find_all(&self, text: &str) -> Vec<(usize, usize)> {
+        let chars: Vec<char> = match self.ignore_case {
+            true => text.chars().flat_map(char::to_lowercase).collect(),
+            false => text.chars().collect(),
+        };
+        // Lowercasing can change the number of chars, fall back to not matching those texts.
+        if chars.len() != text.chars().count() {
+            return vec![];
+        }
+
+        let mut matches = vec![];
+        let mut start = 0;
+        while start <= chars.len() {
+            match self.match_at(&chars, start) {
+                Some(end) if end > start => {
+                    matches.push((start, end));
+                    start = end;
+                }
+                _ => start += 1,
+            }
+        }
+
+        matches
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        !self.find_all(text).is_empty()
+    }
+
+    // The end of the longest match the backtracking finds first, starting at the position.
+    fn match_at(&self, chars: &[char], start: usize) -> Option<usize> {
+        let mut end = None;
+        let matcher = Matcher {
+            chars,
+            ignore_case: self.ignore_case,
+        };
+        matcher.sequence(&self.nodes, start, &mut |position| {
+            end = Some(position);
+            true
+        });
+
+        end
+    }
+}
+
+struct Matcher<'a> {
+    chars: &'a [char],
+    ignore_case: bool,
+}
+
+impl Matcher<'_> {
+    // Matches the nodes from the position, calling `then` with the end of each way they match
+    // until it accepts one.
+    fn sequence(&self, nodes: &[Node], position: usize, then: &mut dyn FnMut(usize) -> bool) -> bool {
+        let (node, rest) = match nodes.split_first() {
+            Some(split) => split,
+            None => return then(position),
+        };
+
+        match node {
+            Node::Start => position == 0 && self.sequence(rest, position, then),
+            Node::End => position == self.chars.len() && self.sequence(rest, position, then),
+            Node::Group(alternatives) => alternatives.iter().any(|alternative| {
+                self.sequence(alternative, position, &mut |end| self.sequence(rest, end, then))
+            }),
+            Node::Repeat(node, min, max) => self.repeat(node, *min, *max, position, &mut |end| self.sequence(rest, end, then)),
+            node => match self.chars.get(position) {
+                Some(c) if self.matches_char(node, *c) => self.sequence(rest, position + 1, then),
+                _ => false,
+            },
+        }
+    }
+
+    // Greedily matches the node as often as allowed, giving back one at a time. Loops over the
+    // positions each number of repetitions reaches instead of recursing per repetition, so long
+    // texts do not overflow the stack.
+    fn repeat(&self, node: &Node, min: usize, max: usize, position: usize, then: &mut dyn FnMut(usize) -> bool) -> bool {
+        // A single char matches in one way only, the positions are the chars it matches in a row.
+        if let Node::Char(_) | Node::Any | Node::Class(..) = node {
+            let count = self.chars[position..]
+                .iter()
+                .take(max)
+                .take_while(|c| self.matches_char(node, **c))
+                .count();
+            return count >= min && (min..=count).rev().any(|count| then(position + count));
+        }
+
+        // The positions reached after as many repetitions as the index.
+        let mut counts = vec![vec![position]];
+        // Without a maximum, a position reached again after at least `min` repetitions continues the
+        // same way as before.
+        let mut reached = HashSet::new();
+        while counts.len() <= max {
+            let count = counts.len();
+            let mut ends = vec![];
+            let mut found = HashSet::new();
+            for &start in &counts[count - 1] {
+                self.sequence(std::slice::from_ref(node), start, &mut |end| {
+                    // Stop repeating what matches nothing.
+                    if end > start && found.insert(end) {
+                        ends.push(end);
+                    }
+                    false
+                });
+            }
+            if max == usize::MAX && count >= min {
+                ends.retain(|end| reached.insert(*end));
+            }
+            if ends.is_empty() {
+                break;
+            }
+            counts.push(ends);
+        }
+
+        let mut tried = HashSet::new();
+        counts.iter().skip(min).rev().any(|ends| ends.iter().any(|end| tried.insert(*end) && then(*end)))
+    }
+
+    fn matches_char(&self, node: &Node, c: char) -> bool {
+        match node {
+            Node::Char(expected) if self.ignore_case => expected.to_lowercase().eq(std::iter::once(c)),
+            Node::Char(expected) => *expected == c,
+            Node::Any => c != '\n',
+            Node::Class(items, negated) => {
+                let matched = items.iter().any(|item| item.matches(c))
+                    || (self.ignore_case && items.iter().any(|item| c.to_uppercase().any(|upper| item.matches(upper))));
+                matched != *negated
+            }
+            _ => false,
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Result<char, String> {
+        let c = self.peek().ok_or("Unexpected end of the pattern")?;
+        self.position += 1;
+        Ok(c)
+    }
+
+    fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, String> {
+        let mut alternatives = vec![self.sequence()?];
+        while self.peek() == Some('|') {
+            self.position += 1;
+            alternatives.push(self.sequence()?);
+        }
+
+        Ok(alternatives)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, String> {
+        let mut nodes = vec![];
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.atom()?;
+            nodes.push(self.quantified(atom)?);
+        }
+
+        Ok(nodes)
+    }
+
+    fn atom(&mut self) -> Result<Node, String> {
+        Ok(match self.next()? {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '(' => {
+                let alternatives = self.alternatives()?;
+                if self.next().ok() != Some(')') {
+                    return Err(String::from("Missing ) in the pattern"));
+                }
+                Node::Group(alternatives)
+            }
+            '[' => self.class()?,
+            '\\' => match self.escape()? {
+                ClassItem::Range(c, _) => Node::Char(c),
+                item => Node::Class(vec![item], false),
+            },
+            c @ ('*' | '+' | '?' | '{') => return Err(format!("Nothing to repeat before {}", c)),
+            c => Node::Char(c),
+        })
+    }
+
+    fn escape(&mut self) -> Result<ClassItem, String> {
+        Ok(match self.next()? {
+            'd' => ClassItem::Digit(false),
+            'D' => ClassItem::Digit(true),
+            'w' => ClassItem::Word(false),
+            'W' => ClassItem::Word(true),
+            's' => ClassItem::Space(false),
+            'S' => ClassItem::Space(true),
+            'n' => ClassItem::Range('\n', '\n'),
+            't' => ClassItem::Range('\t', '\t'),
+            c => ClassItem::Range(c, c),
+        })
+    }
+
+    fn class(&mut self) -> Result<Node, String> {
+        let negated = self.peek() == Some('^');
+        if negated {
+            self.position += 1;
+        }
+
+        let mut items = vec![];
+        loop {
+            let item = match self.next().map_err(|_| String::from("Missing ] in the pattern"))? {
+                ']' if !items.is_empty() => break,
+                '\\' => self.escape()?,
+                c => ClassItem::Range(c, c),
+            };
+            // A range like `a-z`, a `-` at the end is literal.
+            match (item, self.peek(), self.chars.get(self.position + 1)) {
+                (ClassItem::Range(from, _), Some('-'), Some(to)) if *to != ']' => {
+                    let to = *to;
+                    self.position += 2;
+                    items.push(ClassItem::Range(from, to));
+                }
+                (item, _, _) => items.push(item),
+            }
+        }
+
+        Ok(Node::Class(items, negated))
+    }
+
+    fn quantified(&mut self, atom: Node) -> Result<Node, String> {
+        let (min, max) = match self.peek() {
+            Some('*') => (0, usize::MAX),
+            Some('+') => (1, usize::MAX),
+            Some('?') => (0, 1),
+            Some('{') => return self.counted(atom),
+            _ => return Ok(atom),
+        };
+        self.position += 1;
+
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    // `{n}`, `{n,}` or `{n,m}`.
+    fn counted(&mut self, atom: Node) -> Result<Node, String> {
+        let end = self.chars[self.position..]
+            .iter()
+            .position(|c| *c == '}')
+            .ok_or("Missing } in the pattern")?;
+        let counts: String = self.chars[self.position + 1..self.position + end].iter().collect();
+        self.position += end + 1;
+
+        let invalid = || format!("Invalid repetition {{{}}} in the pattern", counts);
+        let number = |text: &str| text.trim().parse::<usize>().map_err(|_| invalid());
+        let (min, max) = match counts.split_once(',') {
+            Some((min, max)) if max.trim().is_empty() => (number(min)?, usize::MAX),
+            Some((min, max)) => (number(min)?, number(max)?),
+            None => (number(&counts)?, number(&counts)?),
+        };
+        if min > max {
+            return Err(invalid());
+        }
+
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Regex;
+
+    fn find_all(pattern: &str, text: &str) -> Vec<(usize, usize)> {
+        Regex::new(pattern).unwrap().find_all(text)
+    }
+
+    #[test]
+    fn anchors() {
+        assert_eq!(find_all("^ab", "abab"), vec![(0, 2)]);
+        assert_eq!(find_all("ab$", "abab"), vec![(2, 4)]);
+        assert_eq!(find_all("^ab$", "abab"), vec![]);
+        assert!(Regex::new("^$").unwrap().find_all("").is_empty());
+    }
+
+    #[test]
+    fn classes() {
+        assert_eq!(find_all("[a-c]+", "xabcx"), vec![(1, 4)]);
+        assert_eq!(find_all("[^0-9]+", "12ab3"), vec![(2, 4)]);
+        assert_eq!(find_all(r"\d+", "a12b345"), vec![(1, 3), (4, 7)]);
+        assert_eq!(find_all(r"\w+\s\w+", "foo bar"), vec![(0, 7)]);
+        assert_eq!(find_all("(?i)[a-z]+", "ABC1"), vec![(0, 3)]);
+        assert_eq!(find_all("[a-]+", "a-b"), vec![(0, 2)]);
+    }
+
+    #[test]
+    fn counted_repetitions() {
+        assert_eq!(find_all("a{2}", "aaaaa"), vec![(0, 2), (2, 4)]);
+        assert_eq!(find_all("a{2,}", "a aaaa"), vec![(2, 6)]);
+        assert_eq!(find_all("a{1,3}", "aaaaa"), vec![(0, 3), (3, 5)]);
+        assert_eq!(find_all("x{0,2}y", "xxxy"), vec![(1, 4)]);
+        assert!(Regex::new("a{3,1}").is_err());
+        assert!(Regex::new("a{x}").is_err());
+    }
+
+    #[test]
+    fn backtracking() {
+        assert_eq!(find_all("a+ab", "aaab"), vec![(0, 4)]);
+        assert_eq!(find_all("(a|ab)+c", "abc"), vec![(0, 3)]);
+        assert_eq!(find_all("(ab|a)*b", "ababb"), vec![(0, 5)]);
+        assert_eq!(find_all(".*x", "abxcdx"), vec![(0, 6)]);
+        assert_eq!(find_all("(a*)*b", "aab"), vec![(0, 3)]);
+    }
+
+    #[test]
+    fn long_inputs() {
+        let text = "a".repeat(200_000);
+        assert_eq!(find_all("[A-Za-z0-9]{20,}", &text), vec![(0, 200_000)]);
+        assert_eq!(find_all("a*b", &text[..5_000]), vec![]);
+        assert_eq!(find_all("(a|aa)+$", &text), vec![(0, 200_000)]);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::DebugEntry;
+use serde_json::Value;
+use udl_core::{base64, vardumper};
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+
+// Renders the data of the entries it matches in the details pane. To add one, implement it in its
+// own module and register it in `Renderers::new`.
+pub trait Renderer: Send + Sync {
+    // Shown in the title of the details pane.
+    fn name(&self) -> &str;
+    fn matches(&self, entry: &DebugEntry) -> bool;
+    fn render(&self, entry: &DebugEntry) -> Vec<Spans<'static>>;
+}
+
+// The registered renderers, the first one matching an entry is used.
+pub struct Renderers {
+    renderers: Vec<Box<dyn Renderer>>,
+}
+
+impl Renderers {
+    pub fn new() -> Renderers {
+        let mut renderers = Renderers { renderers: vec![] };
+        renderers.register(Box::new(ParseErrorRenderer));
+        renderers.register(Box::new(VarDumperRenderer));
+        renderers.register(Box::new(BinaryRenderer));
+        renderers
+    }
+
+    pub fn register(&mut self, renderer: Box<dyn Renderer>) {
+        self.renderers.push(renderer);
+    }
+
+    pub fn find(&self, entry: &DebugEntry) -> Option<&dyn Renderer> {
+        self.renderers
+            .iter()
+            .find(|renderer| renderer.matches(entry))
+            .map(|renderer| renderer.as_ref())
+    }
+}
+
+impl Default for Renderers {
+    fn default() -> Renderers {
+        Renderers::new()
+    }
+}
+
+// Shows the error and the raw payload of entries for payloads that could not be parsed.
+struct ParseErrorRenderer;
+
+impl Renderer for ParseErrorRenderer {
+    fn name(&self) -> &str {
+        "parse error"
+    }
+
+    fn matches(&self, entry: &DebugEntry) -> bool {
+        entry.data.get("parse_error").is_some_and(Value::is_object)
+    }
+
+    fn render(&self, entry: &DebugEntry) -> Vec<Spans<'static>> {
+        let details = &entry.data["parse_error"];
+        let text = |key: &str| match &details[key] {
+            Value::String(text) => text.to_string(),
+            value => value.to_string(),
+        };
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+
+        let mut lines = vec![
+            Spans::from(vec![Span::styled("Client: ", bold), Span::raw(text("client"))]),
+            Spans::from(vec![Span::styled("Count: ", bold), Span::raw(text("count"))]),
+            Spans::from(vec![
+                Span::styled("Error: ", bold),
+                Span::styled(text("error"), Style::default().fg(Color::Red)),
+            ]),
+            Spans::from(String::new()),
+            Spans::from(Span::styled("Payload:", bold)),
+        ];
+        lines.extend(text("payload").lines().map(|line| Spans::from(line.to_string())));
+
+        lines
+    }
+}
+
+// Shows Symfony VarDumper dumps with the colors of their classes, keeping the type and class notes.
+struct VarDumperRenderer;
+
+impl Renderer for VarDumperRenderer {
+    fn name(&self) -> &str {
+        "vardumper"
+    }
+
+    fn matches(&self, entry: &DebugEntry) -> bool {
+        entry.data.get("vardumper").and_then(|dump| dump.get("html")).is_some_and(Value::is_string)
+    }
+
+    fn render(&self, entry: &DebugEntry) -> Vec<Spans<'static>> {
+        let html = entry.data["vardumper"]["html"].as_str().unwrap_or_default();
+        let mut lines: Vec<Vec<Span<'static>>> = vec![vec![]];
+        for (class, text) in vardumper::segments(html) {
+            let style = dump_style(class.as_deref());
+            for (index, part) in text.split('\n').enumerate() {
+                if index > 0 {
+                    lines.push(vec![]);
+                }
+                if !part.is_empty() {
+                    lines.last_mut().unwrap().push(Span::styled(part.to_string(), style));
+                }
+            }
+        }
+
+        lines.into_iter().map(Spans::from).collect()
+    }
+}
+
+fn dump_style(class: Option<&str>) -> Style {
+    let style = Style::default();
+    match class.unwrap_or_default().trim_start_matches("sf-dump-") {
+        "str" => style.fg(Color::Green),
+        "num" => style.fg(Color::Blue),
+        "const" => style.fg(Color::Magenta).add_modifier(Modifier::BOLD),
+        "note" => style.fg(Color::Yellow),
+        "key" | "index" => style.fg(Color::Cyan),
+        "public" | "protected" | "private" | "meta" => style.fg(Color::Gray),
+        "ref" | "ellipsis" => style.fg(Color::DarkGray),
+        _ => style,
+    }
+}
+
+// Shows values sent as `{"$binary": "<base64>"}` as hex and ASCII, sixteen bytes per line. An
+// optional `mime` is shown along with the size.
+struct BinaryRenderer;
+
+impl Renderer for BinaryRenderer {
+    fn name(&self) -> &str {
+        "binary"
+    }
+
+    fn matches(&self, entry: &DebugEntry) -> bool {
+        entry.data.values().any(has_binary)
+    }
+
+    fn render(&self, entry: &DebugEntry) -> Vec<Spans<'static>> {
+        let mut values = vec![];
+        for (key, value) in entry.data.iter() {
+            find_binary(key.to_string(), value, &mut values);
+        }
+        values.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let dim = Style::default().fg(Color::DarkGray);
+        let mut lines = vec![];
+        for (path, binary) in values {
+            let bytes = match binary["$binary"].as_str().and_then(base64::decode) {
+                Some(bytes) => bytes,
+                None => {
+                    lines.push(Spans::from(Span::styled(format!("{}: invalid base64", path), bold)));
+                    lines.push(Spans::from(String::new()));
+                    continue;
+                }
+            };
+            let mime = binary.get("mime").and_then(Value::as_str).map(|mime| format!(", {}", mime));
+            lines.push(Spans::from(Span::styled(
+                format!("{} ({} bytes{})", path, bytes.len(), mime.unwrap_or_default()),
+                bold,
+            )));
+
+            for (index, chunk) in bytes.chunks(16).enumerate() {
+                let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+                let ascii: String = chunk
+                    .iter()
+                    .map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' })
+                    .collect();
+                lines.push(Spans::from(vec![
+                    Span::styled(format!("{:08x}  ", index * 16), dim),
+                    Span::raw(format!("{:<48}", hex.join(" "))),
+                    Span::styled(format!(" |{}|", ascii), Style::default().fg(Color::Cyan)),
+                ]));
+            }
+            lines.push(Spans::from(String::new()));
+        }
+
+        lines
+    }
+}
+
+fn is_binary(value: &Value) -> bool {
+    value.get("$binary").is_some_and(Value::is_string)
+}
+
+fn has_binary(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => is_binary(value) || map.values().any(has_binary),
+        Value::Array(items) => items.iter().any(has_binary),
+        _ => false,
+    }
+}
+
+// Collects the binary values with their path, like `upload.file` or `images.0`.
+fn find_binary<'a>(path: String, value: &'a Value, found: &mut Vec<(String, &'a Value)>) {
+    match value {
+        _ if is_binary(value) => found.push((path, value)),
+        Value::Object(map) => {
+            for (key, value) in map.iter() {
+                find_binary(format!("{}.{}", path, key), value, found);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                find_binary(format!("{}.{}", path, index), value, found);
+            }
+        }
+        _ => {}
+    }
+}
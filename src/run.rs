@@ -0,0 +1,55 @@
+use crate::DebugEntry;
+use serde_json::Value;
+
+// What to do with the entries so far when a client starts a new run, set with `--on-new-run`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnNewRun {
+    Clear,
+    // Add a marker entry before the first entry of the run.
+    Separate,
+}
+
+impl OnNewRun {
+    pub fn named(name: &str) -> Option<OnNewRun> {
+        match name {
+            "clear" => Some(OnNewRun::Clear),
+            "separate" => Some(OnNewRun::Separate),
+            _ => None,
+        }
+    }
+}
+
+// Finds where a run starts: an entry with `session_start` in its data, or with another
+// `request_id` than the entry before it that had one.
+pub struct Runs {
+    pub on_new_run: OnNewRun,
+    request_id: Option<Value>,
+}
+
+impl Runs {
+    pub fn new(on_new_run: OnNewRun) -> Runs {
+        Runs {
+            on_new_run,
+            request_id: None,
+        }
+    }
+
+    // The name of the run the entry starts, if it starts one.
+    pub fn starts_run(&mut self, entry: &DebugEntry) -> Option<String> {
+        let request_id = entry.data.get("request_id").filter(|id| !id.is_null());
+        let new_request = request_id.is_some() && self.request_id.as_ref() != request_id;
+        if new_request {
+            self.request_id = request_id.cloned();
+        }
+
+        match entry.data.get("session_start") {
+            Some(Value::String(name)) => return Some(name.clone()),
+            Some(Value::Null) | Some(Value::Bool(false)) | None => {}
+            Some(_) => return Some(String::from("session")),
+        }
+        match request_id.filter(|_| new_request)? {
+            Value::String(id) => Some(format!("request {}", id)),
+            id => Some(format!("request {}", id)),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use crate::DebugEntry;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+// A user script transforming incoming entries, started once with `--script`. Every entry is
+// written to its stdin as a line of json, and the script answers with a line holding the entry
+// to add, changed or not, or `null` to drop it. The script can rename labels, redact data, add
+// fields or set the `color` to highlight entries, in any language.
+pub struct Script {
+    // Kept so the script is not left running without its stdin.
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Script {
+    pub fn spawn(command: &str) -> io::Result<Script> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| io::Error::other("No stdin for the script"))?;
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("No stdout for the script"))?;
+
+        Ok(Script {
+            _child: child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    // Returns the entry as changed by the script, or `None` when the script dropped it.
+    pub fn transform(&mut self, entry: &DebugEntry) -> io::Result<Option<DebugEntry>> {
+        writeln!(self.stdin, "{}", serde_json::to_string(entry)?)?;
+        self.stdin.flush()?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The script stopped"));
+        }
+
+        serde_json::from_str::<Option<DebugEntry>>(&line)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid script output: {}", error)))
+    }
+}
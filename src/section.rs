@@ -0,0 +1,77 @@
+use crate::DebugEntry;
+use serde_json::Value;
+use std::collections::HashSet;
+
+// Between the names of nested groups in a section.
+const SEPARATOR: &str = " › ";
+
+// Groups started and ended by clients with `group_start` and `group_end` in the data, like
+// `{"group_start": "Handling POST /checkout"}`. The entries sent in between get the group as their
+// section, nested groups joined with `›`, and the group can be folded with `z`.
+#[derive(Default)]
+pub struct Sections {
+    // Names of the groups started and not ended yet, outermost first.
+    open: Vec<String>,
+    // Folded sections, showing only the entry starting the group.
+    folded: HashSet<String>,
+}
+
+fn name(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.to_string(),
+        value => value.to_string(),
+    }
+}
+
+pub fn is_group_start(entry: &DebugEntry) -> bool {
+    entry.data.contains_key("group_start")
+}
+
+impl Sections {
+    // Puts the entry in the innermost open group, unless it has a section of its own. Entries
+    // starting a group are its first entry, entries ending one its last.
+    pub fn place(&mut self, entry: &mut DebugEntry) {
+        if let Some(started) = entry.data.get("group_start").map(name) {
+            self.open.push(started);
+        }
+        // Ending an outer group by its name ends the groups within it as well.
+        let ended = match entry.data.get("group_end") {
+            Some(Value::String(ended)) if self.open.contains(ended) => self.open.iter().rposition(|open| open == ended),
+            Some(_) => self.open.len().checked_sub(1),
+            None => None,
+        };
+        let depth = ended.map_or(self.open.len(), |position| position + 1);
+        if entry.section.is_none() && depth > 0 {
+            entry.section = Some(self.open[..depth].join(SEPARATOR));
+        }
+        if let Some(position) = ended {
+            self.open.truncate(position);
+        }
+    }
+
+    pub fn is_folded(&self, section: &str) -> bool {
+        self.folded.contains(section)
+    }
+
+    // Folds the section or unfolds it, returning whether it is folded now.
+    pub fn toggle(&mut self, section: &str) -> bool {
+        if !self.folded.remove(section) {
+            self.folded.insert(section.to_string());
+        }
+
+        self.is_folded(section)
+    }
+
+    // Whether the entry is hidden by a folded section, which is the case for all the entries of the
+    // section and the ones nested in it, except the entry starting the folded group.
+    pub fn hides(&self, entry: &DebugEntry) -> bool {
+        let section = match &entry.section {
+            Some(section) if !self.folded.is_empty() => section,
+            _ => return false,
+        };
+
+        self.folded.iter().any(|folded| {
+            (section == folded && !is_group_start(entry)) || section.starts_with(&format!("{}{}", folded, SEPARATOR))
+        })
+    }
+}
@@ -0,0 +1,100 @@
+use crate::cli::Cli;
+use crate::DebugEntry;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{self, BufRead, Read, Write};
+use std::net::TcpStream;
+use udl_core::timestamp;
+
+// Labels taken from the text are cut off after this many characters.
+const MAX_LABEL_LENGTH: usize = 100;
+
+// Sends the arguments, or what is read from stdin, as an entry to a running udl, for shell scripts
+// and `tmux pipe-pane`: `echo '{"x":1}' | udl send --label build`. A json object is sent as the data
+// of the entry, anything else as its `value`. With `lines` every line of stdin is sent as it is read.
+pub fn run(cli: &Cli, arguments: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut stream = TcpStream::connect(cli.address())
+        .map_err(|error| format!("Could not connect to udl on {}: {}", cli.address(), error))?;
+    write_payload(&mut stream, &json!({"hello": {"app": "udl send"}}))?;
+
+    if !arguments.is_empty() {
+        return send(&mut stream, &arguments.join(" "), cli.label.as_deref());
+    }
+    if cli.lines {
+        for line in io::stdin().lock().lines() {
+            send(&mut stream, &line?, cli.label.as_deref())?;
+        }
+        return Ok(());
+    }
+    let mut text = String::new();
+    io::stdin().read_to_string(&mut text)?;
+
+    send(&mut stream, &text, cli.label.as_deref())
+}
+
+fn send(stream: &mut TcpStream, text: &str, label: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let text = strip_escapes(text);
+    let text = text.trim_end();
+    if text.trim().is_empty() {
+        return Ok(());
+    }
+
+    let data = match serde_json::from_str(text) {
+        Ok(Value::Object(object)) => object.into_iter().collect(),
+        Ok(value) => HashMap::from([(String::from("value"), value)]),
+        Err(_) => HashMap::from([(String::from("value"), Value::String(text.to_string()))]),
+    };
+    let entry = DebugEntry {
+        label: label
+            .map_or_else(|| text.lines().next().unwrap_or("").trim().chars().take(MAX_LABEL_LENGTH).collect(), str::to_string)
+            .into(),
+        time: timestamp::now(),
+        data,
+        ..DebugEntry::default()
+    };
+
+    write_payload(stream, &serde_json::to_value(&entry)?)?;
+
+    Ok(())
+}
+
+fn write_payload(stream: &mut TcpStream, payload: &Value) -> io::Result<()> {
+    stream.write_all(payload.to_string().as_bytes())?;
+    stream.write_all(b"\0")?;
+    stream.flush()
+}
+
+// The text without the escape sequences of terminals, like the colors in the output of a pane, and
+// without carriage returns.
+fn strip_escapes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '\x1b' => match chars.next() {
+                // Control sequences end with a char from `@` to `~`.
+                Some('[') => {
+                    for char in chars.by_ref() {
+                        if ('@'..='~').contains(&char) {
+                            break;
+                        }
+                    }
+                }
+                // Operating system commands, like titles, end with a bell or `ESC \`.
+                Some(']') => {
+                    while let Some(char) = chars.next() {
+                        if char == '\x07' || (char == '\x1b' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {}
+            char => stripped.push(char),
+        }
+    }
+
+    stripped
+}
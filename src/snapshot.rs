@@ -0,0 +1,55 @@
+use crate::hyperlink::sgr;
+use std::fs;
+use std::io;
+use std::path::Path;
+use tui::buffer::{Buffer, Cell};
+use tui::layout::Rect;
+use tui::widgets::Widget;
+
+// Part of the screen written by a snapshot, `s` for the details and `S` for the whole screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    Details,
+    Screen,
+}
+
+// Copies the rendered cells of the area, line by line. Rendered last, so it sees the frame as drawn.
+pub struct Snapshot<'a> {
+    pub lines: &'a mut Vec<Vec<Cell>>,
+}
+
+impl<'a> Widget for Snapshot<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            self.lines.push((area.left()..area.right()).map(|x| buf.get(x, y).clone()).collect());
+        }
+    }
+}
+
+// Writes the lines as plain text, or with their colors as ANSI escape sequences when the file ends in
+// `.ans` or `.ansi`, to show them with `cat` or paste them where colors are kept.
+pub fn write(path: &Path, lines: &[Vec<Cell>]) -> io::Result<()> {
+    let colored = matches!(path.extension().and_then(|extension| extension.to_str()), Some("ans") | Some("ansi"));
+    let mut text = String::new();
+    for line in lines {
+        // Spaces at the end of a line are left out.
+        let length = line.iter().rposition(|cell| cell.symbol.trim() != "").map_or(0, |last| last + 1);
+        let mut previous: Option<String> = None;
+        for cell in &line[..length] {
+            if colored {
+                let style = sgr(cell);
+                if previous.as_ref() != Some(&style) {
+                    text.push_str(&format!("\x1b[0;{}m", style));
+                    previous = Some(style);
+                }
+            }
+            text.push_str(&cell.symbol);
+        }
+        if colored && previous.is_some() {
+            text.push_str("\x1b[0m");
+        }
+        text.push('\n');
+    }
+
+    fs::write(path, text)
+}
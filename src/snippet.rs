@@ -0,0 +1,111 @@
+// Client code to paste into a project, printed by `udl snippet <language>`. Each sends an entry as
+// json followed by a null byte, with the caller as backtrace.
+
+const PHP: &str = r#"<?php
+
+function udl(string $label, $data = []): void
+{
+    $backtrace = array_map(fn ($frame) => [
+        'file' => $frame['file'] ?? '',
+        'line' => $frame['line'] ?? 0,
+        'function' => $frame['function'] ?? '',
+    ], debug_backtrace(DEBUG_BACKTRACE_IGNORE_ARGS));
+
+    $payload = json_encode([
+        'label' => $label,
+        'time' => gmdate('Y-m-d H:i:s'),
+        'data' => is_array($data) ? (object) $data : ['value' => $data],
+        'backtrace' => $backtrace,
+    ]);
+
+    $socket = @fsockopen('{host}', {port}, $errno, $errstr, 1);
+    if ($socket) {
+        fwrite($socket, $payload . "\0");
+        fclose($socket);
+    }
+}
+"#;
+
+const PYTHON: &str = r#"import json
+import socket
+import time
+import traceback
+
+
+def udl(label, data=None):
+    frames = traceback.extract_stack()[:-1]
+    payload = {
+        "label": label,
+        "time": time.strftime("%Y-%m-%d %H:%M:%S", time.gmtime()),
+        "data": data if isinstance(data, dict) else {"value": data},
+        "backtrace": [
+            {"file": frame.filename, "line": frame.lineno, "function": frame.name}
+            for frame in reversed(frames)
+        ],
+    }
+    try:
+        with socket.create_connection(("{host}", {port}), timeout=1) as connection:
+            connection.sendall(json.dumps(payload, default=repr).encode() + b"\0")
+    except OSError:
+        pass
+"#;
+
+const NODE: &str = r#"const net = require('net');
+
+function udl(label, data = {}) {
+  const backtrace = new Error().stack
+    .split('\n')
+    .slice(2)
+    .map((line) => line.match(/at (?:(.+?) \()?(.+?):(\d+):(\d+)\)?$/))
+    .filter(Boolean)
+    .map(([, fn, file, line, column]) => ({ file, line: Number(line), column: Number(column), function: fn || '' }));
+
+  const payload = JSON.stringify({
+    label,
+    time: new Date().toISOString().slice(0, 19).replace('T', ' '),
+    data: data !== null && typeof data === 'object' && !Array.isArray(data) ? data : { value: data },
+    backtrace,
+  });
+
+  const socket = net.connect({ host: '{host}', port: {port} }, () => socket.end(payload + '\0'));
+  socket.on('error', () => {});
+}
+
+module.exports = udl;
+"#;
+
+const RUBY: &str = r#"require 'json'
+require 'socket'
+
+def udl(label, data = {})
+  backtrace = caller_locations.map do |frame|
+    { file: frame.absolute_path || frame.path, line: frame.lineno, function: frame.label }
+  end
+
+  payload = {
+    label: label,
+    time: Time.now.utc.strftime('%Y-%m-%d %H:%M:%S'),
+    data: data.is_a?(Hash) ? data : { value: data },
+    backtrace: backtrace
+  }
+
+  Socket.tcp('{host}', {port}, connect_timeout: 1) { |socket| socket.write(payload.to_json + "\0") }
+rescue SystemCallError
+  nil
+end
+"#;
+
+pub const LANGUAGES: [&str; 4] = ["php", "python", "node", "ruby"];
+
+// The snippet for the language, sending to the host and port.
+pub fn for_language(language: &str, host: &str, port: u16) -> Option<String> {
+    let template = match language.to_lowercase().as_str() {
+        "php" => PHP,
+        "python" | "py" => PYTHON,
+        "node" | "javascript" | "js" => NODE,
+        "ruby" | "rb" => RUBY,
+        _ => return None,
+    };
+
+    Some(template.replace("{host}", host).replace("{port}", &port.to_string()))
+}
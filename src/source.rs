@@ -0,0 +1,23 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// Reads the lines around `line` (1 based) from a local file, returned with their line numbers.
+pub fn preview(path: &str, line: usize, context: usize) -> Option<Vec<(usize, String)>> {
+    let file = File::open(path).ok()?;
+    let first = line.saturating_sub(context).max(1);
+
+    let lines: Vec<(usize, String)> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .enumerate()
+        .map(|(index, content)| (index + 1, content))
+        .skip(first - 1)
+        .take(line + context + 1 - first)
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines)
+    }
+}
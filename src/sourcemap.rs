@@ -0,0 +1,148 @@
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+// A parsed source map (version 3), mapping positions in a generated file to the original sources.
+#[derive(Debug, Clone)]
+pub struct SourceMap {
+    sources: Vec<String>,
+    // Segments of each generated line, ordered by column.
+    lines: Vec<Vec<Segment>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    column: i64,
+    source: usize,
+    line: i64,
+}
+
+#[derive(Deserialize)]
+struct RawSourceMap {
+    #[serde(default, rename = "sourceRoot")]
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    mappings: String,
+}
+
+impl SourceMap {
+    // Parses the map, resolving the sources relative to the directory of the map.
+    pub fn parse(json: &str, directory: &Path) -> Result<SourceMap, String> {
+        let raw: RawSourceMap = serde_json::from_str(json).map_err(|error| error.to_string())?;
+        let root = raw.source_root.unwrap_or_default();
+        let sources = raw
+            .sources
+            .iter()
+            .map(|source| resolve_source(&format!("{}{}", root, source.as_deref().unwrap_or_default()), directory))
+            .collect();
+
+        // Every field but the generated column is relative to the previous segment of the whole map.
+        let (mut source, mut line) = (0i64, 0i64);
+        let mut lines = vec![];
+        for generated in raw.mappings.split(';') {
+            let mut column = 0i64;
+            let mut segments = vec![];
+            for segment in generated.split(',').filter(|segment| !segment.is_empty()) {
+                let fields = decode_vlq(segment)?;
+                column += fields[0];
+                if fields.len() >= 4 {
+                    source += fields[1];
+                    line += fields[2];
+                    segments.push(Segment {
+                        column,
+                        source: source as usize,
+                        line,
+                    });
+                }
+            }
+            lines.push(segments);
+        }
+
+        Ok(SourceMap { sources, lines })
+    }
+
+    // The original file and line of a one based generated line and column.
+    pub fn lookup(&self, line: i64, column: i64) -> Option<(String, i64)> {
+        if line < 1 {
+            return None;
+        }
+        let segments = self.lines.get(line as usize - 1)?;
+        let segment = segments.iter().rev().find(|segment| segment.column < column)?;
+        Some((self.sources.get(segment.source)?.clone(), segment.line + 1))
+    }
+}
+
+// Strips bundler prefixes like `webpack:///./` and makes relative sources relative to the map.
+fn resolve_source(source: &str, directory: &Path) -> String {
+    let source = match source.find("://") {
+        Some(index) => source[index + 3..].trim_start_matches('/'),
+        None => source,
+    };
+    let source = source.trim_start_matches("./");
+    if Path::new(source).is_absolute() {
+        return source.to_string();
+    }
+
+    directory.join(source).to_string_lossy().to_string()
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, String> {
+    let mut values = vec![];
+    let (mut value, mut shift) = (0i64, 0);
+    for c in segment.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(format!("Invalid mapping: {}", segment)),
+        } as i64;
+        value += (digit & 31) << shift;
+        if digit & 32 != 0 {
+            shift += 5;
+            continue;
+        }
+        values.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+        value = 0;
+        shift = 0;
+    }
+
+    Ok(values)
+}
+
+// Source maps read so far, read again when the file changed. Invalid maps are cached as `None` as
+// well, so they are not read on every draw.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMaps {
+    cache: RefCell<HashMap<PathBuf, CachedMap>>,
+}
+
+// The modification time of the file when it was read, and the map when it is valid.
+type CachedMap = (Option<SystemTime>, Option<SourceMap>);
+
+impl SourceMaps {
+    // Looks up the position in the map for the generated file, `<file>.map` in the directory or next
+    // to the file.
+    pub fn resolve(&self, directory: &Path, file: &str, line: i64, column: i64) -> Option<(String, i64)> {
+        let name = format!("{}.map", Path::new(file).file_name()?.to_string_lossy());
+        let path = [directory.join(&name), PathBuf::from(format!("{}.map", file))]
+            .iter()
+            .find(|path| path.exists())?
+            .clone();
+        let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok();
+
+        let mut cache = self.cache.borrow_mut();
+        if cache.get(&path).is_none_or(|(cached, _)| *cached != modified) {
+            let map = fs::read_to_string(&path)
+                .ok()
+                .and_then(|json| SourceMap::parse(&json, path.parent().unwrap_or(directory)).ok());
+            cache.insert(path.clone(), (modified, map));
+        }
+
+        cache.get(&path)?.1.as_ref()?.lookup(line, column)
+    }
+}
@@ -0,0 +1,85 @@
+use crate::DebugEntry;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use udl_core::RawPayload;
+
+pub type Held = Vec<(DebugEntry, Option<Rc<RawPayload>>)>;
+
+// Sources muted with `M` or held back with `h` while the others keep coming in, like a chatty queue
+// worker. A source is the client of an entry, like `worker (127.0.0.1)` after a hello. What they
+// sent since is counted, so nothing is lost without notice.
+#[derive(Default)]
+pub struct Sources {
+    // Entries dropped from each muted source.
+    muted: BTreeMap<String, u64>,
+    // Entries held back from each paused source with their payload, added once resumed.
+    held: BTreeMap<String, Held>,
+}
+
+// The source of the entry, entries loaded from files have none.
+pub fn of(entry: &DebugEntry) -> Option<&str> {
+    entry.arrival.as_ref().map(|arrival| arrival.client.as_str())
+}
+
+impl Sources {
+    // Mutes the source, or unmutes it returning the entries dropped meanwhile.
+    pub fn toggle_mute(&mut self, source: &str) -> Option<u64> {
+        match self.muted.remove(source) {
+            Some(dropped) => Some(dropped),
+            None => {
+                self.muted.insert(source.to_string(), 0);
+                None
+            }
+        }
+    }
+
+    // Holds back the entries of the source, or resumes it returning the entries held back meanwhile.
+    pub fn toggle_hold(&mut self, source: &str) -> Option<Held> {
+        match self.held.remove(source) {
+            Some(held) => Some(held),
+            None => {
+                self.held.insert(source.to_string(), vec![]);
+                None
+            }
+        }
+    }
+
+    // Unmutes and resumes all sources, returning the entries held back.
+    pub fn clear(&mut self) -> Held {
+        self.muted.clear();
+        let held = std::mem::take(&mut self.held);
+
+        held.into_values().flatten().collect()
+    }
+
+    // Whether the source of the entry is muted, counting the entry as dropped.
+    pub fn drops(&mut self, entry: &DebugEntry) -> bool {
+        match of(entry).and_then(|source| self.muted.get_mut(source)) {
+            Some(dropped) => {
+                *dropped += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Takes the entry when its source is held back, giving it back otherwise.
+    pub fn hold(&mut self, entry: DebugEntry, raw: Option<Rc<RawPayload>>) -> Option<(DebugEntry, Option<Rc<RawPayload>>)> {
+        match of(&entry).and_then(|source| self.held.get_mut(source)) {
+            Some(held) => {
+                held.push((entry, raw));
+                None
+            }
+            None => Some((entry, raw)),
+        }
+    }
+
+    // What the status line shows of the muted and held back sources, like
+    // `Muted worker (120 dropped)`.
+    pub fn status(&self) -> Vec<String> {
+        let muted = self.muted.iter().map(|(source, dropped)| format!("Muted {} ({} dropped)", source, dropped));
+        let held = self.held.iter().map(|(source, held)| format!("Holding {} ({} held back)", source, held.len()));
+
+        muted.chain(held).collect()
+    }
+}
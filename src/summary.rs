@@ -0,0 +1,98 @@
+use crate::kind::Kind;
+use crate::metrics::format_bytes;
+use crate::DebugEntry;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use udl_core::{timestamp, Buffer};
+
+// Labels listed, the others are counted below them.
+const SHOWN_LABELS: usize = 20;
+
+// Levels of log entries that count as errors.
+const ERROR_LEVELS: [&str; 4] = ["error", "critical", "alert", "emergency"];
+
+// A Markdown summary of the session to paste into a debugging log or stand-up note: how long udl
+// ran, the entries and errors received, the busiest minute, the largest payload and the entries per
+// label. Written with the `summary` palette command, and printed on quitting with
+// `summary_on_exit` in the config.
+pub fn render(buffer: &Buffer, started: SystemTime) -> String {
+    let items = &buffer.items;
+    let now = SystemTime::now();
+    let seconds = |time: SystemTime| time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs() as i64);
+    let duration = now.duration_since(started).map_or(0, |duration| duration.as_secs());
+
+    let mut summary = String::from("# udl session summary\n\n");
+    summary.push_str(&format!(
+        "- Session: {} to {} UTC ({})\n",
+        timestamp::format(seconds(started)),
+        &timestamp::format(seconds(now))[11..],
+        format_duration(duration)
+    ));
+
+    let errors = items.iter().filter(|item| is_error(item)).count();
+    let mut entries = format!("- Entries: {}, {}", buffer.received, plural(errors, "error", "errors"));
+    if buffer.evicted > 0 {
+        entries.push_str(&format!(" among the {} still in memory", items.len()));
+    }
+    summary.push_str(&entries);
+    summary.push('\n');
+
+    // Received times when there are, the time the client sent otherwise.
+    let mut minutes: HashMap<&str, usize> = HashMap::new();
+    for item in items {
+        let time = item.arrival.as_ref().map_or(item.time.as_str(), |arrival| arrival.time.as_str());
+        *minutes.entry(time.get(..16).unwrap_or(time)).or_insert(0) += 1;
+    }
+    if let Some((minute, count)) = minutes.into_iter().max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(a.0))) {
+        summary.push_str(&format!("- Busiest minute: {}, {}\n", minute, plural(count, "entry", "entries")));
+    }
+
+    let largest = items.iter().filter_map(|item| Some((item.arrival.as_ref()?.bytes, item))).max_by_key(|(bytes, _)| *bytes);
+    if let Some((bytes, item)) = largest {
+        summary.push_str(&format!("- Largest payload: {}, {:?} (#{})\n", format_bytes(bytes as u64), one_line(&item.label), item.id));
+    }
+
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    for item in items {
+        *labels.entry(&item.label).or_insert(0) += 1;
+    }
+    let mut labels: Vec<(&str, usize)> = labels.into_iter().collect();
+    labels.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    if !labels.is_empty() {
+        summary.push_str("\n| Entries | Label |\n| ---: | --- |\n");
+        for (label, count) in labels.iter().take(SHOWN_LABELS) {
+            summary.push_str(&format!("| {} | {} |\n", count, one_line(label).replace('|', "\\|")));
+        }
+        if labels.len() > SHOWN_LABELS {
+            summary.push_str(&format!("\nAnd {} other labels.\n", labels.len() - SHOWN_LABELS));
+        }
+    }
+
+    summary
+}
+
+// Exceptions, parse errors and log entries at an error level.
+fn is_error(entry: &DebugEntry) -> bool {
+    let is_error_level = |level: &str| ERROR_LEVELS.contains(&level.to_lowercase().as_str());
+
+    matches!(Kind::of(entry), Some(Kind::Exception) | Some(Kind::ParseError))
+        || entry.data.get("level").and_then(Value::as_str).is_some_and(is_error_level)
+        || entry.tags.iter().any(|tag| is_error_level(tag))
+}
+
+fn plural(count: usize, one: &str, more: &str) -> String {
+    format!("{} {}", count, if count == 1 { one } else { more })
+}
+
+fn one_line(label: &str) -> String {
+    label.replace('\n', " ")
+}
+
+fn format_duration(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
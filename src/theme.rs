@@ -0,0 +1,79 @@
+use tui::style::{Color, Style};
+
+// Parses a color name (`red`, `light-blue`, ...) or a `#rrggbb` hex code as sent by clients.
+pub fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim().to_lowercase();
+
+    if let Some(hex) = value.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16).ok();
+        return Some(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+    }
+
+    let color = match value.replace('_', "-").as_str() {
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" | "purple" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "dark-gray" | "dark-grey" => Color::DarkGray,
+        "light-red" => Color::LightRed,
+        "light-green" => Color::LightGreen,
+        "light-yellow" => Color::LightYellow,
+        "light-blue" => Color::LightBlue,
+        "light-magenta" => Color::LightMagenta,
+        "light-cyan" => Color::LightCyan,
+        "orange" => Color::Rgb(255, 165, 0),
+        "white" => Color::White,
+        _ => return None,
+    };
+
+    Some(color)
+}
+
+// Colors of the panels, chosen with `--theme`.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub background: Color,
+    pub foreground: Color,
+    pub header: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            background: Color::Black,
+            foreground: Color::Reset,
+            header: Color::Blue,
+        }
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Theme> {
+        match name.trim().to_lowercase().as_str() {
+            "dark" => Some(Theme::default()),
+            "light" => Some(Theme {
+                background: Color::White,
+                foreground: Color::Black,
+                header: Color::LightBlue,
+            }),
+            // Keep the colors of the terminal itself.
+            "terminal" => Some(Theme {
+                background: Color::Reset,
+                foreground: Color::Reset,
+                header: Color::Blue,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn panel(&self) -> Style {
+        Style::default().bg(self.background).fg(self.foreground)
+    }
+}
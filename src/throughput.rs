@@ -0,0 +1,139 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+// Seconds of history kept for the graph.
+const HISTORY_SECONDS: u64 = 60;
+
+// Seconds the current rates are averaged over.
+const RATE_SECONDS: u64 = 5;
+
+// What was received during a second.
+#[derive(Default)]
+struct Second {
+    // Since the throughput started being measured.
+    second: u64,
+    entries: u64,
+    bytes: u64,
+    // Entries and bytes by client address.
+    clients: HashMap<String, (u64, u64)>,
+}
+
+// Rates of a client over the last seconds.
+pub struct ClientRate {
+    pub client: String,
+    pub entries_per_second: f64,
+    pub bytes_per_second: f64,
+}
+
+// Entries and bytes received per second over the last minute, shown in the panel toggled with `T`, to
+// tell whether the app under test is sending at all or flooding udl.
+pub struct Throughput {
+    start: Instant,
+    // Oldest first, seconds without anything received are left out.
+    seconds: VecDeque<Second>,
+}
+
+impl Default for Throughput {
+    fn default() -> Throughput {
+        Throughput::new()
+    }
+}
+
+impl Throughput {
+    pub fn new() -> Throughput {
+        Throughput {
+            start: Instant::now(),
+            seconds: VecDeque::new(),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_secs()
+    }
+
+    // The counts of the current second, forgetting the seconds beyond the history.
+    fn current(&mut self) -> &mut Second {
+        let now = self.now();
+        while self.seconds.front().is_some_and(|second| second.second + HISTORY_SECONDS <= now) {
+            self.seconds.pop_front();
+        }
+        if self.seconds.back().is_none_or(|second| second.second != now) {
+            self.seconds.push_back(Second {
+                second: now,
+                ..Second::default()
+            });
+        }
+
+        self.seconds.back_mut().unwrap()
+    }
+
+    // Counts a payload received from the client.
+    pub fn payload(&mut self, client: &str, bytes: usize) {
+        let second = self.current();
+        second.bytes += bytes as u64;
+        second.clients.entry(client.to_string()).or_default().1 += bytes as u64;
+    }
+
+    // Counts an entry parsed from a payload of the client.
+    pub fn entry(&mut self, client: &str) {
+        let second = self.current();
+        second.entries += 1;
+        second.clients.entry(client.to_string()).or_default().0 += 1;
+    }
+
+    // The seconds counted in the current rates, with how many seconds they span.
+    fn recent(&self) -> (impl Iterator<Item = &Second>, f64) {
+        let now = self.now();
+        // Until measuring for a while, average over the seconds measured so far.
+        let span = (now + 1).min(RATE_SECONDS);
+        let recent = self.seconds.iter().filter(move |second| second.second + span > now);
+
+        (recent, span as f64)
+    }
+
+    // Entries and bytes per second, averaged over the last seconds.
+    pub fn rates(&self) -> (f64, f64) {
+        let (recent, span) = self.recent();
+        let (entries, bytes) = recent.fold((0, 0), |(entries, bytes), second| (entries + second.entries, bytes + second.bytes));
+
+        (entries as f64 / span, bytes as f64 / span)
+    }
+
+    // Rates of each client that sent something in the last seconds, the busiest first.
+    pub fn clients(&self) -> Vec<ClientRate> {
+        let (recent, span) = self.recent();
+        let mut totals: HashMap<&str, (u64, u64)> = HashMap::new();
+        for second in recent {
+            for (client, (entries, bytes)) in &second.clients {
+                let total = totals.entry(client.as_str()).or_default();
+                total.0 += entries;
+                total.1 += bytes;
+            }
+        }
+        let mut clients: Vec<ClientRate> = totals
+            .into_iter()
+            .map(|(client, (entries, bytes))| ClientRate {
+                client: client.to_string(),
+                entries_per_second: entries as f64 / span,
+                bytes_per_second: bytes as f64 / span,
+            })
+            .collect();
+        clients.sort_by(|a, b| b.bytes_per_second.total_cmp(&a.bytes_per_second).then_with(|| a.client.cmp(&b.client)));
+
+        clients
+    }
+
+    // Entries received in each of the last `count` seconds, oldest first, for the graph.
+    pub fn history(&self, count: usize) -> Vec<u64> {
+        let now = self.now();
+        let mut history = vec![0; count];
+        for second in &self.seconds {
+            let age = (now - second.second) as usize;
+            if age < count {
+                history[count - 1 - age] = second.entries;
+            }
+        }
+
+        history
+    }
+}
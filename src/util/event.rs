@@ -1,33 +1,56 @@
-use std::io;
-use std::sync::mpsc;
+use async_std::channel::{self, Receiver, RecvError, Sender};
+use async_std::fs::File;
+use async_std::io::{self, ReadExt};
+use async_std::task;
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
-use std::thread;
 use std::time::Duration;
 
-use termion::event::Key;
-use termion::input::TermRead;
+use crate::{DebugEntry, StatefulTable};
+use termion::event::{parse_event, Event, Key, MouseButton, MouseEvent};
+use udl_core::Received;
 
-pub enum Event<I> {
-    Input(I),
+/// Everything the main loop reacts to. The main loop owns the state, other tasks send events
+/// instead of sharing it.
+pub enum AppEvent {
+    Input(Key),
+    /// A right click at the column and row, starting at 1, when mouse reporting is enabled.
+    RightClick(u16, u16),
+    /// The terminal gained or lost focus, when focus reporting is enabled.
+    Focus(bool),
     Tick,
+    Resize,
+    /// Sent by a client.
+    Received(Received),
+    /// Pushed again by `udl replay`.
+    Replayed(Box<DebugEntry>),
+    /// Runs with the state, for the web server to read from it.
+    Query(Box<dyn FnOnce(&StatefulTable) + Send>),
+    /// The config file was changed and should be loaded again.
+    ConfigChanged,
+    /// A hook from the config finished running on an entry.
+    Hooked(crate::hook::Finished),
+    /// A background task failed, like the listener not being able to bind its address.
+    Error(String),
 }
 
 /// A small event handler that wrap termion input and tick events. Each event
-/// type is handled in its own thread and returned to a common `Receiver`
+/// type is handled in its own task and returned to a common `Receiver`,
+/// other sources can send their events through `sender`.
 pub struct Events {
-    rx: mpsc::Receiver<Event<Key>>,
-    input_handle: thread::JoinHandle<()>,
+    tx: Sender<AppEvent>,
+    rx: Receiver<AppEvent>,
     ignore_exit_key: Arc<AtomicBool>,
-    tick_handle: thread::JoinHandle<()>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct Config {
     pub exit_key: Key,
     pub tick_rate: Duration,
+    /// Read the keys from the terminal instead of stdin, for when entries are piped into stdin.
+    pub keys_from_tty: bool,
 }
 
 impl Default for Config {
@@ -35,54 +58,54 @@ impl Default for Config {
         Config {
             exit_key: Key::Char('q'),
             tick_rate: Duration::from_millis(250),
+            keys_from_tty: false,
         }
     }
 }
 
+impl Default for Events {
+    fn default() -> Events {
+        Events::new()
+    }
+}
+
 impl Events {
     pub fn new() -> Events {
         Events::with_config(Config::default())
     }
 
     pub fn with_config(config: Config) -> Events {
-        let (tx, rx) = mpsc::channel();
+        let (tx, rx) = channel::unbounded();
         let ignore_exit_key = Arc::new(AtomicBool::new(false));
-        let input_handle = {
+        task::spawn(read_keys(tx.clone(), ignore_exit_key.clone(), config.exit_key, config.keys_from_tty));
+        {
             let tx = tx.clone();
-            let ignore_exit_key = ignore_exit_key.clone();
-            thread::spawn(move || {
-                let stdin = io::stdin();
-                for evt in stdin.keys() {
-                    if let Ok(key) = evt {
-                        if let Err(err) = tx.send(Event::Input(key)) {
-                            eprintln!("{}", err);
-                            return;
-                        }
-                        if !ignore_exit_key.load(Ordering::Relaxed) && key == config.exit_key {
-                            return;
-                        }
+            task::spawn(async move {
+                let mut size = termion::terminal_size().ok();
+                loop {
+                    let current = termion::terminal_size().ok();
+                    let event = if current != size { AppEvent::Resize } else { AppEvent::Tick };
+                    size = current;
+                    if tx.send(event).await.is_err() {
+                        break;
                     }
+                    task::sleep(config.tick_rate).await;
                 }
-            })
-        };
-        let tick_handle = {
-            thread::spawn(move || loop {
-                if tx.send(Event::Tick).is_err() {
-                    break;
-                }
-                thread::sleep(config.tick_rate);
-            })
-        };
+            });
+        }
         Events {
+            tx,
             rx,
             ignore_exit_key,
-            input_handle,
-            tick_handle,
         }
     }
 
-    pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
-        self.rx.recv()
+    pub async fn next(&self) -> Result<AppEvent, RecvError> {
+        self.rx.recv().await
+    }
+
+    pub fn sender(&self) -> Sender<AppEvent> {
+        self.tx.clone()
     }
 
     pub fn disable_exit_key(&mut self) {
@@ -93,3 +116,66 @@ impl Events {
         self.ignore_exit_key.store(false, Ordering::Relaxed);
     }
 }
+
+// Reads keys from stdin or the terminal, stopping after the exit key unless it is ignored.
+async fn read_keys(tx: Sender<AppEvent>, ignore_exit_key: Arc<AtomicBool>, exit_key: Key, from_tty: bool) {
+    let mut input: Box<dyn io::Read + Unpin + Send> = match from_tty {
+        true => match File::open("/dev/tty").await {
+            Ok(tty) => Box::new(tty),
+            Err(error) => {
+                let _ = tx.send(AppEvent::Error(format!("Cannot read keys from the terminal: {}", error))).await;
+                return;
+            }
+        },
+        false => Box::new(io::stdin()),
+    };
+    let mut buffer = [0u8; 64];
+    loop {
+        let read = match input.read(&mut buffer).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => read,
+        };
+
+        let mut rest = &buffer[..read];
+        while let Some((&byte, tail)) = rest.split_first() {
+            // Focus reports are not known to termion.
+            if let Some(focused) = focus_report(rest) {
+                rest = &rest[3..];
+                if tx.send(AppEvent::Focus(focused)).await.is_err() {
+                    return;
+                }
+                continue;
+            }
+            let mut bytes = tail.iter().map(|byte| Ok(*byte));
+            let event = parse_event(byte, &mut bytes);
+            rest = &tail[tail.len() - bytes.len()..];
+            let key = match event {
+                Ok(Event::Key(key)) => key,
+                Ok(Event::Mouse(MouseEvent::Press(MouseButton::Right, x, y))) => {
+                    if tx.send(AppEvent::RightClick(x, y)).await.is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                // An escape on its own is the escape key rather than the start of a sequence.
+                Err(_) if byte == b'\x1B' => Key::Esc,
+                _ => continue,
+            };
+            if tx.send(AppEvent::Input(key)).await.is_err() {
+                return;
+            }
+            if !ignore_exit_key.load(Ordering::Relaxed) && key == exit_key {
+                return;
+            }
+        }
+    }
+}
+
+// Whether the bytes start with the terminal reporting it gained focus, `ESC [ I`, or lost it, `ESC [ O`.
+fn focus_report(bytes: &[u8]) -> Option<bool> {
+    match bytes {
+        [b'\x1B', b'[', b'I', ..] => Some(true),
+        [b'\x1B', b'[', b'O', ..] => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,95 @@
+use crate::filter;
+use crate::DebugEntry;
+use serde_json::Value;
+
+// Changes kept per watch, older ones are forgotten.
+const MAX_CHANGES: usize = 50;
+
+// A value that changed, with the entry it changed in.
+pub struct Change {
+    pub id: u64,
+    pub time: String,
+    pub value: String,
+}
+
+// A path like `data.cart.total` followed across the entries having it, keeping every change of its
+// value.
+pub struct Watch {
+    pub path: String,
+    // Oldest first.
+    pub changes: Vec<Change>,
+}
+
+impl Watch {
+    pub fn new(path: &str) -> Watch {
+        Watch {
+            path: path.trim().to_string(),
+            changes: vec![],
+        }
+    }
+
+    pub fn latest(&self) -> Option<&Change> {
+        self.changes.last()
+    }
+
+    fn observe(&mut self, entry: &DebugEntry) {
+        let value = match filter::resolve(entry, &self.path) {
+            Some(Value::String(text)) => text,
+            Some(value) => value.to_string(),
+            None => return,
+        };
+        if self.latest().is_some_and(|latest| latest.value == value) {
+            return;
+        }
+        self.changes.push(Change {
+            id: entry.id,
+            time: entry.time.clone(),
+            value,
+        });
+        if self.changes.len() > MAX_CHANGES {
+            self.changes.remove(0);
+        }
+    }
+}
+
+// The watches added with `watch <path>` or in the config, updated as entries arrive.
+#[derive(Default)]
+pub struct Watches {
+    pub watches: Vec<Watch>,
+}
+
+impl Watches {
+    pub fn is_empty(&self) -> bool {
+        self.watches.is_empty()
+    }
+
+    // Watches the path, starting from the entries so far, given newest first. Watching a path again
+    // keeps its changes.
+    pub fn add(&mut self, path: &str, items: &[DebugEntry]) {
+        let mut watch = Watch::new(path);
+        if self.watches.iter().any(|existing| existing.path == watch.path) {
+            return;
+        }
+        for item in items.iter().rev() {
+            watch.observe(item);
+        }
+        self.watches.push(watch);
+    }
+
+    // Stops watching the path, or every path without one. Returns whether something was watched.
+    pub fn remove(&mut self, path: Option<&str>) -> bool {
+        let count = self.watches.len();
+        match path {
+            Some(path) => self.watches.retain(|watch| watch.path != path.trim()),
+            None => self.watches.clear(),
+        }
+
+        self.watches.len() != count
+    }
+
+    pub fn observe(&mut self, entry: &DebugEntry) {
+        for watch in self.watches.iter_mut() {
+            watch.observe(entry);
+        }
+    }
+}
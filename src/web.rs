@@ -0,0 +1,154 @@
+use crate::filter::Filter;
+use crate::http::{self, Request};
+use crate::util::event::AppEvent;
+use crate::{timestamp, DebugEntry, StatefulTable};
+use async_std::{
+    channel::{self, Sender},
+    io,
+    net::{TcpListener, TcpStream},
+    prelude::*,
+    task,
+};
+use std::time::Duration;
+
+const PAGE: &str = include_str!("web.html");
+
+// Serves a page mirroring the entries live, for `--web`. New entries are sent to the page as
+// server-sent events. The entries in memory can also be queried as json from `/entries`.
+pub async fn serve(address: String, events: Sender<AppEvent>) -> io::Result<()> {
+    let listener = TcpListener::bind(address).await?;
+    let mut incoming = listener.incoming();
+    while let Some(stream) = incoming.next().await {
+        let stream = stream?;
+        let events = events.clone();
+        task::spawn(async move {
+            // A client going away is not an error for the server.
+            let _ = handle(stream, events).await;
+        });
+    }
+
+    Ok(())
+}
+
+// Runs the function with the state in the main loop and waits for its result.
+async fn query<T, F>(events: &Sender<AppEvent>, function: F) -> io::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce(&StatefulTable) -> T + Send + 'static,
+{
+    let (sender, receiver) = channel::bounded(1);
+    events
+        .send(AppEvent::Query(Box::new(move |table| {
+            let _ = sender.try_send(function(table));
+        })))
+        .await
+        .map_err(|_| io::Error::other("udl is stopping"))?;
+
+    receiver.recv().await.map_err(|_| io::Error::other("udl is stopping"))
+}
+
+async fn handle(stream: TcpStream, events: Sender<AppEvent>) -> io::Result<()> {
+    let request = match http::read_request(&stream).await? {
+        Some(request) if request.method == "GET" => request,
+        _ => return http::respond(&stream, "400 Bad Request", "text/plain", "Bad request").await,
+    };
+
+    match request.path.trim_end_matches('/') {
+        "" => http::respond(&stream, "200 OK", "text/html; charset=utf-8", PAGE).await,
+        "/events" => stream_events(stream, events).await,
+        "/entries" => {
+            let result = query(&events, move |table| query_entries(&request, table)).await?;
+            match result {
+                Ok(json) => http::respond(&stream, "200 OK", "application/json", &json).await,
+                Err(error) => http::respond(&stream, "400 Bad Request", "text/plain", &error).await,
+            }
+        }
+        path => {
+            let id = path.strip_prefix("/entries/").and_then(|id| id.parse().ok());
+            let entry = match id {
+                Some(id) => {
+                    query(&events, move |table| {
+                        table.buffer.get(id).and_then(|entry| serde_json::to_string(entry).ok())
+                    })
+                    .await?
+                }
+                None => None,
+            };
+            match entry {
+                Some(json) => http::respond(&stream, "200 OK", "application/json", &json).await,
+                None => http::respond(&stream, "404 Not Found", "text/plain", "Not found").await,
+            }
+        }
+    }
+}
+
+// The entries in memory matching the query, in order of arrival. `label` matches part of the
+// label, `since` is a time, `after` an id, `filter` a filter expression and `limit` keeps only the
+// newest entries.
+fn query_entries(request: &Request, table: &StatefulTable) -> Result<String, String> {
+    let label = request.param("label").map(str::to_lowercase);
+    let since = match request.param("since") {
+        Some(since) => Some(timestamp::parse(since).ok_or_else(|| format!("Invalid since: {}", since))?),
+        None => None,
+    };
+    let after: u64 = match request.param("after") {
+        Some(after) => after.parse().map_err(|_| format!("Invalid after: {}", after))?,
+        None => 0,
+    };
+    let filter = request.param("filter").map(Filter::parse).transpose()?;
+    let limit: usize = match request.param("limit") {
+        Some(limit) => limit.parse().map_err(|_| format!("Invalid limit: {}", limit))?,
+        None => usize::MAX,
+    };
+
+    let entries: Vec<&DebugEntry> = table
+        .buffer
+        .items
+        .iter()
+        .filter(|entry| entry.id > after)
+        .filter(|entry| label.as_ref().is_none_or(|label| entry.label.to_lowercase().contains(label)))
+        .filter(|entry| since.is_none_or(|since| timestamp::parse(&entry.time).is_some_and(|time| time >= since)))
+        .filter(|entry| filter.as_ref().is_none_or(|filter| filter.matches(entry)))
+        .take(limit)
+        .collect();
+
+    serde_json::to_string(&entries.into_iter().rev().collect::<Vec<_>>()).map_err(|error| error.to_string())
+}
+
+// Sends the entries in memory, and then every new one, until the client disconnects.
+async fn stream_events(mut stream: TcpStream, events: Sender<AppEvent>) -> io::Result<()> {
+    stream
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n")
+        .await?;
+
+    let mut received = 0;
+    let mut idle = 0;
+    loop {
+        let seen = received;
+        let (total, new_events) = query(&events, move |table| {
+            let buffer = &table.buffer;
+            let new = (buffer.received - seen).min(buffer.items.len());
+            let new_events: Vec<String> = buffer.items[..new]
+                .iter()
+                .rev()
+                .filter_map(|entry| serde_json::to_string(entry).ok())
+                .map(|json| format!("data: {}\n\n", json))
+                .collect();
+            (buffer.received, new_events)
+        })
+        .await?;
+        received = total;
+
+        if new_events.is_empty() {
+            // Writing a comment now and then notices when the client is gone.
+            idle += 1;
+            if idle % 60 == 0 {
+                stream.write_all(b": keep alive\n\n").await?;
+            }
+        } else {
+            idle = 0;
+            stream.write_all(new_events.concat().as_bytes()).await?;
+        }
+        task::sleep(Duration::from_millis(250)).await;
+    }
+}
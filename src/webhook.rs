@@ -0,0 +1,87 @@
+use crate::filter::Filter;
+use crate::DebugEntry;
+use serde::Deserialize;
+use serde_json::json;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+// Longest part of the data included in a message, chat services limit the size of messages.
+const MAX_DATA_LENGTH: usize = 1000;
+
+// Posts a summary of the entries matching the filter to a Slack or Discord webhook, configured as
+// `webhooks` in the config file. Messages are sent with `curl`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    // Filter expression, every entry is posted without one.
+    pub filter: Option<String>,
+    #[serde(skip)]
+    compiled: Option<Filter>,
+}
+
+impl Webhook {
+    // Parses the filter, done once when the config is loaded.
+    pub fn compile(&mut self) -> Result<(), String> {
+        self.compiled = self.filter.as_deref().map(Filter::parse).transpose()?;
+        Ok(())
+    }
+
+    pub fn matches(&self, entry: &DebugEntry) -> bool {
+        match (&self.filter, &self.compiled) {
+            (Some(_), Some(filter)) => filter.matches(entry),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    // Posts in the background, a failing webhook should not interrupt receiving entries.
+    pub fn post(&self, entry: &DebugEntry) {
+        let url = self.url.clone();
+        let body = self.payload(entry).to_string();
+        thread::spawn(move || {
+            let _ = curl(&url, &body);
+        });
+    }
+
+    fn payload(&self, entry: &DebugEntry) -> serde_json::Value {
+        let mut data = serde_json::to_string(&entry.data).unwrap_or_default();
+        if data.len() > MAX_DATA_LENGTH {
+            let mut end = MAX_DATA_LENGTH;
+            while !data.is_char_boundary(end) {
+                end -= 1;
+            }
+            data.truncate(end);
+            data.push('…');
+        }
+        // Discord expects `content` and bold with two asterisks, Slack `text` with one.
+        let discord = self.url.contains("discord.com") || self.url.contains("discordapp.com");
+        let bold = if discord { "**" } else { "*" };
+        let mut text = format!("{}{}{} at {}\n```{}```", bold, entry.label, bold, entry.time, data);
+        if let Some(frame) = entry.backtrace.first() {
+            text.push_str(&format!("\n{}:{}", frame.file, frame.line));
+        }
+
+        if discord {
+            json!({ "content": text })
+        } else {
+            json!({ "text": text })
+        }
+    }
+}
+
+fn curl(url: &str, body: &str) -> io::Result<()> {
+    let mut child = Command::new("curl")
+        .args(["--silent", "--max-time", "10", "--header", "Content-Type: application/json"])
+        .args(["--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes())?;
+    }
+    child.wait()?;
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+use crate::{BacktraceItem, DebugEntry};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use udl_core::timestamp;
+
+// A line of a trace file.
+enum Record<'a> {
+    Call {
+        // Function number of the computer readable format, referred to by the exit and return records.
+        number: Option<&'a str>,
+        level: usize,
+        function: &'a str,
+        time: f64,
+        memory: i64,
+        file: &'a str,
+        line: i64,
+        params: Vec<&'a str>,
+        internal: bool,
+    },
+    Exit {
+        number: &'a str,
+        time: f64,
+        memory: i64,
+    },
+    Return {
+        number: &'a str,
+        value: &'a str,
+    },
+}
+
+// Loads an Xdebug function trace as an entry per function call, with the calling functions as
+// backtrace. Both the computer readable format (`xdebug.trace_format=1`), which includes the exit
+// time, memory and return values, and the default human readable format are read.
+pub fn load(path: &Path) -> Result<Vec<DebugEntry>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let mut start: f64 = 0.0;
+    let mut entries: Vec<DebugEntry> = vec![];
+    // The calls leading to the current one, as (level, backtrace frame).
+    let mut stack: Vec<(usize, BacktraceItem)> = vec![];
+    let mut numbers: HashMap<&str, usize> = HashMap::new();
+
+    for line in content.lines() {
+        if let Some(time) = line.strip_prefix("TRACE START [") {
+            start = timestamp::parse(time.trim_end_matches(']')).unwrap_or(0.0);
+            continue;
+        }
+
+        match record(line) {
+            Some(Record::Call {
+                number,
+                level,
+                function,
+                time,
+                memory,
+                file,
+                line,
+                params,
+                internal,
+            }) => {
+                while stack.last().is_some_and(|(caller, _)| *caller >= level) {
+                    stack.pop();
+                }
+                let frame = BacktraceItem {
+                    file: file.into(),
+                    line,
+                    column: None,
+                    function: function.to_string(),
+                };
+                stack.push((level, frame));
+
+                let params: Map<String, Value> = params
+                    .iter()
+                    .enumerate()
+                    .map(|(index, param)| (index.to_string(), Value::String(param.to_string())))
+                    .collect();
+                let mut data = HashMap::new();
+                data.insert(
+                    String::from("xdebug"),
+                    json!({
+                        "function": function,
+                        "level": level,
+                        "time": time,
+                        "memory": memory,
+                        "params": params,
+                    }),
+                );
+
+                if let Some(number) = number {
+                    numbers.insert(number, entries.len());
+                }
+                entries.push(DebugEntry {
+                    label: function.into(),
+                    time: timestamp::format((start + time) as i64),
+                    data,
+                    backtrace: stack
+                        .iter()
+                        .rev()
+                        .map(|(_, frame)| BacktraceItem {
+                            file: frame.file.clone(),
+                            line: frame.line,
+                            column: None,
+                            function: frame.function.clone(),
+                        })
+                        .collect(),
+                    tags: if internal { vec![String::from("internal")] } else { vec![] },
+                    ..DebugEntry::default()
+                });
+            }
+            Some(Record::Exit { number, time, memory }) => {
+                if let Some(entry) = numbers.get(number).and_then(|index| entries.get_mut(*index)) {
+                    if let Some(details) = entry.data.get_mut("xdebug") {
+                        let duration = time - details["time"].as_f64().unwrap_or(time);
+                        details["duration"] = json!(duration);
+                        details["memory_delta"] = json!(memory - details["memory"].as_i64().unwrap_or(memory));
+                        entry.elapsed = Duration::try_from_secs_f64(duration.max(0.0)).ok();
+                    }
+                }
+            }
+            Some(Record::Return { number, value }) => {
+                let entry = numbers.get(number).and_then(|index| entries.get_mut(*index));
+                if let Some(details) = entry.and_then(|entry| entry.data.get_mut("xdebug")) {
+                    details["return"] = Value::String(value.to_string());
+                }
+            }
+            None => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+fn record(line: &str) -> Option<Record<'_>> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() < 3 {
+        return human_readable(line);
+    }
+
+    // Level, function number, record type, then the fields of the type.
+    match fields[2] {
+        "0" if fields.len() >= 10 => Some(Record::Call {
+            number: Some(fields[1]),
+            level: fields[0].parse().ok()?,
+            function: fields[5],
+            time: fields[3].parse().ok()?,
+            memory: fields[4].parse().ok()?,
+            file: fields[8],
+            line: fields[9].parse().ok()?,
+            params: fields.iter().skip(11).copied().collect(),
+            internal: fields[6] == "0",
+        }),
+        "1" if fields.len() >= 5 => Some(Record::Exit {
+            number: fields[1],
+            time: fields[3].parse().ok()?,
+            memory: fields[4].parse().ok()?,
+        }),
+        "R" if fields.len() >= 6 => Some(Record::Return {
+            number: fields[1],
+            value: fields[5],
+        }),
+        _ => None,
+    }
+}
+
+// A call of the human readable format, like `    0.0003     393400     -> foo($a = 1) /var/www/index.php:12`,
+// indented after the memory column by two spaces per level.
+fn human_readable(line: &str) -> Option<Record<'_>> {
+    let arrow = line.find("-> ")?;
+    let before = &line[..arrow];
+    let mut columns = before.split_whitespace();
+    let time = columns.next()?.parse().ok()?;
+    let memory = columns.next()?.parse().ok()?;
+    let indentation = before.len() - before.trim_end().len();
+
+    let (call, location) = line[arrow + 3..].rsplit_once(' ')?;
+    let (file, line) = location.rsplit_once(':')?;
+    let (function, params) = match call.find('(') {
+        Some(open) => (&call[..open], call[open + 1..].trim_end_matches(')')),
+        None => (call, ""),
+    };
+
+    Some(Record::Call {
+        number: None,
+        level: indentation.saturating_sub(1) / 2,
+        function,
+        time,
+        memory,
+        file,
+        line: line.parse().ok()?,
+        params: params.split(", ").filter(|param| !param.is_empty()).collect(),
+        internal: false,
+    })
+}
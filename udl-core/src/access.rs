@@ -0,0 +1,95 @@
+//! Who may connect to the listener, for when it is exposed beyond the local machine: an allowlist
+//! of client addresses and networks, and a maximum number of connections open at the same time.
+//! Rejected connections are closed right away and reported as diagnostics.
+
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// An address, or a network of addresses like `192.168.1.0/24` or `fd00::/8`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    address: IpAddr,
+    prefix: u8,
+}
+
+impl Network {
+    pub fn parse(text: &str) -> Result<Network, String> {
+        let (address, prefix) = match text.trim().split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (text.trim(), None),
+        };
+        let address: IpAddr = address.parse().map_err(|_| format!("Invalid address: {}", text))?;
+        let bits = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse().ok().filter(|prefix| *prefix <= bits).ok_or_else(|| format!("Invalid network: {}", text))?,
+            None => bits,
+        };
+
+        Ok(Network { address, prefix })
+    }
+
+    pub fn contains(&self, address: IpAddr) -> bool {
+        // Compared as IPv6, with IPv4 as IPv4-mapped addresses, which is how IPv4 clients of a listener
+        // on an IPv6 address show up. So `10.0.0.0/8` and `::ffff:10.0.0.0/104` are the same network.
+        let prefix = match self.address {
+            IpAddr::V4(_) => self.prefix + 96,
+            IpAddr::V6(_) => self.prefix,
+        };
+        masked(mapped(self.address), prefix, 128) == masked(mapped(address), prefix, 128)
+    }
+}
+
+fn mapped(address: IpAddr) -> u128 {
+    match address {
+        IpAddr::V4(v4) => u128::from(v4.to_ipv6_mapped()),
+        IpAddr::V6(v6) => u128::from(v6),
+    }
+}
+
+// The first `prefix` bits of an address of `bits` bits.
+fn masked(address: u128, prefix: u8, bits: u8) -> u128 {
+    match prefix {
+        0 => 0,
+        prefix => address >> (bits - prefix),
+    }
+}
+
+/// Shared between the listener and the connections it reads, counting the open ones.
+#[derive(Debug, Clone, Default)]
+pub struct Access {
+    /// Clients allowed to connect, any client when empty.
+    pub allowed: Vec<Network>,
+    /// Connections read at the same time, unlimited when `None`.
+    pub max_connections: Option<usize>,
+    open: Arc<AtomicUsize>,
+}
+
+impl Access {
+    pub fn new(allowed: Vec<Network>, max_connections: Option<usize>) -> Access {
+        Access {
+            allowed,
+            max_connections,
+            open: Arc::default(),
+        }
+    }
+
+    /// Why a connection from the address is rejected, or `None` when it is accepted. Accepted
+    /// connections count as open until `close` is called.
+    pub fn admit(&self, address: Option<IpAddr>) -> Option<String> {
+        if !self.allowed.is_empty() && !address.is_some_and(|address| self.allowed.iter().any(|network| network.contains(address))) {
+            return Some(String::from("not in --allow"));
+        }
+        let open = self.open.fetch_add(1, Ordering::SeqCst);
+        if let Some(max) = self.max_connections.filter(|max| open >= *max) {
+            self.close();
+            return Some(format!("{} connections are open already", max));
+        }
+
+        None
+    }
+
+    pub fn close(&self) {
+        self.open.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,74 @@
+//! Bounds the entries received but not yet handled, for when the consumer cannot keep up with the
+//! clients. The listener either stops reading from clients until there is room again, which makes
+//! them wait on their sends, or keeps reading and drops the entries, counting them.
+
+use async_std::task;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Shared between the listener, counting the entries it hands out, and the consumer marking them
+/// handled.
+#[derive(Debug, Clone)]
+pub struct Backlog {
+    pending: Arc<AtomicUsize>,
+    dropped: Arc<AtomicUsize>,
+    capacity: usize,
+    drop_when_full: bool,
+}
+
+impl Backlog {
+    pub fn new(capacity: usize, drop_when_full: bool) -> Backlog {
+        Backlog {
+            pending: Arc::new(AtomicUsize::new(0)),
+            dropped: Arc::new(AtomicUsize::new(0)),
+            capacity: capacity.max(1),
+            drop_when_full,
+        }
+    }
+
+    /// A backlog that is never full.
+    pub fn unbounded() -> Backlog {
+        Backlog::new(usize::MAX, false)
+    }
+
+    /// Number of entries handed out and not handled yet.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Relaxed)
+    }
+
+    /// Number of entries dropped because the backlog was full.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Marks an entry handed out by the listener as handled.
+    pub fn done(&self) {
+        let _ = self
+            .pending
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pending| pending.checked_sub(1));
+    }
+
+    pub(crate) fn is_full(&self) -> bool {
+        self.pending() >= self.capacity
+    }
+
+    pub(crate) fn drops_when_full(&self) -> bool {
+        self.drop_when_full
+    }
+
+    pub(crate) fn add(&self) {
+        self.pending.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn drop_entry(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Waits until the consumer made room.
+    pub(crate) async fn wait_for_room(&self) {
+        while self.is_full() {
+            task::sleep(Duration::from_millis(10)).await;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use crate::entry::DebugEntry;
+use crate::intern::Interner;
+use crate::spill::Spill;
+use crate::store::Store;
+use std::time::{Duration, Instant};
+
+/// The received entries, newest first. Entries can be persisted to a store as they arrive, and the
+/// oldest ones are evicted beyond `max_entries`, optionally into a spill file they can be paged
+/// back in from. With a `ttl` entries are removed once buffered longer than that.
+#[derive(Default)]
+pub struct Buffer {
+    pub items: Vec<DebugEntry>,
+    pub store: Option<Store>,
+    pub max_entries: Option<usize>,
+    pub ttl: Option<Duration>,
+    pub spill: Option<Spill>,
+    /// Number of entries pushed, including the ones evicted since.
+    pub received: usize,
+    /// Id for the next entry without one.
+    next_id: u64,
+    /// Number of entries evicted from memory.
+    pub evicted: usize,
+    /// Number of entries removed after the ttl.
+    pub expired: usize,
+    /// Number of entries at the end of the items that were paged back in from the spill file.
+    pub paged: usize,
+    /// The last error writing to the store or spill file.
+    pub error: Option<String>,
+    /// Labels and backtrace files of the entries in memory.
+    pub strings: Interner,
+}
+
+impl Buffer {
+    pub fn new() -> Buffer {
+        Buffer::default()
+    }
+
+    /// Adds the entry as the newest one and evicts the oldest ones beyond `max_entries`. Entries
+    /// get the next id, unless they already have one from being stored before. Returns the id,
+    /// the entry itself can be gone already when `max_entries` is 0.
+    pub fn push(&mut self, mut entry: DebugEntry) -> u64 {
+        if entry.id == 0 {
+            entry.id = self.next_id.max(1);
+        }
+        self.next_id = self.next_id.max(entry.id + 1);
+        self.intern(&mut entry);
+        entry.arrived.get_or_insert_with(Instant::now);
+
+        if let Some(store) = self.store.as_mut() {
+            if let Err(error) = store.append(&entry) {
+                self.error = Some(error.to_string());
+            }
+        }
+
+        let id = entry.id;
+        // Before adding the entry, which is not expired by its own push however short the ttl.
+        self.expire();
+        self.items.insert(0, entry);
+        self.received += 1;
+        self.evict();
+
+        id
+    }
+
+    /// Removes the entries buffered longer than the ttl, returning how many. They stay in the store,
+    /// but are not spilled. Entries paged back in from the spill file are removed along with them.
+    pub fn expire(&mut self) -> usize {
+        let ttl = match self.ttl {
+            Some(ttl) => ttl,
+            None => return 0,
+        };
+
+        let mut count = 0;
+        while self.items.last().is_some_and(|entry| entry.arrived.is_none_or(|arrived| arrived.elapsed() > ttl)) {
+            self.items.pop();
+            match self.paged {
+                0 => self.expired += 1,
+                _ => self.paged -= 1,
+            }
+            count += 1;
+        }
+        if count > 0 {
+            self.strings.purge();
+        }
+
+        count
+    }
+
+    fn evict(&mut self) {
+        let max_entries = match self.max_entries {
+            Some(max_entries) => max_entries,
+            None => return,
+        };
+
+        while self.items.len() > max_entries {
+            let entry = match self.items.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+            // Paged in entries are still in the spill file.
+            if self.paged > 0 {
+                self.paged -= 1;
+                continue;
+            }
+            if let Some(spill) = self.spill.as_mut() {
+                if let Err(error) = spill.append(&entry) {
+                    self.error = Some(error.to_string());
+                }
+            }
+            self.evicted += 1;
+        }
+        self.strings.purge();
+    }
+
+    fn intern(&mut self, entry: &mut DebugEntry) {
+        entry.label = self.strings.intern(&entry.label);
+        for frame in entry.backtrace.iter_mut() {
+            frame.file = self.strings.intern(&frame.file);
+        }
+    }
+
+    /// Stores the entry at the index again after it changed, replacing the earlier version once the
+    /// store is opened again.
+    pub fn store_again(&mut self, index: usize) {
+        let (store, entry) = match (self.store.as_mut(), self.items.get(index)) {
+            (Some(store), Some(entry)) => (store, entry),
+            _ => return,
+        };
+        if let Err(error) = store.append(entry) {
+            self.error = Some(error.to_string());
+        }
+    }
+
+    /// Persists the pin, note and tags of the entry with the id to the store, if any.
+    pub fn annotate(&mut self, id: u64) {
+        let (store, entry) = match (self.store.as_mut(), self.items.iter().find(|entry| entry.id == id)) {
+            (Some(store), Some(entry)) => (store, entry),
+            _ => return,
+        };
+        if let Err(error) = store.annotate(entry) {
+            self.error = Some(error.to_string());
+        }
+    }
+
+    /// Finds an entry in memory by its id.
+    pub fn get(&self, id: u64) -> Option<&DebugEntry> {
+        self.items.iter().find(|entry| entry.id == id)
+    }
+
+    /// Pages in up to `count` older entries from the spill file, appending them to the items.
+    /// Returns how many were loaded.
+    pub fn load_older(&mut self, count: usize) -> usize {
+        let spill = match &self.spill {
+            Some(spill) => spill,
+            None => return 0,
+        };
+        let end = spill.len().saturating_sub(self.paged);
+        let entries = match spill.read(end.saturating_sub(count), end) {
+            Ok(entries) => entries,
+            Err(error) => {
+                self.error = Some(error.to_string());
+                return 0;
+            }
+        };
+
+        let loaded = entries.len();
+        for mut entry in entries.into_iter().rev() {
+            self.intern(&mut entry);
+            self.items.push(entry);
+        }
+        self.paged += loaded;
+
+        loaded
+    }
+}
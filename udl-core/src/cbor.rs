@@ -0,0 +1,158 @@
+//! CBOR payloads as json values. Byte strings become base64 text, tags are left out for the value
+//! they tag, and simple values other than booleans, null and undefined become null.
+
+use crate::base64;
+use crate::decoder::{self, Reader, MAX_DEPTH};
+use serde_json::{Map, Value};
+use std::convert::TryFrom;
+
+/// Ends the items of a value with an indefinite length.
+const BREAK: u8 = 0xff;
+
+/// The single value of the payload.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader::new(bytes);
+    let value = read(&mut reader, 0)?;
+    reader.finish()?;
+
+    Ok(value)
+}
+
+fn read(reader: &mut Reader, depth: usize) -> Result<Value, String> {
+    if depth > MAX_DEPTH {
+        return Err(String::from("nested too deep"));
+    }
+
+    let initial = reader.byte()?;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    if major == 7 {
+        return simple(reader, info);
+    }
+    // Indefinite lengths, for byte and text strings in chunks.
+    if info == 31 {
+        return match major {
+            2 | 3 => chunks(reader, major),
+            4 => {
+                let mut items = vec![];
+                while !breaks(reader) {
+                    items.push(read(reader, depth + 1)?);
+                }
+                Ok(Value::Array(items))
+            }
+            5 => {
+                let mut object = Map::new();
+                while !breaks(reader) {
+                    let key = decoder::key(read(reader, depth + 1)?);
+                    object.insert(key, read(reader, depth + 1)?);
+                }
+                Ok(Value::Object(object))
+            }
+            _ => Err(format!("invalid indefinite length at byte {}", reader.position - 1)),
+        };
+    }
+
+    let argument = argument(reader, info)?;
+    let value = match major {
+        0 => Value::from(argument),
+        // -1 minus the argument, which only fits an i64 up to its minimum.
+        1 => match i64::try_from(argument) {
+            Ok(argument) => Value::from(-1 - argument),
+            Err(_) => decoder::float(-1.0 - argument as f64),
+        },
+        2 => Value::String(base64::encode(reader.take(argument as usize)?)),
+        3 => Value::String(String::from_utf8_lossy(reader.take(argument as usize)?).into_owned()),
+        4 => {
+            // Not trusting the count for the capacity, every item takes at least a byte.
+            let mut items = Vec::with_capacity((argument as usize).min(1024));
+            for _ in 0..argument {
+                items.push(read(reader, depth + 1)?);
+            }
+            Value::Array(items)
+        }
+        5 => {
+            let mut object = Map::new();
+            for _ in 0..argument {
+                let key = decoder::key(read(reader, depth + 1)?);
+                object.insert(key, read(reader, depth + 1)?);
+            }
+            Value::Object(object)
+        }
+        // A tag, like a date, for the value after it.
+        _ => read(reader, depth + 1)?,
+    };
+
+    Ok(value)
+}
+
+// The number following the initial byte, or in it for the smallest ones.
+fn argument(reader: &mut Reader, info: u8) -> Result<u64, String> {
+    match info {
+        0..=23 => Ok(u64::from(info)),
+        24..=27 => reader.number(1 << (info - 24)),
+        _ => Err(format!("invalid additional information {} at byte {}", info, reader.position - 1)),
+    }
+}
+
+fn simple(reader: &mut Reader, info: u8) -> Result<Value, String> {
+    let value = match info {
+        20 => Value::Bool(false),
+        21 => Value::Bool(true),
+        25 => decoder::float(half(reader.number(2)? as u16)),
+        26 => decoder::float(f64::from(f32::from_bits(reader.number(4)? as u32))),
+        27 => decoder::float(f64::from_bits(reader.number(8)?)),
+        24 => {
+            reader.byte()?;
+            Value::Null
+        }
+        31 => return Err(format!("unexpected break at byte {}", reader.position - 1)),
+        _ => Value::Null,
+    };
+
+    Ok(value)
+}
+
+// Consumes the break ending the items of an indefinite length, when it is next.
+fn breaks(reader: &mut Reader) -> bool {
+    let found = reader.peek() == Some(BREAK);
+    if found {
+        reader.position += 1;
+    }
+
+    found
+}
+
+// A byte or text string sent in chunks of definite length.
+fn chunks(reader: &mut Reader, major: u8) -> Result<Value, String> {
+    let mut bytes = vec![];
+    while !breaks(reader) {
+        let initial = reader.byte()?;
+        if initial >> 5 != major {
+            return Err(format!("invalid chunk at byte {}", reader.position - 1));
+        }
+        let size = argument(reader, initial & 0x1f)? as usize;
+        bytes.extend_from_slice(reader.take(size)?);
+    }
+
+    Ok(Value::String(match major {
+        2 => base64::encode(&bytes),
+        _ => String::from_utf8_lossy(&bytes).into_owned(),
+    }))
+}
+
+// A half precision float.
+fn half(bits: u16) -> f64 {
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f64::from(bits & 0x3ff);
+    let value = match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * 2f64.powi(exponent - 25),
+    };
+
+    if bits & 0x8000 != 0 {
+        -value
+    } else {
+        value
+    }
+}
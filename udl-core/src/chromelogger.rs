@@ -0,0 +1,101 @@
+//! ChromeLogger data, as sent in the `X-ChromeLogger-Data` header by ChromePhp and similar
+//! middleware: base64 encoded json with rows of logged values, a backtrace and a type. A small relay
+//! can forward the header to udl, each row becomes an entry.
+
+use crate::base64;
+use crate::entry::{BacktraceItem, DebugEntry};
+use crate::timestamp;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// The entries of the rows, or `None` when the payload is not ChromeLogger data. The payload can be
+/// the header value, with or without the header name, or the decoded json.
+pub fn to_entries(payload: &str) -> Option<Vec<DebugEntry>> {
+    let payload = payload.trim();
+    let payload = payload.strip_prefix("X-ChromeLogger-Data:").unwrap_or(payload).trim();
+    let json = if payload.starts_with('{') {
+        payload.to_string()
+    } else {
+        String::from_utf8(base64::decode(payload)?).ok()?
+    };
+
+    let log: Value = serde_json::from_str(&json).ok()?;
+    let rows = log.get("rows")?.as_array()?;
+    let columns: Vec<&str> = match log.get("columns").and_then(Value::as_array) {
+        Some(columns) => columns.iter().filter_map(Value::as_str).collect(),
+        None => vec!["log", "backtrace", "type"],
+    };
+    let column = |row: &Value, name: &str| {
+        columns
+            .iter()
+            .position(|column| *column == name)
+            .and_then(|index| row.get(index))
+            .cloned()
+            .unwrap_or(Value::Null)
+    };
+
+    // Groups nest, entries within them get the group names as section.
+    let mut groups: Vec<String> = vec![];
+    let mut entries = vec![];
+    for row in rows {
+        let values = match column(row, "log") {
+            Value::Array(values) => values,
+            Value::Null => vec![],
+            value => vec![value],
+        };
+        let kind = column(row, "type").as_str().unwrap_or_default().to_string();
+        let first = values.first().map(|value| match value {
+            Value::String(text) => text.to_string(),
+            value => value.to_string(),
+        });
+
+        match kind.as_str() {
+            "group" | "groupCollapsed" => {
+                groups.push(first.unwrap_or_else(|| String::from("group")));
+                continue;
+            }
+            "groupEnd" => {
+                groups.pop();
+                continue;
+            }
+            _ => {}
+        }
+
+        // Keyed by position, the details pane shows objects rather than arrays.
+        let logged: Map<String, Value> =
+            values.into_iter().enumerate().map(|(index, value)| (index.to_string(), value)).collect();
+        let mut data = HashMap::new();
+        data.insert(String::from("log"), Value::Object(logged));
+        let severity = if kind.is_empty() { String::from("log") } else { kind };
+        data.insert(String::from("type"), Value::String(severity.clone()));
+
+        entries.push(DebugEntry {
+            label: first.unwrap_or_else(|| severity.clone()).into(),
+            time: timestamp::now(),
+            data,
+            backtrace: column(row, "backtrace").as_str().and_then(backtrace_item).into_iter().collect(),
+            color: match severity.as_str() {
+                "error" => Some(String::from("red")),
+                "warn" => Some(String::from("yellow")),
+                "info" => Some(String::from("blue")),
+                _ => None,
+            },
+            section: Some(groups.join(" / ")).filter(|section| !section.is_empty()),
+            tags: vec![severity],
+            ..DebugEntry::default()
+        });
+    }
+
+    Some(entries)
+}
+
+// A backtrace like `/var/www/index.php : 12`.
+fn backtrace_item(backtrace: &str) -> Option<BacktraceItem> {
+    let (file, line) = backtrace.rsplit_once(':')?;
+    Some(BacktraceItem {
+        file: file.trim().into(),
+        line: line.trim().parse().ok()?,
+        column: None,
+        function: String::new(),
+    })
+}
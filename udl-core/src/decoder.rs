@@ -0,0 +1,201 @@
+//! How clients frame and encode the payloads they send over a connection. A decoder splits the
+//! bytes read into payloads and turns each into json, which is then parsed like any other payload.
+//! Binary encodings like MessagePack and CBOR make dumps holding lots of binary data much smaller.
+//!
+//! ```
+//! use udl_core::decoder;
+//!
+//! let decoder = decoder::named("msgpack").unwrap();
+//! // {"label": "hi"}
+//! let json = decoder.decode(&[0x81, 0xa5, b'l', b'a', b'b', b'e', b'l', 0xa2, b'h', b'i']).unwrap();
+//! assert_eq!(&*json, br#"{"label":"hi"}"#);
+//! ```
+
+use crate::cbor;
+use crate::msgpack;
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// How payloads are separated on a connection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Framing {
+    /// Payloads end with this byte, the last one by closing the connection as well.
+    Delimited(u8),
+    /// Payloads start with their size in bytes, as an unsigned 32 bit big endian number.
+    LengthPrefixed,
+}
+
+/// Framing and decoding of the payloads of a connection. Listeners take a `&'static dyn Decoder`,
+/// the decoders of udl are found by name with `named`.
+pub trait Decoder: Send + Sync {
+    /// The name selecting it, like `msgpack`.
+    fn name(&self) -> &'static str;
+
+    fn framing(&self) -> Framing;
+
+    /// The payload as json. Json payloads are handed on as they are.
+    fn decode<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, String>;
+}
+
+/// Json payloads terminated by a null byte, what the udl clients send.
+pub struct Json;
+
+/// A json payload per line.
+pub struct Ndjson;
+
+/// Json payloads preceded by their size.
+pub struct LengthPrefixed;
+
+/// MessagePack payloads preceded by their size.
+pub struct MessagePack;
+
+/// CBOR payloads preceded by their size.
+pub struct Cbor;
+
+impl Decoder for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Delimited(b'\0')
+    }
+
+    fn decode<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+        Ok(Cow::Borrowed(payload))
+    }
+}
+
+impl Decoder for Ndjson {
+    fn name(&self) -> &'static str {
+        "ndjson"
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::Delimited(b'\n')
+    }
+
+    fn decode<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+        Ok(Cow::Borrowed(payload.strip_suffix(b"\r").unwrap_or(payload)))
+    }
+}
+
+impl Decoder for LengthPrefixed {
+    fn name(&self) -> &'static str {
+        "length-prefixed"
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::LengthPrefixed
+    }
+
+    fn decode<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+        Ok(Cow::Borrowed(payload))
+    }
+}
+
+impl Decoder for MessagePack {
+    fn name(&self) -> &'static str {
+        "msgpack"
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::LengthPrefixed
+    }
+
+    fn decode<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+        to_json(msgpack::decode(payload).map_err(|error| format!("Invalid MessagePack: {}", error))?)
+    }
+}
+
+impl Decoder for Cbor {
+    fn name(&self) -> &'static str {
+        "cbor"
+    }
+
+    fn framing(&self) -> Framing {
+        Framing::LengthPrefixed
+    }
+
+    fn decode<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, String> {
+        to_json(cbor::decode(payload).map_err(|error| format!("Invalid CBOR: {}", error))?)
+    }
+}
+
+fn to_json(value: Value) -> Result<Cow<'static, [u8]>, String> {
+    serde_json::to_vec(&value).map(Cow::Owned).map_err(|error| error.to_string())
+}
+
+/// The decoders of udl, the first one being the default.
+pub const DECODERS: [&dyn Decoder; 5] = [&Json, &Ndjson, &LengthPrefixed, &MessagePack, &Cbor];
+
+/// The decoder with the name, like `cbor`.
+pub fn named(name: &str) -> Option<&'static dyn Decoder> {
+    DECODERS.iter().copied().find(|decoder| decoder.name() == name)
+}
+
+/// Json terminated by a null byte.
+pub fn default() -> &'static dyn Decoder {
+    DECODERS[0]
+}
+
+/// Reads the values of binary encodings.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pub position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes, position: 0 }
+    }
+
+    pub fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.position.checked_add(count).filter(|end| *end <= self.bytes.len());
+        match end {
+            Some(end) => {
+                let taken = &self.bytes[self.position..end];
+                self.position = end;
+                Ok(taken)
+            }
+            None => Err(format!("ends in the middle of a value at byte {}", self.position)),
+        }
+    }
+
+    pub fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// A big endian unsigned number of this many bytes.
+    pub fn number(&mut self, size: usize) -> Result<u64, String> {
+        Ok(self.take(size)?.iter().fold(0, |number, byte| number << 8 | u64::from(*byte)))
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    /// Fails when there is more after the value.
+    pub fn finish(&self) -> Result<(), String> {
+        match self.bytes.len() - self.position {
+            0 => Ok(()),
+            left => Err(format!("{} bytes left after the value", left)),
+        }
+    }
+}
+
+/// Nesting deeper than this is refused, like serde_json does for json.
+pub(crate) const MAX_DEPTH: usize = 128;
+
+/// A float as json, which has no NaN or infinity.
+pub(crate) fn float(value: f64) -> Value {
+    serde_json::Number::from_f64(value).map_or(Value::Null, Value::Number)
+}
+
+/// A map key as json, which only has text keys.
+pub(crate) fn key(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,85 @@
+//! Payloads that are not valid UTF-8, like the Latin-1 strings of legacy PHP apps. Instead of
+//! replacing the invalid bytes with `�`, each is decoded as the Latin-1 char for it, and the strings
+//! holding them keep their bytes as received so the entry can be flagged and the bytes shown.
+
+use crate::DebugEntry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Marks the invalid bytes in the second decoding, to find the strings holding them.
+const REPLACEMENT: char = '\u{FFFD}';
+
+/// A string of an entry that held bytes that are not valid UTF-8.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InvalidText {
+    /// Where the string is, like `label`, `data.user.name` or `data.items[2]`.
+    pub path: String,
+    /// The string as received.
+    pub bytes: Vec<u8>,
+}
+
+/// The bytes as text, with each byte that is not valid UTF-8 decoded as Latin-1, or as `�` when
+/// `latin1` is false. Both have a char for every invalid byte, so their strings line up.
+pub fn decode(bytes: &[u8], latin1: bool) -> String {
+    let mut text = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            text.push(if latin1 { *byte as char } else { REPLACEMENT });
+        }
+    }
+
+    text
+}
+
+/// The strings of the entry parsed from the Latin-1 decoding holding invalid bytes, found by
+/// comparing them with those of the same entry parsed from the decoding with `�` for them.
+pub fn invalid_texts(entry: &DebugEntry, marked: &DebugEntry) -> Vec<InvalidText> {
+    let mut found = vec![];
+    collect_text("label", &entry.label, &marked.label, &mut found);
+    let mut keys: Vec<&String> = entry.data.keys().collect();
+    keys.sort();
+    for key in keys {
+        if let Some(other) = marked.data.get(key) {
+            collect(&format!("data.{}", key), &entry.data[key], other, &mut found);
+        }
+    }
+
+    found
+}
+
+fn collect(path: &str, value: &Value, marked: &Value, found: &mut Vec<InvalidText>) {
+    match (value, marked) {
+        (Value::String(text), Value::String(other)) => collect_text(path, text, other, found),
+        (Value::Array(items), Value::Array(others)) => {
+            for (index, (item, other)) in items.iter().zip(others).enumerate() {
+                collect(&format!("{}[{}]", path, index), item, other, found);
+            }
+        }
+        (Value::Object(object), Value::Object(others)) => {
+            for (key, item) in object {
+                if let Some(other) = others.get(key) {
+                    collect(&format!("{}.{}", path, key), item, other, found);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_text(path: &str, text: &str, marked: &str, found: &mut Vec<InvalidText>) {
+    if text == marked {
+        return;
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+    for (char, mark) in text.chars().zip(marked.chars()) {
+        // Latin-1 chars are the byte they were decoded from.
+        if mark == REPLACEMENT && char != REPLACEMENT {
+            bytes.push(char as u32 as u8);
+        } else {
+            bytes.extend_from_slice(char.encode_utf8(&mut [0; 4]).as_bytes());
+        }
+    }
+    found.push(InvalidText { path: path.to_string(), bytes });
+}
@@ -0,0 +1,111 @@
+use crate::encoding::InvalidText;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Payloads read since udl started, numbering their entries.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// A frame of the backtrace sent with an entry.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct BacktraceItem {
+    /// Shared with the other frames in the same file once buffered.
+    pub file: Arc<str>,
+    pub line: i64,
+    /// Column within the line, used to resolve positions in minified files through source maps.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<i64>,
+    pub function: String,
+}
+
+/// An entry as sent by a client.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DebugEntry {
+    /// Assigned by the buffer in order of arrival, starting at 1.
+    #[serde(default)]
+    pub id: u64,
+    /// Set by the client to refer to the entry later, like jumping to it with `goto`.
+    #[serde(default, rename = "ref", skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Set by the client to replace the label, time, data and color of the entry with the same `ref`
+    /// instead of adding a new one.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub update: bool,
+    /// Shared with the other entries with the same label once buffered.
+    pub label: Arc<str>,
+    /// As sent, a unix timestamp sent as a number is kept as its text.
+    #[serde(deserialize_with = "string_or_number")]
+    pub time: String,
+    pub data: HashMap<String, Value>,
+    pub backtrace: Vec<BacktraceItem>,
+    /// Optional client hints for highlighting and grouping the entry in the list.
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub section: Option<String>,
+    /// Tags sent by the client or added from the UI.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Free text note and pin attached from the UI.
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Fields of the hello of the connection the entry was sent over, like the app and pid.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub connection: Map<String, Value>,
+    /// Recorded as the entry is received, whatever the client sent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrival: Option<Arrival>,
+    /// Set on `measure_stop` entries once matched with their `measure_start`.
+    #[serde(skip)]
+    pub elapsed: Option<Duration>,
+    /// Bytes of the data serialized as json, set once buffered by the UI.
+    #[serde(skip)]
+    pub size: usize,
+    /// When the entry was buffered, to expire it after the ttl of the buffer.
+    #[serde(skip)]
+    pub arrived: Option<Instant>,
+}
+
+/// When and how an entry was received, as udl saw it. The time of clients is often wrong or missing.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Arrival {
+    /// UTC time the payload was read, like `2024-01-01 12:00:00.250`.
+    pub time: String,
+    /// The client that sent the payload, like `127.0.0.1` or `app (127.0.0.1)` after a hello.
+    pub client: String,
+    /// Bytes of the payload as received.
+    pub bytes: usize,
+    /// Number of the payload among those read since udl started, from 1. Entries from the same
+    /// payload share it.
+    pub sequence: u64,
+    /// Strings of the entry with bytes that are not valid UTF-8, which are decoded as Latin-1.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub invalid_utf8: Vec<InvalidText>,
+}
+
+impl Arrival {
+    /// Numbers a payload of the client read just now.
+    pub fn record(client: &str, bytes: usize) -> Arrival {
+        let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |duration| duration.as_secs_f64());
+        Arrival {
+            time: crate::timestamp::format_millis(seconds, 0),
+            client: client.to_string(),
+            bytes,
+            sequence: SEQUENCE.fetch_add(1, Ordering::SeqCst) + 1,
+            invalid_utf8: vec![],
+        }
+    }
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::String(text) => Ok(text),
+        Value::Number(number) => Ok(number.to_string()),
+        value => Err(de::Error::custom(format!("expected a time as text or a number, found {}", value))),
+    }
+}
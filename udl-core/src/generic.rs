@@ -0,0 +1,49 @@
+//! Arbitrary json objects, like the lines of a structured log, turned into entries so udl works as
+//! a json log viewer too. The whole object becomes the data, the label and time are taken from
+//! fields commonly holding them.
+
+use crate::entry::DebugEntry;
+use crate::timestamp;
+use serde_json::Value;
+
+/// Fields holding the label, the first one with text is used.
+const LABEL_FIELDS: [&str; 8] = ["label", "message", "msg", "event", "title", "name", "type", "level"];
+
+/// Fields holding the time, the first one that can be read is used.
+const TIME_FIELDS: [&str; 7] = ["time", "timestamp", "@timestamp", "ts", "datetime", "date", "created_at"];
+
+/// Labels are cut off after this many characters.
+const MAX_LABEL_LENGTH: usize = 100;
+
+/// The entry for a json object, or `None` for other payloads. Objects with all the fields of an
+/// entry are left out as well, those are entries sent wrong and should show up as parse errors.
+pub fn to_entry(payload: &[u8]) -> Option<DebugEntry> {
+    let object = match serde_json::from_slice(payload).ok()? {
+        Value::Object(object) => object,
+        _ => return None,
+    };
+    if ["label", "time", "data", "backtrace"].iter().all(|field| object.contains_key(*field)) {
+        return None;
+    }
+
+    let label = LABEL_FIELDS
+        .iter()
+        .find_map(|field| object.get(*field).and_then(Value::as_str).filter(|text| !text.trim().is_empty()))
+        .map_or_else(|| String::from("JSON"), |text| text.chars().take(MAX_LABEL_LENGTH).collect());
+    let time = TIME_FIELDS
+        .iter()
+        .filter_map(|field| match object.get(*field)? {
+            Value::String(text) => Some(text.to_string()),
+            Value::Number(number) => Some(number.to_string()),
+            _ => None,
+        })
+        .find(|time| timestamp::parse(time).is_some())
+        .unwrap_or_else(timestamp::now);
+
+    Some(DebugEntry {
+        label: label.into(),
+        time,
+        data: object.into_iter().collect(),
+        ..DebugEntry::default()
+    })
+}
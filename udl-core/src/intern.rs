@@ -0,0 +1,46 @@
+//! A pool of shared strings. Labels and backtrace file paths repeat across most entries, the
+//! buffer keeps a single copy of each.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+#[derive(Debug, Default)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+    /// Size of the pool after the last purge, it is purged again once it doubled.
+    purged_at: usize,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    /// The shared copy of the string, added to the pool when it is new.
+    pub fn intern(&mut self, text: &Arc<str>) -> Arc<str> {
+        if let Some(existing) = self.strings.get(text) {
+            return Arc::clone(existing);
+        }
+
+        self.strings.insert(Arc::clone(text));
+        Arc::clone(text)
+    }
+
+    /// Drops the strings no entry refers to anymore, once the pool doubled in size.
+    pub fn purge(&mut self) {
+        if self.strings.len() < (self.purged_at * 2).max(1024) {
+            return;
+        }
+
+        self.strings.retain(|text| Arc::strong_count(text) > 1);
+        self.purged_at = self.strings.len();
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
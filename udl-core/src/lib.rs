@@ -0,0 +1,45 @@
+//! The collector behind udl: receiving entries from clients over tcp, keeping them in a buffer and
+//! persisting them. The terminal interface is built on top of this, but it can be embedded in other
+//! tools as well.
+//!
+//! ```no_run
+//! use udl_core::{listen, Buffer, Received};
+//!
+//! let mut buffer = Buffer::new();
+//! async_std::task::block_on(listen("127.0.0.1:9337", |received| match received {
+//!     Received::Entry(entry) => {
+//!         buffer.push(*entry);
+//!     }
+//!     Received::ParseError(error) => eprintln!("{}", error.error),
+//!     Received::Diagnostic(_) | Received::Raw(_) => {}
+//! }))
+//! .unwrap();
+//! ```
+
+pub mod access;
+pub mod backlog;
+pub mod base64;
+pub mod buffer;
+pub mod cbor;
+pub mod chromelogger;
+pub mod decoder;
+pub mod encoding;
+pub mod entry;
+pub mod generic;
+pub mod intern;
+pub mod listener;
+pub mod msgpack;
+pub mod protocol;
+pub mod session;
+pub mod spill;
+pub mod store;
+pub mod tail;
+pub mod timestamp;
+pub mod vardumper;
+
+pub use access::Access;
+pub use backlog::Backlog;
+pub use buffer::Buffer;
+pub use decoder::Decoder;
+pub use entry::{BacktraceItem, DebugEntry};
+pub use listener::{listen, listen_bounded, read_lines, ParseError, RawPayload, Received};
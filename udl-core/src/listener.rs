@@ -0,0 +1,528 @@
+use crate::access::Access;
+use crate::backlog::Backlog;
+use crate::chromelogger;
+use crate::decoder::{self, Decoder, Framing};
+use crate::encoding;
+use crate::entry::{Arrival, DebugEntry};
+use crate::generic;
+use crate::protocol;
+use crate::timestamp;
+use crate::vardumper;
+use async_std::{
+    channel::{self, Sender},
+    future,
+    io::{self, BufRead, BufReader},
+    net::{TcpListener, TcpStream},
+    prelude::*,
+    task,
+};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::pin::Pin;
+
+/// What a client sent over a connection.
+#[derive(Debug)]
+pub enum Received {
+    /// Boxed, entries are much larger than the other variants.
+    Entry(Box<DebugEntry>),
+    ParseError(ParseError),
+    /// What happened to a connection, like it being accepted or failing to read, to find out why a
+    /// client does not show up.
+    Diagnostic(String),
+    /// The bytes of a payload as read, handed out before the entries or parse error parsed from it.
+    Raw(RawPayload),
+}
+
+/// A payload as it came in over the connection, to inspect what a client sent.
+#[derive(Debug)]
+pub struct RawPayload {
+    pub client: String,
+    /// Without the terminating null byte. Only the start is kept of payloads that were too large.
+    pub bytes: Vec<u8>,
+    /// Bytes of the whole payload.
+    pub size: usize,
+    /// Whether the payload ended with a null byte, rather than by closing the connection.
+    pub terminated: bool,
+}
+
+/// A payload that could not be parsed as an entry.
+#[derive(Debug)]
+pub struct ParseError {
+    pub client: String,
+    pub payload: String,
+    pub error: serde_json::Error,
+}
+
+impl ParseError {
+    /// Failures of the same kind from the same client belong to the same group.
+    pub fn group(&self) -> String {
+        format!("{} {:?}", self.client, self.error.classify())
+    }
+
+    fn details(&self, count: u64) -> Value {
+        serde_json::json!({
+            "group": self.group(),
+            "client": self.client,
+            "error": self.error.to_string(),
+            "count": count,
+            "payload": self.payload,
+        })
+    }
+
+    /// Updates the entry of an earlier failure in the same group with a count, returning its index
+    /// when there was one.
+    pub fn merge_into(&self, items: &mut [DebugEntry]) -> Option<usize> {
+        let group = self.group();
+        let existing = items.iter_mut().enumerate().find(|(_, item)| {
+            item.data
+                .get("parse_error")
+                .and_then(|details| details.get("group"))
+                .is_some_and(|existing| existing == group.as_str())
+        });
+
+        match existing {
+            Some((index, item)) => {
+                let count = item.data["parse_error"]["count"].as_u64().unwrap_or(1) + 1;
+                item.data.insert(String::from("parse_error"), self.details(count));
+                item.label = format!("Parse error ({}x)", count).into();
+                item.time = timestamp::now();
+                Some(index)
+            }
+            None => None,
+        }
+    }
+
+    /// A red entry showing the raw payload and the error.
+    pub fn to_entry(&self) -> DebugEntry {
+        let mut data = HashMap::new();
+        data.insert(String::from("parse_error"), self.details(1));
+
+        DebugEntry {
+            label: "Parse error".into(),
+            time: timestamp::now(),
+            data,
+            color: Some(String::from("red")),
+            ..DebugEntry::default()
+        }
+    }
+}
+
+/// Payloads larger than this are dropped by `listen`, unless another maximum is given.
+pub const DEFAULT_MAX_PAYLOAD_SIZE: usize = 64 * 1024 * 1024;
+
+/// Bytes of a dropped payload kept to show what it was.
+const PREVIEW_SIZE: usize = 1000;
+
+/// Parses a payload sent by the client: a json entry, a VarDumper html dump, ChromeLogger data,
+/// which holds an entry for each of its rows, or any other json object. Json is parsed from the
+/// bytes as received, they are only turned into text for the other formats and errors. Payloads that
+/// are not valid UTF-8 are parsed with the invalid bytes decoded as Latin-1, and their entries keep
+/// the strings holding them as received.
+pub fn parse(payload: &[u8], client: &str) -> Vec<Received> {
+    let arrival = Arrival::record(client, payload.len());
+    let (mut received, marked) = match std::str::from_utf8(payload) {
+        Ok(_) => (parse_payload(payload, client), vec![]),
+        Err(_) => (
+            parse_payload(encoding::decode(payload, true).as_bytes(), client),
+            parse_payload(encoding::decode(payload, false).as_bytes(), client),
+        ),
+    };
+    for (index, received) in received.iter_mut().enumerate() {
+        if let Received::Entry(entry) = received {
+            let mut arrival = arrival.clone();
+            if let Some(Received::Entry(marked)) = marked.get(index) {
+                arrival.invalid_utf8 = encoding::invalid_texts(entry, marked);
+            }
+            entry.arrival = Some(arrival);
+        }
+    }
+
+    received
+}
+
+fn parse_payload(payload: &[u8], client: &str) -> Vec<Received> {
+    let error = match serde_json::from_slice::<DebugEntry>(payload) {
+        Ok(entry) => return vec![Received::Entry(Box::new(entry))],
+        Err(error) => error,
+    };
+
+    let text = String::from_utf8_lossy(payload);
+    if vardumper::is_dump(&text) {
+        return vec![Received::Entry(Box::new(vardumper::to_entry(&text)))];
+    }
+
+    if let Some(entries) = chromelogger::to_entries(&text) {
+        return entries.into_iter().map(|entry| Received::Entry(Box::new(entry))).collect();
+    }
+
+    match generic::to_entry(payload) {
+        Some(entry) => vec![Received::Entry(Box::new(entry))],
+        None => vec![Received::ParseError(ParseError {
+            client: client.to_string(),
+            payload: text.into_owned(),
+            error,
+        })],
+    }
+}
+
+/// A red entry in place of a payload that was too large, with its size and start.
+fn too_large(client: &str, size: usize, max_size: usize, start: &[u8]) -> DebugEntry {
+    let mut data = HashMap::new();
+    data.insert(
+        String::from("payload_too_large"),
+        serde_json::json!({
+            "client": client,
+            "size": size,
+            "max_size": max_size,
+            "start": String::from_utf8_lossy(start),
+        }),
+    );
+
+    DebugEntry {
+        label: format!("Payload too large ({} bytes)", size).into(),
+        time: timestamp::now(),
+        data,
+        color: Some(String::from("red")),
+        arrival: Some(Arrival::record(client, size)),
+        ..DebugEntry::default()
+    }
+}
+
+/// Reads a payload per line, like newline delimited json piped into udl, calling `on_received` as
+/// `listen_bounded` does with `client` as the sender. Reading waits while the backlog is full, the
+/// writer then waits as well. Blank lines are skipped.
+pub async fn read_lines<R: io::BufRead + Unpin, F: FnMut(Received)>(
+    reader: R,
+    client: &str,
+    backlog: &Backlog,
+    mut on_received: F,
+) -> io::Result<()> {
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next().await {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        backlog.wait_for_room().await;
+        let received = parse(line.as_bytes(), client);
+        on_received(Received::Raw(RawPayload {
+            client: client.to_string(),
+            size: line.len(),
+            bytes: line.into_bytes(),
+            terminated: false,
+        }));
+        for received in received {
+            backlog.add();
+            on_received(received);
+        }
+    }
+
+    Ok(())
+}
+
+/// Listens on the address, calling `on_received` for every payload a client sends. A connection
+/// sends payloads terminated by a null byte, the last one by closing the connection as well, most
+/// clients send a single payload per connection. Connections are read at the same time. See
+/// `listen_bounded` for other framings and encodings.
+///
+/// A connection can start with a hello, like `{"hello": {"app": "checkout-service", "pid": 1234}}`,
+/// whose fields are attached to the entries sent over it after as their `connection`, naming the
+/// client by its `app`. A hello with a `protocol` version is answered with a welcome, see
+/// `protocol`.
+pub async fn listen<F: FnMut(Received)>(address: &str, on_received: F) -> io::Result<()> {
+    listen_bounded(address, &Backlog::unbounded(), DEFAULT_MAX_PAYLOAD_SIZE, &Access::default(), decoder::default(), on_received).await
+}
+
+/// Like `listen`, handing out no more entries and parse errors than the backlog has room for. The
+/// consumer marks them handled in the backlog. Diagnostics are not counted. Payloads larger than
+/// `max_payload_size` bytes are skipped without keeping them, and handed out as an entry saying so.
+/// Connections the access does not admit are closed right away. The decoder splits what clients
+/// send into payloads and decodes them into json.
+pub async fn listen_bounded<F: FnMut(Received)>(
+    address: &str,
+    backlog: &Backlog,
+    max_payload_size: usize,
+    access: &Access,
+    decoder: &'static dyn Decoder,
+    mut on_received: F,
+) -> io::Result<()> {
+    let listener: TcpListener = TcpListener::bind(address).await?;
+    if access.allowed.is_empty() && !listener.local_addr()?.ip().is_loopback() {
+        on_received(Received::Diagnostic(format!(
+            "Listening on {} without --allow, any client that can reach it can send entries",
+            address
+        )));
+    }
+    let (sender, receiver) = channel::unbounded::<io::Result<Received>>();
+    let accepting = backlog.clone();
+    let access = access.clone();
+    task::spawn(async move {
+        let mut incoming = listener.incoming();
+        loop {
+            // Not accepting connections makes clients wait, once the pending connections fill up.
+            if !accepting.drops_when_full() {
+                accepting.wait_for_room().await;
+            }
+            let stream = match incoming.next().await {
+                Some(Ok(stream)) => stream,
+                Some(Err(error)) => {
+                    let _ = sender.send(Err(error)).await;
+                    break;
+                }
+                None => break,
+            };
+            let address = stream.peer_addr().ok().map(|address| address.ip());
+            if let Some(reason) = access.admit(address) {
+                let client = address.map_or_else(|| String::from("unknown client"), |address| address.to_string());
+                let _ = sender.send(Ok(Received::Diagnostic(format!("Rejected connection from {}, {}", client, reason)))).await;
+                continue;
+            }
+            let (access, backlog, sender) = (access.clone(), accepting.clone(), sender.clone());
+            task::spawn(async move {
+                read_connection(stream, backlog, max_payload_size, decoder, sender).await;
+                access.close();
+            });
+        }
+    });
+
+    while let Ok(received) = receiver.recv().await {
+        let received = received?;
+        if let Received::Diagnostic(_) | Received::Raw(_) = received {
+            on_received(received);
+            continue;
+        }
+        // Waiting only happens between payloads, so a payload with many entries can go over.
+        if backlog.drops_when_full() && backlog.is_full() {
+            backlog.drop_entry();
+            let client = match &received {
+                Received::ParseError(error) => error.client.clone(),
+                _ => String::from("a client"),
+            };
+            on_received(Received::Diagnostic(format!("Dropped an entry from {}, the queue is full", client)));
+            continue;
+        }
+        backlog.add();
+        on_received(received);
+    }
+
+    Ok(())
+}
+
+// Reads the payloads of a connection until it is closed, sending what was received.
+async fn read_connection(
+    stream: TcpStream,
+    backlog: Backlog,
+    max_payload_size: usize,
+    decoder: &'static dyn Decoder,
+    sender: Sender<io::Result<Received>>,
+) {
+    let send = |received: Received| {
+        let sender = sender.clone();
+        async move { sender.send(Ok(received)).await.is_ok() }
+    };
+    let address = stream
+        .peer_addr()
+        .map_or_else(|_| String::from("unknown client"), |address| address.ip().to_string());
+    let mut client = address.clone();
+    send(Received::Diagnostic(format!("Accepted connection from {}", client))).await;
+
+    let mut reader = BufReader::new(&stream);
+    let mut hello: Option<Map<String, Value>> = None;
+    let mut first = true;
+    loop {
+        if !first && !backlog.drops_when_full() {
+            backlog.wait_for_room().await;
+        }
+        let (received, terminated) = match read(&mut reader, decoder.framing(), max_payload_size).await {
+            // What is left after the null byte of the last payload.
+            Ok(Payload::Complete(payload, false)) if !first && payload.iter().all(u8::is_ascii_whitespace) => break,
+            Ok(Payload::Complete(payload, terminated)) if payload.iter().all(u8::is_ascii_whitespace) => {
+                send(Received::Diagnostic(format!("Ignored an empty payload from {}", client))).await;
+                (vec![], terminated)
+            }
+            Ok(Payload::Complete(payload, terminated)) => {
+                send(Received::Diagnostic(format!("Read {} bytes from {}", payload.len(), client))).await;
+                let received = match decoder.decode(&payload) {
+                    Ok(json) => match hello_fields(&json) {
+                        Some(fields) => {
+                            if let Some(app) = fields.get("app").and_then(Value::as_str) {
+                                client = format!("{} ({})", app, address);
+                            }
+                            send(Received::Diagnostic(format!("Hello from {}: {}", client, Value::Object(fields.clone())))).await;
+                            if protocol::wants_welcome(&fields) {
+                                let welcome = protocol::welcome(&fields, decoder);
+                                // A client going away before reading it shows up when reading next.
+                                if let Err(error) = (&stream).write_all(&protocol::frame(&welcome, decoder.framing())).await {
+                                    send(Received::Diagnostic(format!("Could not welcome {}: {}", client, error))).await;
+                                }
+                            }
+                            hello = Some(fields);
+                            vec![]
+                        }
+                        None => parse_decoded(&json, payload.len(), &client),
+                    },
+                    Err(error) => vec![Received::ParseError(ParseError {
+                        client: client.clone(),
+                        payload: String::from_utf8_lossy(&payload).into_owned(),
+                        error: serde::de::Error::custom(error),
+                    })],
+                };
+                send(Received::Raw(RawPayload {
+                    client: client.clone(),
+                    size: payload.len(),
+                    bytes: payload,
+                    terminated,
+                }))
+                .await;
+                (received, terminated)
+            }
+            Ok(Payload::TooLarge { size, start, terminated }) => {
+                send(Received::Diagnostic(format!(
+                    "Skipped a payload of {} bytes from {}, the maximum is {}",
+                    size, client, max_payload_size
+                )))
+                .await;
+                let entry = too_large(&client, size, max_payload_size, &start);
+                send(Received::Raw(RawPayload {
+                    client: client.clone(),
+                    bytes: start,
+                    size,
+                    terminated,
+                }))
+                .await;
+                (vec![Received::Entry(Box::new(entry))], terminated)
+            }
+            Err(error) => {
+                send(Received::Diagnostic(format!("Could not read from {}: {}", client, error))).await;
+                break;
+            }
+        };
+        for mut received in received {
+            if let (Received::Entry(entry), Some(hello)) = (&mut received, &hello) {
+                if entry.connection.is_empty() {
+                    entry.connection = hello.clone();
+                }
+            }
+            if !send(received).await {
+                return;
+            }
+        }
+        if !terminated {
+            break;
+        }
+        first = false;
+    }
+    send(Received::Diagnostic(format!("Closed connection from {}", client))).await;
+}
+
+// Parses a payload decoded into json, counting the bytes as received for its entries.
+fn parse_decoded(json: &[u8], size: usize, client: &str) -> Vec<Received> {
+    let mut received = parse(json, client);
+    for received in &mut received {
+        if let Received::Entry(entry) = received {
+            if let Some(arrival) = &mut entry.arrival {
+                arrival.bytes = size;
+            }
+        }
+    }
+
+    received
+}
+
+// The fields of a hello, a payload like `{"hello": {"app": "checkout-service"}}`.
+fn hello_fields(payload: &[u8]) -> Option<Map<String, Value>> {
+    match serde_json::from_slice(payload).ok()? {
+        Value::Object(mut object) if object.len() == 1 => match object.remove("hello")? {
+            Value::Object(fields) => Some(fields),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+enum Payload {
+    // The bytes, and whether they ended with a null byte.
+    Complete(Vec<u8>, bool),
+    // The size of the whole payload, and its first bytes.
+    TooLarge { size: usize, start: Vec<u8>, terminated: bool },
+}
+
+async fn read(reader: &mut BufReader<&TcpStream>, framing: Framing, max_size: usize) -> io::Result<Payload> {
+    match framing {
+        Framing::Delimited(delimiter) => read_delimited(reader, delimiter, max_size).await,
+        Framing::LengthPrefixed => read_prefixed(reader, max_size).await,
+    }
+}
+
+async fn read_delimited(reader: &mut BufReader<&TcpStream>, delimiter: u8, max_size: usize) -> io::Result<Payload> {
+    let mut content: Vec<u8> = Vec::new();
+    let mut size = 0;
+
+    // Read what arrived up to the next delimiter, which is the end of the message and not part of
+    // it. The size is checked before keeping a chunk, so a message over the maximum is skipped as it
+    // comes in, only counting it and keeping its start. What comes after the delimiter is the next
+    // message and stays buffered.
+    let terminated = loop {
+        if reader.buffer().is_empty() && fill(reader).await? == 0 {
+            break false;
+        }
+        let buffer = reader.buffer();
+        let end = buffer.iter().position(|byte| *byte == delimiter);
+        let chunk = &buffer[..end.unwrap_or(buffer.len())];
+        if size + chunk.len() <= max_size {
+            // Growing as usual, but not past the maximum.
+            let capacity = (content.capacity() * 2).clamp(content.len() + chunk.len(), max_size);
+            content.reserve_exact(capacity - content.len());
+            content.extend_from_slice(chunk);
+        } else {
+            if size <= max_size {
+                content.truncate(PREVIEW_SIZE);
+                content.shrink_to_fit();
+            }
+            let keep = PREVIEW_SIZE.saturating_sub(content.len()).min(chunk.len());
+            content.extend_from_slice(&chunk[..keep]);
+        }
+        size += chunk.len();
+        let consumed = chunk.len() + usize::from(end.is_some());
+        Pin::new(&mut *reader).consume(consumed);
+        if end.is_some() {
+            break true;
+        }
+    };
+
+    if size > max_size {
+        return Ok(Payload::TooLarge { size, start: content, terminated });
+    }
+
+    Ok(Payload::Complete(content, terminated))
+}
+
+// Reads more into the buffer of the reader, returning how much it holds, none once the connection
+// is closed.
+async fn fill(reader: &mut BufReader<&TcpStream>) -> io::Result<usize> {
+    future::poll_fn(|context| Pin::new(&mut *reader).poll_fill_buf(context).map_ok(<[u8]>::len)).await
+}
+
+// Reads a message preceded by its size. A connection closed before the size is an empty message
+// that is not terminated, like with delimiters.
+async fn read_prefixed(reader: &mut BufReader<&TcpStream>, max_size: usize) -> io::Result<Payload> {
+    let mut size = [0; 4];
+    if reader.read(&mut size[..1]).await? == 0 {
+        return Ok(Payload::Complete(vec![], false));
+    }
+    reader.read_exact(&mut size[1..]).await?;
+    let size = u32::from_be_bytes(size) as usize;
+
+    if size <= max_size {
+        let mut content = vec![0; size];
+        reader.read_exact(&mut content).await?;
+        return Ok(Payload::Complete(content, true));
+    }
+
+    // Skip the rest of the message, keeping only its start.
+    let mut start = vec![0; size.min(PREVIEW_SIZE)];
+    reader.read_exact(&mut start).await?;
+    io::copy(&mut (&mut *reader).take((size - start.len()) as u64), &mut io::sink()).await?;
+
+    Ok(Payload::TooLarge { size, start, terminated: true })
+}
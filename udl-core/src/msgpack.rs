@@ -0,0 +1,97 @@
+//! MessagePack payloads as json values. Binary data becomes base64 text, and extension types an
+//! object with their `type` and base64 `data`, as json has neither.
+
+use crate::base64;
+use crate::decoder::{self, Reader, MAX_DEPTH};
+use serde_json::{Map, Value};
+
+/// The single value of the payload.
+pub fn decode(bytes: &[u8]) -> Result<Value, String> {
+    let mut reader = Reader::new(bytes);
+    let value = read(&mut reader, 0)?;
+    reader.finish()?;
+
+    Ok(value)
+}
+
+fn read(reader: &mut Reader, depth: usize) -> Result<Value, String> {
+    if depth > MAX_DEPTH {
+        return Err(String::from("nested too deep"));
+    }
+
+    let marker = reader.byte()?;
+    let value = match marker {
+        0x00..=0x7f => Value::from(marker),
+        0x80..=0x8f => map(reader, usize::from(marker & 0x0f), depth)?,
+        0x90..=0x9f => array(reader, usize::from(marker & 0x0f), depth)?,
+        0xa0..=0xbf => text(reader, usize::from(marker & 0x1f))?,
+        0xc0 => Value::Null,
+        0xc2 => Value::Bool(false),
+        0xc3 => Value::Bool(true),
+        0xc4..=0xc6 => {
+            let size = reader.number(1 << (marker - 0xc4))? as usize;
+            Value::String(base64::encode(reader.take(size)?))
+        }
+        0xc7..=0xc9 => {
+            let size = reader.number(1 << (marker - 0xc7))? as usize;
+            extension(reader, size)?
+        }
+        0xca => decoder::float(f64::from(f32::from_bits(reader.number(4)? as u32))),
+        0xcb => decoder::float(f64::from_bits(reader.number(8)?)),
+        0xcc..=0xcf => Value::from(reader.number(1 << (marker - 0xcc))?),
+        0xd0..=0xd3 => {
+            let size = 1 << (marker - 0xd0);
+            // Sign extends the number from its size.
+            let shift = 64 - size * 8;
+            Value::from((reader.number(size)? << shift) as i64 >> shift)
+        }
+        0xd4..=0xd8 => extension(reader, 1 << (marker - 0xd4))?,
+        0xd9..=0xdb => {
+            let size = reader.number(1 << (marker - 0xd9))? as usize;
+            text(reader, size)?
+        }
+        0xdc | 0xdd => {
+            let count = reader.number(2 << (marker - 0xdc))? as usize;
+            array(reader, count, depth)?
+        }
+        0xde | 0xdf => {
+            let count = reader.number(2 << (marker - 0xde))? as usize;
+            map(reader, count, depth)?
+        }
+        0xe0..=0xff => Value::from(marker as i8),
+        0xc1 => return Err(format!("unused marker 0xc1 at byte {}", reader.position - 1)),
+    };
+
+    Ok(value)
+}
+
+fn text(reader: &mut Reader, size: usize) -> Result<Value, String> {
+    Ok(Value::String(String::from_utf8_lossy(reader.take(size)?).into_owned()))
+}
+
+fn array(reader: &mut Reader, count: usize, depth: usize) -> Result<Value, String> {
+    // Not trusting the count for the capacity, every item takes at least a byte.
+    let mut items = Vec::with_capacity(count.min(1024));
+    for _ in 0..count {
+        items.push(read(reader, depth + 1)?);
+    }
+
+    Ok(Value::Array(items))
+}
+
+fn map(reader: &mut Reader, count: usize, depth: usize) -> Result<Value, String> {
+    let mut object = Map::new();
+    for _ in 0..count {
+        let key = decoder::key(read(reader, depth + 1)?);
+        object.insert(key, read(reader, depth + 1)?);
+    }
+
+    Ok(Value::Object(object))
+}
+
+fn extension(reader: &mut Reader, size: usize) -> Result<Value, String> {
+    let kind = reader.byte()? as i8;
+    let data = reader.take(size)?;
+
+    Ok(serde_json::json!({"type": kind, "data": base64::encode(data)}))
+}
@@ -0,0 +1,71 @@
+//! The handshake of the wire protocol. A client that names the protocol version it speaks in its
+//! hello, like `{"hello": {"app": "checkout-service", "protocol": 1}}`, gets a welcome back on the
+//! connection saying which version this udl speaks and what it supports, so client libraries can
+//! leave out what an older udl would not understand. Hellos without a `protocol` get no reply, and
+//! udl versions from before the handshake never reply at all.
+
+use crate::decoder::{Decoder, Framing, DECODERS};
+use serde_json::{json, Map, Value};
+
+/// Version of the wire protocol, raised whenever what clients can send changes.
+pub const VERSION: u64 = 1;
+
+/// What clients can send besides plain entries, named after the fields or payloads doing it.
+pub const CAPABILITIES: [&str; 14] = [
+    "hello",
+    "sections",
+    "groups",
+    "tags",
+    "colors",
+    "refs",
+    "updates",
+    "measurements",
+    "metrics",
+    "progress",
+    "binary",
+    "vardumper",
+    "chromelogger",
+    "json-objects",
+];
+
+/// Whether the fields of a hello ask for a welcome.
+pub fn wants_welcome(hello: &Map<String, Value>) -> bool {
+    hello.contains_key("protocol")
+}
+
+/// The welcome answering a hello, like
+/// `{"udl": {"protocol": 1, "version": "0.1.0", "capabilities": ["hello", …], "decoder": "json", …}}`.
+/// `protocol` is the lower of the versions of the client and udl, the one to speak from then on.
+pub fn welcome(hello: &Map<String, Value>, decoder: &dyn Decoder) -> Value {
+    let protocol = hello.get("protocol").and_then(Value::as_u64).map_or(VERSION, |version| version.min(VERSION));
+    let decoders: Vec<&str> = DECODERS.iter().map(|decoder| decoder.name()).collect();
+
+    json!({
+        "udl": {
+            "protocol": protocol,
+            "latest_protocol": VERSION,
+            "version": env!("CARGO_PKG_VERSION"),
+            "capabilities": CAPABILITIES,
+            "decoder": decoder.name(),
+            "decoders": decoders,
+        }
+    })
+}
+
+/// The welcome as json, framed like the payloads of the decoder: followed by its delimiter, or
+/// preceded by its size as a 4 byte big endian number. It is json for the binary encodings too.
+pub fn frame(welcome: &Value, framing: Framing) -> Vec<u8> {
+    let json = welcome.to_string().into_bytes();
+    match framing {
+        Framing::Delimited(delimiter) => {
+            let mut bytes = json;
+            bytes.push(delimiter);
+            bytes
+        }
+        Framing::LengthPrefixed => {
+            let mut bytes = (json.len() as u32).to_be_bytes().to_vec();
+            bytes.extend_from_slice(&json);
+            bytes
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use crate::entry::DebugEntry;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::Path;
+
+/// A saved session, holding the entries in order of arrival including their pins and notes.
+#[derive(Deserialize)]
+struct Session {
+    entries: Vec<DebugEntry>,
+}
+
+#[derive(Serialize)]
+struct SessionRef<'a> {
+    entries: Vec<&'a DebugEntry>,
+}
+
+/// Saves the entries, which are given newest first as they are listed.
+pub fn save(path: &Path, items: &[DebugEntry]) -> Result<(), Box<dyn Error>> {
+    let session = SessionRef {
+        entries: items.iter().rev().collect(),
+    };
+    serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), &session)?;
+
+    Ok(())
+}
+
+/// Loads the entries of a session in order of arrival.
+pub fn load(path: &Path) -> Result<Vec<DebugEntry>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let session: Session = serde_json::from_str(&content)
+        .map_err(|error| format!("Invalid session file {}: {}", path.display(), error))?;
+
+    Ok(session.entries)
+}
+
+/// Loads entries from either a saved session or a file with a json entry per line.
+pub fn load_entries(path: &Path) -> Result<Vec<DebugEntry>, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    if let Ok(session) = serde_json::from_str::<Session>(&content) {
+        return Ok(session.entries);
+    }
+
+    let mut entries: Vec<DebugEntry> = vec![];
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(line)
+            .map_err(|error| format!("Invalid entry on line {} of {}: {}", index + 1, path.display(), error))?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,82 @@
+use crate::entry::DebugEntry;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// On disk store for entries evicted from memory. It keeps the offset of every line so older
+/// entries can be paged back in without reading the whole file.
+pub struct Spill {
+    path: PathBuf,
+    file: File,
+    offsets: Vec<u64>,
+    end: u64,
+}
+
+impl Spill {
+    pub fn open(path: &Path) -> io::Result<Spill> {
+        let file = OpenOptions::new().create(true).append(true).read(true).open(path)?;
+
+        // Index the lines already in the file.
+        let mut offsets: Vec<u64> = vec![];
+        let mut end: u64 = 0;
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut line: Vec<u8> = vec![];
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)? as u64;
+            if read == 0 {
+                break;
+            }
+            if line.iter().any(|byte| !byte.is_ascii_whitespace()) {
+                offsets.push(end);
+            }
+            end += read;
+        }
+
+        Ok(Spill {
+            path: path.to_path_buf(),
+            file,
+            offsets,
+            end,
+        })
+    }
+
+    /// Number of entries in the file.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    pub fn append(&mut self, entry: &DebugEntry) -> io::Result<()> {
+        let line = format!("{}\n", serde_json::to_string(entry)?);
+        self.file.write_all(line.as_bytes())?;
+        self.offsets.push(self.end);
+        self.end += line.len() as u64;
+
+        Ok(())
+    }
+
+    /// Reads the entries from `start` up to `end`, in the order they were spilled.
+    pub fn read(&self, start: usize, end: usize) -> io::Result<Vec<DebugEntry>> {
+        let end = end.min(self.offsets.len());
+        if start >= end {
+            return Ok(vec![]);
+        }
+
+        let from = self.offsets[start];
+        let to = self.offsets.get(end).copied().unwrap_or(self.end);
+        let mut file = File::open(&self.path)?;
+        file.seek(SeekFrom::Start(from))?;
+        let mut content = String::new();
+        file.take(to - from).read_to_string(&mut content)?;
+
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+            .collect()
+    }
+}
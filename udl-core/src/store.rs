@@ -0,0 +1,88 @@
+use crate::entry::DebugEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+/// Append only store keeping every received entry as a line of json, so a session survives
+/// restarting udl. Pins, notes and tags added from the UI are appended as annotation lines
+/// whenever they change, the last one for an entry wins.
+pub struct Store {
+    file: File,
+}
+
+/// The pin, note and tags of a stored entry, by its id.
+#[derive(Deserialize, Serialize)]
+struct Annotation {
+    id: u64,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    pinned: bool,
+}
+
+#[derive(Deserialize, Serialize)]
+struct AnnotationLine {
+    annotation: Annotation,
+}
+
+impl Store {
+    /// Opens or creates the store, returning the entries it already contains in order of arrival
+    /// with their latest annotations. Lines that cannot be parsed, like a partially written last
+    /// line, are skipped.
+    pub fn open(path: &Path) -> io::Result<(Store, Vec<DebugEntry>)> {
+        let mut entries: Vec<DebugEntry> = vec![];
+        let mut positions: HashMap<u64, usize> = HashMap::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if let Ok(entry) = serde_json::from_str::<DebugEntry>(&line) {
+                    // An entry stored again after it was updated replaces the earlier version.
+                    match positions.get(&entry.id).filter(|_| entry.id > 0) {
+                        Some(position) => entries[*position] = entry,
+                        None => {
+                            positions.insert(entry.id, entries.len());
+                            entries.push(entry);
+                        }
+                    }
+                } else if let Ok(AnnotationLine { annotation }) = serde_json::from_str(&line) {
+                    if let Some(entry) = positions.get(&annotation.id).map(|position| &mut entries[*position]) {
+                        entry.tags = annotation.tags;
+                        entry.note = annotation.note;
+                        entry.pinned = annotation.pinned;
+                    }
+                }
+            }
+        }
+
+        Ok((Store::append_to(path)?, entries))
+    }
+
+    /// Opens or creates the store without reading the entries it contains.
+    pub fn append_to(path: &Path) -> io::Result<Store> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Store { file })
+    }
+
+    pub fn append(&mut self, entry: &DebugEntry) -> io::Result<()> {
+        let line = serde_json::to_string(entry)?;
+        writeln!(self.file, "{}", line)
+    }
+
+    /// Appends the current pin, note and tags of the entry, to be restored when opened again.
+    pub fn annotate(&mut self, entry: &DebugEntry) -> io::Result<()> {
+        let line = serde_json::to_string(&AnnotationLine {
+            annotation: Annotation {
+                id: entry.id,
+                tags: entry.tags.clone(),
+                note: entry.note.clone(),
+                pinned: entry.pinned,
+            },
+        })?;
+        writeln!(self.file, "{}", line)
+    }
+}
@@ -0,0 +1,264 @@
+//! Following a log file, for applications that write structured logs but cannot send entries over
+//! tcp. Lines appended to the file are parsed by the format of the log and handed out like the
+//! payloads of a connection.
+
+use crate::backlog::Backlog;
+use crate::entry::{Arrival, BacktraceItem, DebugEntry};
+use crate::listener::{self, RawPayload, Received};
+use crate::timestamp;
+use async_std::{fs::File, io, prelude::*, task};
+use serde_json::{Deserializer, Value};
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::time::Duration;
+
+/// How often the file is checked for new lines.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Labels are cut off after this many characters.
+const MAX_LABEL_LENGTH: usize = 100;
+
+/// How the lines of a followed file are turned into entries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineFormat {
+    /// A json payload per line, parsed like the payloads of a connection.
+    Json,
+    /// Laravel (Monolog) logs, like `[2024-01-01 12:00:00] local.ERROR: message {"context":1} []`,
+    /// with the stack trace of exceptions on the lines after it.
+    Laravel,
+    /// Every line is an entry labeled with the line.
+    Plain,
+}
+
+impl LineFormat {
+    pub fn named(name: &str) -> Option<LineFormat> {
+        match name {
+            "json" => Some(LineFormat::Json),
+            "laravel" | "monolog" => Some(LineFormat::Laravel),
+            "plain" => Some(LineFormat::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// Follows the file like `tail -f`, starting at its end, calling `on_received` for every record
+/// appended to it with the name of the file as the client. Starts from the beginning again when the
+/// file is truncated or replaced by a smaller one, like after log rotation. Only returns on errors.
+pub async fn follow<F: FnMut(Received)>(
+    path: &Path,
+    format: LineFormat,
+    backlog: &Backlog,
+    mut on_received: F,
+) -> io::Result<()> {
+    let client = path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned());
+    let mut offset = async_std::fs::metadata(path).await?.len();
+    // Bytes after the last line break, a line still being written.
+    let mut partial: Vec<u8> = vec![];
+    // Lines of the record being read, for formats where a record can span lines.
+    let mut record: Vec<String> = vec![];
+    on_received(Received::Diagnostic(format!("Following {}", path.display())));
+
+    loop {
+        let length = match async_std::fs::metadata(path).await {
+            Ok(metadata) => metadata.len(),
+            // Gone for a moment while the log is rotated.
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                task::sleep(POLL_INTERVAL).await;
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        if length < offset {
+            on_received(Received::Diagnostic(format!("{} was truncated, reading it from the start", path.display())));
+            offset = 0;
+            partial.clear();
+        }
+        if length == offset {
+            // Nothing was added, so the record read last is complete.
+            if !record.is_empty() {
+                emit(&record.join("\n"), format, &client, backlog, &mut on_received).await;
+                record.clear();
+            }
+            task::sleep(POLL_INTERVAL).await;
+            continue;
+        }
+
+        let mut file = File::open(path).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut bytes = vec![];
+        file.take(length - offset).read_to_end(&mut bytes).await?;
+        offset += bytes.len() as u64;
+        partial.extend(bytes);
+        let complete = match partial.iter().rposition(|byte| *byte == b'\n') {
+            Some(position) => partial.drain(..=position).collect::<Vec<u8>>(),
+            None => continue,
+        };
+
+        for line in String::from_utf8_lossy(&complete).lines() {
+            let line = line.trim_end_matches('\r');
+            match format {
+                LineFormat::Laravel => {
+                    if is_laravel_header(line) && !record.is_empty() {
+                        emit(&record.join("\n"), format, &client, backlog, &mut on_received).await;
+                        record.clear();
+                    }
+                    // Lines before the first record, the rest of a record written before following.
+                    if is_laravel_header(line) || !record.is_empty() {
+                        record.push(line.to_string());
+                    }
+                }
+                _ if line.trim().is_empty() => {}
+                _ => emit(line, format, &client, backlog, &mut on_received).await,
+            }
+        }
+    }
+}
+
+async fn emit<F: FnMut(Received)>(text: &str, format: LineFormat, client: &str, backlog: &Backlog, on_received: &mut F) {
+    backlog.wait_for_room().await;
+    let received = match format {
+        LineFormat::Json => listener::parse(text.as_bytes(), client),
+        LineFormat::Laravel => vec![arrived(laravel_entry(text), client, text)],
+        LineFormat::Plain => vec![arrived(plain_entry(text), client, text)],
+    };
+    on_received(Received::Raw(RawPayload {
+        client: client.to_string(),
+        bytes: text.as_bytes().to_vec(),
+        size: text.len(),
+        terminated: false,
+    }));
+    for received in received {
+        backlog.add();
+        on_received(received);
+    }
+}
+
+fn arrived(entry: DebugEntry, client: &str, text: &str) -> Received {
+    Received::Entry(Box::new(DebugEntry {
+        arrival: Some(Arrival::record(client, text.len())),
+        ..entry
+    }))
+}
+
+fn label(text: &str) -> String {
+    text.lines().next().unwrap_or("").trim().chars().take(MAX_LABEL_LENGTH).collect()
+}
+
+fn plain_entry(line: &str) -> DebugEntry {
+    let mut data = HashMap::new();
+    data.insert(String::from("line"), Value::String(line.to_string()));
+
+    DebugEntry {
+        label: label(line).into(),
+        time: timestamp::now(),
+        data,
+        ..DebugEntry::default()
+    }
+}
+
+// Whether the line starts a record, `[time] channel.LEVEL: `.
+fn is_laravel_header(line: &str) -> bool {
+    laravel_header(line).is_some()
+}
+
+// The time, channel, level and message of the first line of a record.
+fn laravel_header(line: &str) -> Option<(&str, &str, &str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (time, rest) = rest.split_once("] ")?;
+    timestamp::parse(time)?;
+    let (name, message) = rest.split_once(": ")?;
+    let (channel, level) = name.rsplit_once('.')?;
+    if channel.contains(' ') || level.is_empty() || !level.chars().all(|c| c.is_ascii_uppercase()) {
+        return None;
+    }
+
+    Some((time, channel, level, message))
+}
+
+fn laravel_entry(record: &str) -> DebugEntry {
+    let (first, continued) = record.split_once('\n').unwrap_or((record, ""));
+    let (time, channel, level, message) = laravel_header(first).unwrap_or(("", "", "", first));
+    let text = match continued {
+        "" => message.to_string(),
+        continued => format!("{}\n{}", message, continued),
+    };
+    // Exceptions break the json of the context over several lines, within its strings.
+    let escaped = text.replace('\n', "\\n");
+    let (message, context, extra) = match split_context(&escaped) {
+        Some((position, context, extra)) => (escaped[..position].replace("\\n", "\n"), context, extra),
+        None => (text, Value::Null, Value::Null),
+    };
+
+    let mut data = HashMap::new();
+    data.insert(String::from("level"), Value::String(level.to_lowercase()));
+    data.insert(String::from("channel"), Value::String(channel.to_string()));
+    data.insert(String::from("message"), Value::String(message.trim_end().to_string()));
+    for (key, value) in [("context", context), ("extra", extra)] {
+        if !is_empty(&value) {
+            data.insert(String::from(key), value);
+        }
+    }
+
+    DebugEntry {
+        label: label(&message).into(),
+        time: match time {
+            "" => timestamp::now(),
+            time => time.to_string(),
+        },
+        data,
+        backtrace: record.lines().filter_map(frame).collect(),
+        tags: vec![level.to_lowercase()],
+        ..DebugEntry::default()
+    }
+}
+
+// The start of the context and extra json at the end of a message, and their values. Laravel leaves
+// out the extra when it is empty.
+fn split_context(text: &str) -> Option<(usize, Value, Value)> {
+    let mut positions: Vec<usize> = text
+        .match_indices(" {")
+        .chain(text.match_indices(" ["))
+        .map(|(position, _)| position)
+        .collect();
+    positions.sort_unstable();
+
+    positions.into_iter().find_map(|position| {
+        let mut values = Deserializer::from_str(&text[position..]).into_iter::<Value>();
+        let context = values.next()?.ok()?;
+        let extra = match values.next() {
+            Some(extra) => extra.ok()?,
+            None => Value::Null,
+        };
+        if !text[position + values.byte_offset()..].trim().is_empty() {
+            return None;
+        }
+
+        Some((position, context, extra))
+    })
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(items) => items.is_empty(),
+        Value::Object(object) => object.is_empty(),
+        _ => false,
+    }
+}
+
+// A frame of a php stack trace, like `#0 /app/Http/Controller.php(54): App\Foo->bar()`. Within the
+// json of the context the backslashes of namespaces are escaped.
+fn frame(line: &str) -> Option<BacktraceItem> {
+    let rest = line.strip_prefix('#')?;
+    let (_, rest) = rest.split_once(' ')?;
+    let (location, function) = rest.split_once(": ")?;
+    let (file, line) = location.strip_suffix(')')?.rsplit_once('(')?;
+
+    Some(BacktraceItem {
+        file: file.into(),
+        line: line.parse().ok()?,
+        column: None,
+        function: function.replace("\\\\", "\\"),
+    })
+}
@@ -0,0 +1,123 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Parses a time into seconds since the unix epoch in UTC. Accepted are `YYYY-MM-DD HH:MM:SS`, as
+/// Laravel logs it, and ISO 8601 with a `T` separator, both with optional fractional seconds and a
+/// time zone like `Z`, `UTC`, `+02:00` or `-0500`, and unix timestamps in seconds or milliseconds.
+/// Times without a time zone are taken as UTC.
+pub fn parse(time: &str) -> Option<f64> {
+    let time = time.trim().trim_start_matches('[').trim_end_matches(']');
+    if let Ok(epoch) = time.parse::<f64>() {
+        // Milliseconds go past the year 5000 when taken as seconds.
+        return Some(if epoch.abs() >= 1e11 { epoch / 1000.0 } else { epoch });
+    }
+
+    let (time, offset) = split_offset(time)?;
+    let (date, clock) = time.split_at(time.find([' ', 'T'])?);
+    let clock = &clock[1..];
+
+    let mut date_parts = date.split('-').map(|part| part.parse::<i64>().ok());
+    let year = date_parts.next()??;
+    let month = date_parts.next()??;
+    let day = date_parts.next()??;
+
+    let mut clock_parts = clock.split(':');
+    let hours: i64 = clock_parts.next()?.parse().ok()?;
+    let minutes: i64 = clock_parts.next()?.parse().ok()?;
+    let seconds: f64 = clock_parts.next().unwrap_or("0").parse().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    Some((days * 86400 + hours * 3600 + minutes * 60 - offset) as f64 + seconds)
+}
+
+/// Seconds the time zone of a time is ahead of UTC, 0 for times without one and unix timestamps.
+pub fn offset(time: &str) -> i64 {
+    let time = time.trim().trim_start_matches('[').trim_end_matches(']');
+    if time.parse::<f64>().is_ok() {
+        return 0;
+    }
+
+    split_offset(time).map_or(0, |(_, offset)| offset)
+}
+
+// The time without its time zone, and the seconds the time zone is ahead of UTC.
+fn split_offset(time: &str) -> Option<(&str, i64)> {
+    if let Some(time) = time.strip_suffix('Z').or_else(|| time.strip_suffix(" UTC")) {
+        return Some((time.trim_end(), 0));
+    }
+
+    // A sign after the clock, which starts after the date separator.
+    let clock_start = time.find([' ', 'T'])?;
+    let sign = match time[clock_start..].rfind(['+', '-']) {
+        Some(index) => clock_start + index,
+        None => return Some((time, 0)),
+    };
+    let digits: String = time[sign + 1..].chars().filter(|c| *c != ':').collect();
+    if !(digits.len() == 2 || digits.len() == 4) || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits.get(2..).filter(|minutes| !minutes.is_empty()).map_or(Some(0), |minutes| minutes.parse().ok())?;
+    let offset = hours * 3600 + minutes * 60;
+
+    Some((time[..sign].trim_end(), if &time[sign..sign + 1] == "-" { -offset } else { offset }))
+}
+
+/// The current UTC time as `YYYY-MM-DD HH:MM:SS`.
+pub fn now() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    format(seconds)
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD HH:MM:SS.mmm`, for the time zone the given
+/// seconds ahead of UTC.
+pub fn format_millis(seconds: f64, offset: i64) -> String {
+    let millis = (seconds * 1000.0).round() as i64 + offset * 1000;
+    format!("{}.{:03}", format(millis.div_euclid(1000)), millis.rem_euclid(1000))
+}
+
+/// Formats seconds since the unix epoch as `YYYY-MM-DD HH:MM:SS` in UTC.
+pub fn format(seconds: i64) -> String {
+    let (days, rest) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+// Days since 1970-01-01 for a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+// Year, month and day for a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = if days >= 0 { days } else { days - 146096 } / 146097;
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
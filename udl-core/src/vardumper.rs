@@ -0,0 +1,129 @@
+//! Dumps of Symfony's VarDumper, as written by its `HtmlDumper`. They are accepted as payloads so
+//! `dump()` output can be sent to udl instead of `var-dump-server`.
+
+use crate::entry::DebugEntry;
+use crate::timestamp;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Longest label taken from the first line of a dump.
+const MAX_LABEL_LENGTH: usize = 60;
+
+/// Whether the payload is an html dump rather than json.
+pub fn is_dump(payload: &str) -> bool {
+    let payload = payload.trim_start();
+    payload.starts_with('<') && payload.contains("sf-dump")
+}
+
+/// An entry holding the dump as html, for styling it, and as plain text, for filtering and
+/// exporting. The label is the first line of the dump, like `array:2 [`.
+pub fn to_entry(payload: &str) -> DebugEntry {
+    let text: String = segments(payload).into_iter().map(|(_, text)| text).collect();
+    let text = text.trim().to_string();
+    let first_line = text.lines().next().unwrap_or_default();
+    let mut label = format!("dump {}", first_line.chars().take(MAX_LABEL_LENGTH).collect::<String>());
+    if first_line.chars().count() > MAX_LABEL_LENGTH {
+        label.push('…');
+    }
+
+    let mut data = HashMap::new();
+    data.insert(String::from("vardumper"), json!({ "html": payload, "text": text }));
+
+    DebugEntry {
+        label: label.into(),
+        time: timestamp::now(),
+        data,
+        ..DebugEntry::default()
+    }
+}
+
+/// Splits the html of a dump into texts with the VarDumper class they are shown with, like
+/// `sf-dump-str` or `sf-dump-note`. Scripts and styles are left out and entities decoded.
+pub fn segments(html: &str) -> Vec<(Option<String>, String)> {
+    let mut segments: Vec<(Option<String>, String)> = vec![];
+    let mut classes: Vec<Option<String>> = vec![];
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        let end = rest.find('<').unwrap_or(rest.len());
+        if end > 0 {
+            let class = classes.iter().rev().flatten().next().cloned();
+            segments.push((class, decode_entities(&rest[..end])));
+        }
+        rest = &rest[end..];
+        if rest.is_empty() {
+            break;
+        }
+
+        let tag_end = rest.find('>').map_or(rest.len(), |index| index + 1);
+        let tag = &rest[1..tag_end.saturating_sub(1).max(1)];
+        rest = &rest[tag_end..];
+
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .find(|part| !part.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+        match name.as_str() {
+            "script" | "style" if !tag.starts_with('/') => {
+                let closing = format!("</{}", name);
+                rest = rest.find(&closing).map_or("", |index| &rest[index..]);
+                rest = rest.find('>').map_or("", |index| &rest[index + 1..]);
+            }
+            "span" | "a" | "abbr" if tag.starts_with('/') => {
+                classes.pop();
+            }
+            "span" | "a" | "abbr" if !tag.ends_with('/') => classes.push(class_of(tag)),
+            _ => {}
+        }
+    }
+
+    segments
+}
+
+// The `class` attribute of a tag, quoted or not.
+fn class_of(tag: &str) -> Option<String> {
+    let start = tag.find("class=")? + "class=".len();
+    let value = &tag[start..];
+    let value = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value.split(|c: char| c.is_whitespace() || c == '>').next()?,
+    };
+
+    Some(value.to_string())
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').filter(|end| *end <= 10).map(|end| &rest[1..end]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ => match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok().and_then(char::from_u32),
+                None => entity.strip_prefix('#').and_then(|number| number.parse().ok()).and_then(char::from_u32),
+            },
+        });
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}